@@ -1,14 +1,17 @@
-use super::Result;
+use super::{ClientError, Result};
 
 use reqwest::Client;
-use serde::{de, Deserialize, Deserializer, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
-use std::{collections::HashMap, fmt::Debug};
+use std::{collections::HashMap, fmt::Debug, time::Duration};
 
 pub const APP_PAYLOAD_URL: &str =
     "http://hometest.buddytv.netdna-cdn.com/appservice/app_availability_prod.json";
 pub const APP_NAME_URL: &str =
     "http://hometest.buddytv.netdna-cdn.com/appservice/vizio_apps_prod.json";
+/// Default seconds to wait for the catalog, which is slower to download than a device is to
+/// respond
+pub const DEFAULT_CATALOG_TIMEOUT: u64 = 15;
 
 #[derive(Clone)]
 /// Various information about an App
@@ -17,7 +20,7 @@ pub struct App {
     description: String,
     image_url: String,
     id: String,
-    payload: Option<Payload>,
+    payload: Option<AppPayload>,
 }
 
 impl App {
@@ -35,6 +38,10 @@ impl App {
     pub fn image_url(&self) -> String {
         self.image_url.clone()
     }
+
+    pub(super) fn payload(&self) -> Option<&AppPayload> {
+        self.payload.as_ref()
+    }
 }
 
 impl Debug for App {
@@ -81,22 +88,60 @@ impl<'de> Deserialize<'de> for App {
 #[derive(Debug)]
 /// Struct used to facilitate populating app info
 pub(super) struct AppList {
-    payloads: HashMap<String, Payload>,
+    payloads: HashMap<String, AppPayload>,
     apps: HashMap<String, App>,
     client: Client,
+    payload_url: String,
+    name_url: String,
+    timeout: Duration,
+    disabled: bool,
 }
 
 impl AppList {
-    pub fn new(client: Client) -> Self {
-        Self {
+    pub fn new() -> Result<Self> {
+        Self::with_urls(APP_PAYLOAD_URL, APP_NAME_URL)
+    }
+
+    pub(super) fn with_urls<S: Into<String>>(payload_url: S, name_url: S) -> Result<Self> {
+        // The catalog is sizeable json served from a CDN so unlike the device's client, accept
+        // compressed responses
+        let client = Client::builder()
+            .gzip(true)
+            .brotli(true)
+            .deflate(true)
+            .build()?;
+
+        Ok(Self {
             payloads: HashMap::new(),
             apps: HashMap::new(),
             client,
-        }
+            payload_url: payload_url.into(),
+            name_url: name_url.into(),
+            timeout: Duration::from_secs(DEFAULT_CATALOG_TIMEOUT),
+            disabled: false,
+        })
+    }
+
+    /// Never fetch the catalog, [`get_app()`](Self::get_app) will return an error instead
+    pub fn disable(&mut self) {
+        self.disabled = true;
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        self.disabled
+    }
+
+    /// Wait at most `timeout` for each download of the catalog
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
     }
 
     /// Get app by payload
-    pub async fn get_app(&mut self, payload: Payload) -> Result<Option<App>> {
+    pub async fn get_app(&mut self, payload: AppPayload) -> Result<Option<App>> {
+        if self.disabled {
+            return Err(ClientError::AppCatalogDisabled.into());
+        }
+
         if self.payloads.is_empty() {
             self.update().await?;
         }
@@ -104,74 +149,498 @@ impl AppList {
         Ok(self
             .apps
             .values()
-            .find_map(|app| match &app.payload {
-                Some(pl) if pl == &payload => Some(app),
-                _ => None,
-            })
+            .find(|app| matches!(&app.payload, Some(pl) if pl == &payload))
             .cloned())
     }
 
+    /// Get every app in the catalog which can be launched, sorted by name
+    pub async fn apps(&mut self) -> Result<Vec<App>> {
+        if self.disabled {
+            return Err(ClientError::AppCatalogDisabled.into());
+        }
+
+        if self.payloads.is_empty() {
+            self.update().await?;
+        }
+
+        let mut apps: Vec<App> = self
+            .apps
+            .values()
+            .filter(|app| app.payload.is_some())
+            .cloned()
+            .collect();
+        apps.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(apps)
+    }
+
     /// Update payloads and app descriptions
+    ///
+    /// Nothing is kept unless both downloads succeed, so dropping the future part way through
+    /// leaves the list as it was and the next call starts over.
     pub async fn update(&mut self) -> Result<()> {
-        self.fetch_payloads().await?;
-        self.fetch_app_info().await?;
+        let payloads = self.fetch_payloads().await?;
+        let apps = self.fetch_app_info(&payloads).await?;
+        self.payloads = payloads;
+        self.apps = apps;
         Ok(())
     }
 
     /// Get payloads from online source
-    async fn fetch_payloads(&mut self) -> Result<()> {
+    async fn fetch_payloads(&self) -> Result<HashMap<String, AppPayload>> {
         let payloads: Vec<Value> = self
             .client
-            .get(APP_PAYLOAD_URL)
+            .get(&self.payload_url)
+            .timeout(self.timeout)
             .send()
             .await?
             .json()
             .await?;
+        let mut map = HashMap::new();
         for p in payloads.iter() {
             let info = p["chipsets"]["*"][0].clone();
 
             let id: String = serde_json::from_value(p["id"].clone())?;
-            let payload: Payload = if let Some(payload_str) = info["app_type_payload"].as_str() {
+            let payload: AppPayload = if let Some(payload_str) = info["app_type_payload"].as_str() {
                 serde_json::from_str(payload_str)?
             } else {
                 serde_json::from_value(info["app_type_payload"].clone())?
             };
 
-            self.payloads.insert(id, payload);
+            map.insert(id, payload);
         }
 
-        Ok(())
+        Ok(map)
     }
 
     /// Get app info from online source
-    async fn fetch_app_info(&mut self) -> Result<()> {
-        if self.payloads.is_empty() {
-            self.fetch_payloads().await?;
-        }
-
-        let mut apps: Vec<App> = self.client.get(APP_NAME_URL).send().await?.json().await?;
-        self.apps = apps.iter_mut().fold(HashMap::new(), |mut map, mut app| {
-            app.payload = self.payloads.get(&app.id).cloned();
+    async fn fetch_app_info(
+        &self,
+        payloads: &HashMap<String, AppPayload>,
+    ) -> Result<HashMap<String, App>> {
+        let mut apps: Vec<App> = self
+            .client
+            .get(&self.name_url)
+            .timeout(self.timeout)
+            .send()
+            .await?
+            .json()
+            .await?;
+        Ok(apps.iter_mut().fold(HashMap::new(), |mut map, app| {
+            app.payload = payloads.get(&app.id).cloned();
             map.insert(app.id.clone(), app.clone());
             map
-        });
+        }))
+    }
+}
 
-        Ok(())
+/// Find the app best matching `name`, ignoring case
+///
+/// An app with exactly the name is the best match, then the only app whose name starts with
+/// `name`, then the only app whose name contains it. If several apps match equally well, the
+/// names of all of them are given in [`ClientError::AmbiguousAppName`]. If none match, apps
+/// which share a word with `name` or are spelled close to it are given in
+/// [`ClientError::AppNotFound`].
+pub(super) fn find_by_name(apps: Vec<App>, name: &str) -> Result<App> {
+    let query = name.trim().to_lowercase();
+
+    // Lower is a better match
+    let rank = |app: &App| {
+        let app_name = app.name.to_lowercase();
+        if app_name == query {
+            Some(0)
+        } else if app_name.starts_with(&query) {
+            Some(1)
+        } else if app_name.contains(&query) {
+            Some(2)
+        } else {
+            None
+        }
+    };
+
+    let best = apps.iter().filter_map(rank).min();
+    let mut found: Vec<App> = apps
+        .iter()
+        .filter(|app| best.is_some() && rank(app) == best)
+        .cloned()
+        .collect();
+    match found.len() {
+        1 => Ok(found.remove(0)),
+        0 => {
+            let words: Vec<&str> = query.split_whitespace().collect();
+            // Allow about one typo for every three letters
+            let max_distance = (query.chars().count() / 3).max(1);
+            let near = apps
+                .iter()
+                .filter(|app| {
+                    let app_name = app.name.to_lowercase();
+                    edit_distance(&app_name, &query) <= max_distance
+                        || app_name.split_whitespace().any(|word| {
+                            words.contains(&word) || edit_distance(word, &query) <= max_distance
+                        })
+                })
+                .map(App::name)
+                .collect();
+            Err(ClientError::AppNotFound(name.into(), near).into())
+        }
+        _ => Err(
+            ClientError::AmbiguousAppName(name.into(), found.iter().map(App::name).collect())
+                .into(),
+        ),
     }
 }
 
+/// Number of single character insertions, deletions and substitutions to turn `a` into `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// What the device reports for the app it is running, see
+/// [`current_app_payload()`](super::Device::current_app_payload)
+///
+/// Apps in the catalog are identified by this payload. Its [`Display`](std::fmt::Display) is the
+/// json the catalog has for the app's `app_type_payload`, for reporting an app which is missing.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-pub(super) struct Payload {
+pub struct AppPayload {
     name_space: u32,
     app_id: String,
-    #[serde(deserialize_with = "null_string")]
+    #[serde(deserialize_with = "null_string", serialize_with = "empty_null")]
     message: String,
 }
 
+impl AppPayload {
+    /// Get the `NAME_SPACE` of the payload
+    pub fn name_space(&self) -> u32 {
+        self.name_space
+    }
+
+    /// Get the `APP_ID` of the payload
+    pub fn app_id(&self) -> String {
+        self.app_id.clone()
+    }
+
+    /// Get the `MESSAGE` of the payload, which is empty if there is none
+    pub fn message(&self) -> String {
+        self.message.clone()
+    }
+}
+
+impl std::fmt::Display for AppPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match serde_json::to_string(self) {
+            Ok(json) => write!(f, "{}", json),
+            Err(_) => Err(std::fmt::Error),
+        }
+    }
+}
+
+/// Launching an app without a message expects `null`, as the catalog has it
+fn empty_null<S>(message: &str, serializer: S) -> std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    if message.is_empty() {
+        serializer.serialize_none()
+    } else {
+        serializer.serialize_str(message)
+    }
+}
+
 fn null_string<'de, D>(deserializer: D) -> std::result::Result<String, D::Error>
 where
     D: de::Deserializer<'de>,
 {
     Ok(String::deserialize(deserializer).unwrap_or_default())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{find_by_name, App, AppList, AppPayload};
+    use crate::device::DEFAULT_TIMEOUT;
+    use crate::{ClientError, Error};
+
+    use serde_json::json;
+    use warp::Filter;
+
+    use std::net::SocketAddr;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn gzip_catalog() {
+        let payloads = json!([{
+            "id": "1",
+            "chipsets": {
+                "*": [{
+                    "app_type_payload": "{\"NAME_SPACE\":2,\"APP_ID\":\"3\",\"MESSAGE\":null}"
+                }]
+            }
+        }]);
+        let apps = json!([{
+            "id": "1",
+            "name": "Some App",
+            "mobileAppInfo": {
+                "description": "An app",
+                "app_icon_image_url": "http://127.0.0.1/icon.png"
+            }
+        }]);
+
+        let routes = warp::path("payloads")
+            .map(move || warp::reply::json(&payloads))
+            .or(warp::path("apps").map(move || warp::reply::json(&apps)))
+            .with(warp::compression::gzip());
+        let (addr, server) =
+            warp::serve(routes).bind_ephemeral(SocketAddr::from(([127, 0, 0, 1], 0)));
+        tokio::spawn(server);
+
+        let mut app_list = AppList::with_urls(
+            format!("http://{}/payloads", addr),
+            format!("http://{}/apps", addr),
+        )
+        .unwrap();
+
+        let payload: AppPayload = serde_json::from_value(json!({
+            "NAME_SPACE": 2,
+            "APP_ID": "3",
+            "MESSAGE": null
+        }))
+        .unwrap();
+        let app = app_list.get_app(payload).await.unwrap().unwrap();
+        assert_eq!(app.name(), "Some App");
+    }
+
+    #[tokio::test]
+    async fn disabled_catalog() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let routes = warp::any().map({
+            let hits = hits.clone();
+            move || {
+                hits.fetch_add(1, Ordering::SeqCst);
+                warp::reply::json(&json!([]))
+            }
+        });
+        let (addr, server) =
+            warp::serve(routes).bind_ephemeral(SocketAddr::from(([127, 0, 0, 1], 0)));
+        tokio::spawn(server);
+
+        let mut app_list = AppList::with_urls(
+            format!("http://{}/payloads", addr),
+            format!("http://{}/apps", addr),
+        )
+        .unwrap();
+        app_list.disable();
+
+        let payload: AppPayload = serde_json::from_value(json!({
+            "NAME_SPACE": 2,
+            "APP_ID": "3",
+            "MESSAGE": null
+        }))
+        .unwrap();
+        assert!(matches!(
+            app_list.get_app(payload).await,
+            Err(Error::Client(ClientError::AppCatalogDisabled))
+        ));
+        assert_eq!(hits.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn slow_catalog() {
+        let payloads = json!([{
+            "id": "1",
+            "chipsets": {
+                "*": [{
+                    "app_type_payload": "{\"NAME_SPACE\":2,\"APP_ID\":\"3\",\"MESSAGE\":null}"
+                }]
+            }
+        }]);
+        let apps = json!([{
+            "id": "1",
+            "name": "Some App",
+            "mobileAppInfo": {
+                "description": "An app",
+                "app_icon_image_url": "http://127.0.0.1/icon.png"
+            }
+        }]);
+
+        // The catalog takes longer than a device may to respond
+        let delay = Duration::from_secs(DEFAULT_TIMEOUT) + Duration::from_millis(500);
+        let routes = warp::path("payloads")
+            .map(move || warp::reply::json(&payloads))
+            .or(warp::path("apps").and_then(move || {
+                let apps = apps.clone();
+                async move {
+                    tokio::time::sleep(delay).await;
+                    Ok::<_, warp::Rejection>(warp::reply::json(&apps))
+                }
+            }));
+        let (addr, server) =
+            warp::serve(routes).bind_ephemeral(SocketAddr::from(([127, 0, 0, 1], 0)));
+        tokio::spawn(server);
+
+        let payload: AppPayload = serde_json::from_value(json!({
+            "NAME_SPACE": 2,
+            "APP_ID": "3",
+            "MESSAGE": null
+        }))
+        .unwrap();
+
+        let mut app_list = AppList::with_urls(
+            format!("http://{}/payloads", addr),
+            format!("http://{}/apps", addr),
+        )
+        .unwrap();
+        let app = app_list.get_app(payload.clone()).await.unwrap().unwrap();
+        assert_eq!(app.name(), "Some App");
+
+        let mut app_list = AppList::with_urls(
+            format!("http://{}/payloads", addr),
+            format!("http://{}/apps", addr),
+        )
+        .unwrap();
+        app_list.set_timeout(Duration::from_millis(100));
+        assert!(app_list.get_app(payload).await.unwrap_err().is_timeout());
+    }
+
+    #[tokio::test]
+    async fn aborted_catalog() {
+        let payloads = json!([{
+            "id": "1",
+            "chipsets": {
+                "*": [{
+                    "app_type_payload": "{\"NAME_SPACE\":2,\"APP_ID\":\"3\",\"MESSAGE\":null}"
+                }]
+            }
+        }]);
+        let apps = json!([{
+            "id": "1",
+            "name": "Some App",
+            "mobileAppInfo": {
+                "description": "An app",
+                "app_icon_image_url": "http://127.0.0.1/icon.png"
+            }
+        }]);
+
+        // Only the first download of the app names is slow
+        let slow = Arc::new(AtomicBool::new(true));
+        let routes = warp::path("payloads")
+            .map(move || warp::reply::json(&payloads))
+            .or(warp::path("apps").and_then({
+                let slow = slow.clone();
+                move || {
+                    let apps = apps.clone();
+                    let slow = slow.swap(false, Ordering::SeqCst);
+                    async move {
+                        if slow {
+                            tokio::time::sleep(Duration::from_secs(5)).await;
+                        }
+                        Ok::<_, warp::Rejection>(warp::reply::json(&apps))
+                    }
+                }
+            }));
+        let (addr, server) =
+            warp::serve(routes).bind_ephemeral(SocketAddr::from(([127, 0, 0, 1], 0)));
+        tokio::spawn(server);
+
+        let mut app_list = AppList::with_urls(
+            format!("http://{}/payloads", addr),
+            format!("http://{}/apps", addr),
+        )
+        .unwrap();
+
+        // Dropping the future aborts the download after the payloads were fetched
+        assert!(
+            tokio::time::timeout(Duration::from_millis(500), app_list.apps())
+                .await
+                .is_err()
+        );
+
+        // The partial download is not kept, so the catalog is downloaded again
+        let apps = app_list.apps().await.unwrap();
+        assert_eq!(apps.len(), 1);
+        assert_eq!(apps[0].name(), "Some App");
+    }
+
+    fn apps(names: &[&str]) -> Vec<App> {
+        names
+            .iter()
+            .map(|name| {
+                serde_json::from_value(json!({
+                    "id": name,
+                    "name": name,
+                    "mobileAppInfo": {
+                        "description": "",
+                        "app_icon_image_url": ""
+                    }
+                }))
+                .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn find_app_by_name() {
+        let catalog = apps(&["Netflix", "Prime Video", "Pluto TV", "Plex", "Haystack TV"]);
+
+        let found = |name| find_by_name(catalog.clone(), name).map(|app| app.name());
+        assert_eq!(found("netflix").unwrap(), "Netflix");
+        assert_eq!(found("NETF").unwrap(), "Netflix");
+        assert_eq!(found(" video ").unwrap(), "Prime Video");
+        assert_eq!(found("plex").unwrap(), "Plex");
+
+        // Prefixes beat other matches
+        assert_eq!(found("plu").unwrap(), "Pluto TV");
+
+        match found("pl") {
+            Err(Error::Client(ClientError::AmbiguousAppName(name, candidates))) => {
+                assert_eq!(name, "pl");
+                assert_eq!(candidates, vec!["Pluto TV", "Plex"]);
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+        match found("hulu tv") {
+            Err(Error::Client(ClientError::AppNotFound(name, near))) => {
+                assert_eq!(name, "hulu tv");
+                assert_eq!(near, vec!["Pluto TV", "Haystack TV"]);
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+
+        // Typos are suggested by how close they are to a name
+        match found("netflx") {
+            Err(Error::Client(ClientError::AppNotFound(_, near))) => {
+                assert_eq!(near, vec!["Netflix"]);
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+        match found("prme") {
+            Err(Error::Client(ClientError::AppNotFound(_, near))) => {
+                assert_eq!(near, vec!["Prime Video"]);
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn edit_distance() {
+        assert_eq!(super::edit_distance("netflix", "netflix"), 0);
+        assert_eq!(super::edit_distance("netflx", "netflix"), 1);
+        assert_eq!(super::edit_distance("kitten", "sitting"), 3);
+        assert_eq!(super::edit_distance("", "plex"), 4);
+    }
+}
@@ -1,19 +1,55 @@
 use super::{
+    remote::ButtonEvent,
     response::{self, Response},
-    Button, Device, EndpointBase, KeyEvent, Result,
+    Device, EndpointBase, Error, Result,
 };
 
 use serde::ser::{Serialize, SerializeStruct, Serializer};
 use serde_json::Value;
 
+use std::error::Error as StdError;
+use std::io::ErrorKind;
 use std::result::Result as StdResult;
+use std::sync::atomic::{AtomicU64, Ordering};
 
-#[derive(Debug, Copy, Clone)]
+/// Logged in place of the auth token
+const REDACTED: &str = "<redacted>";
+
+/// HTTP method used for a request
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum RequestType {
+    /// GET request
     Get,
+    /// PUT request
     Put,
 }
 
+/// Connection statistics of a device's client, see [`pool_stats()`](super::Device::pool_stats)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Requests sent to the device, not counting retries
+    pub requests: u64,
+    /// Read requests which were retried on a new connection because their connection was reset,
+    /// usually after sitting idle in the pool. Requests which change something on the device are
+    /// not retried.
+    pub stale_retries: u64,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct PoolCounters {
+    pub requests: AtomicU64,
+    pub stale_retries: AtomicU64,
+}
+
+impl PoolCounters {
+    pub fn stats(&self) -> PoolStats {
+        PoolStats {
+            requests: self.requests.load(Ordering::Relaxed),
+            stale_retries: self.stale_retries.load(Ordering::Relaxed),
+        }
+    }
+}
+
 #[allow(unused)] // Temp - TODO: remove
 #[derive(Debug)]
 pub(super) enum CommandDetail {
@@ -34,7 +70,7 @@ pub(super) enum CommandDetail {
     },
     GetPowerState,
     GetDeviceInfo,
-    RemoteButtonPress(KeyEvent, Button),
+    RemoteButtonPress(Vec<ButtonEvent>),
     GetCurrentInput,
     GetInputList,
     ChangeInput {
@@ -102,62 +138,174 @@ pub(super) struct Command {
     detail: CommandDetail,
     endpoint: String,
     device: Device,
+    auth_token: Option<String>,
+    without_auth: bool,
 }
 
 impl Command {
-    pub fn new(device: Device, detail: CommandDetail) -> Self {
-        let endpoint = detail.endpoint(device.settings_root());
+    /// Build the command. The settings root is read here so that the endpoint is consistent even if
+    /// the settings root is updated while the command is in flight.
+    pub async fn new(device: Device, detail: CommandDetail) -> Self {
+        let endpoint = detail.endpoint(device.settings_root().await);
         Self {
             detail,
             endpoint,
             device,
+            auth_token: None,
+            without_auth: false,
         }
     }
 
+    /// Send the command with this auth token instead of the device's
+    pub fn with_auth_token(mut self, auth_token: Option<String>) -> Self {
+        self.auth_token = auth_token;
+        self
+    }
+
+    /// Send the command without any auth token
+    pub fn without_auth_token(mut self) -> Self {
+        self.without_auth = true;
+        self
+    }
+
     pub async fn send(self) -> Result<Response> {
         let device = self.device.clone();
         let client = device.inner.client.clone();
 
-        let url: String = format!(
-            "https://{}:{}{}",
-            device.ip(),
-            device.port(),
-            self.detail.endpoint(device.settings_root())
-        );
+        let url: String = format!("https://{}:{}{}", device.ip(), device.port(), self.endpoint);
+
+        // Wait for a turn so the device is not sent more requests than it can handle
+        let _permit = device
+            .request_limit()
+            .await
+            .acquire_owned()
+            .await
+            .map_err(|e| Error::Other(format!("Request limit closed: {}", e)))?;
 
-        let res = {
+        let auth_token = match &self.auth_token {
+            _ if self.without_auth => None,
+            Some(token) => Some(token.clone()),
+            None => device.auth_token().await,
+        };
+        log::debug!("{:?} {}", self.detail.request_type(), self.endpoint);
+
+        let req = {
             // Request building
             let mut req = match self.detail.request_type() {
                 RequestType::Get => client.get(url),
                 RequestType::Put => {
+                    let body = match &self.detail {
+                        // Custom bodies are sent as-is
+                        CommandDetail::Custom(_, _, Some(body)) => body.to_string(),
+                        _ => serde_json::to_string(&self).unwrap(),
+                    };
+                    if log::log_enabled!(log::Level::Debug) && device.logs_request_bodies().await {
+                        log::debug!("body: {}", redacted_body(&body, auth_token.as_deref()));
+                    }
                     client
                         .put(url)
                         // Add content type header
                         .header("Content-Type", "application/json")
                         // Add body for PUT commands
-                        .body(serde_json::to_string(&self).unwrap())
+                        .body(body)
                 }
             };
             // Add auth token header
-            if let Some(token) = &device.auth_token().await {
-                req = req.header("Auth", token.to_string())
+            if let Some(token) = auth_token {
+                req = req.header("Auth", token)
             }
-            log::debug!("req: {:?}", req);
 
             req
-        }
+        };
+
         // Request send
-        .send()
-        .await?
+        device
+            .inner
+            .pool_stats
+            .requests
+            .fetch_add(1, Ordering::Relaxed);
+        // Only reads are sent again. The device may have acted on a PUT, such as a key press or
+        // setting write, before the connection was reset.
+        let retry = match self.detail.request_type() {
+            RequestType::Get => req.try_clone(),
+            RequestType::Put => None,
+        };
+        let res = match retry {
+            Some(retry) => match req.send().await {
+                // A connection which sat idle in the pool may have been closed by the device
+                Err(e) if is_stale_connection(&e) => {
+                    log::warn!("Connection was reset, retrying with a new connection");
+                    device
+                        .inner
+                        .pool_stats
+                        .stale_retries
+                        .fetch_add(1, Ordering::Relaxed);
+                    retry.send().await
+                }
+                other => other,
+            },
+            None => req.send().await,
+        }?
         // Get response as text because some device errors do not follow json format
         .text()
         .await?;
 
+        #[cfg(feature = "last-status")]
+        device.record_status(&res).await;
+
         // Process response
         response::process(res)
     }
 }
 
+/// Pretty print a request body for the log, with the auth token replaced in case it is in the
+/// body, as it may be with custom requests
+fn redacted_body(body: &str, auth_token: Option<&str>) -> String {
+    fn redact(value: &mut Value, token: &str) {
+        match value {
+            Value::String(s) => *s = s.replace(token, REDACTED),
+            Value::Array(values) => values.iter_mut().for_each(|v| redact(v, token)),
+            Value::Object(map) => map.values_mut().for_each(|v| redact(v, token)),
+            _ => {}
+        }
+    }
+
+    let token = auth_token.filter(|token| !token.is_empty());
+    match serde_json::from_str::<Value>(body) {
+        Ok(mut value) => {
+            if let Some(token) = token {
+                redact(&mut value, token);
+            }
+            serde_json::to_string_pretty(&value).unwrap()
+        }
+        Err(_) => match token {
+            Some(token) => body.replace(token, REDACTED),
+            None => body.into(),
+        },
+    }
+}
+
+/// Whether a request failed because the connection was reset rather than because the device
+/// could not be reached
+fn is_stale_connection(e: &reqwest::Error) -> bool {
+    if e.is_connect() || e.is_timeout() {
+        return false;
+    }
+    let mut source = StdError::source(e);
+    while let Some(err) = source {
+        if let Some(io) = err.downcast_ref::<std::io::Error>() {
+            if matches!(
+                io.kind(),
+                ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted | ErrorKind::BrokenPipe
+            ) {
+                return true;
+            }
+        }
+        source = err.source();
+    }
+    false
+}
+
 impl Serialize for Command {
     fn serialize<S>(&self, serializer: S) -> StdResult<S::Ok, S::Error>
     where
@@ -197,20 +345,8 @@ impl Serialize for Command {
                 command.serialize_field("PAIRING_REQ_TOKEN", pairing_token)?;
                 command.end()
             }
-            CommandDetail::RemoteButtonPress(event, button) => {
-                #[derive(serde::Serialize)]
-                #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-                struct Helper {
-                    codeset: u8,
-                    code: u8,
-                    action: String,
-                }
-                let helper = Helper {
-                    codeset: button.codeset(),
-                    code: button.code(),
-                    action: event.to_string(),
-                };
-                command.serialize_field("KEYLIST", &(vec![helper]))?;
+            CommandDetail::RemoteButtonPress(events) => {
+                command.serialize_field("KEYLIST", events)?;
                 command.end()
             }
             CommandDetail::ChangeInput { name, hashval } => {
@@ -233,3 +369,168 @@ impl Serialize for Command {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{redacted_body, ButtonEvent, Command, CommandDetail};
+    use crate::{Button, Device, KeyEvent};
+
+    use serde_json::json;
+    use warp::Filter;
+
+    use std::sync::{Mutex, Once};
+
+    /// Every message logged while the tests run
+    static LOGGED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    struct CaptureLogger;
+
+    impl log::Log for CaptureLogger {
+        fn enabled(&self, _: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            LOGGED.lock().unwrap().push(record.args().to_string());
+        }
+
+        fn flush(&self) {}
+    }
+
+    fn capture_logs() {
+        static INIT: Once = Once::new();
+        INIT.call_once(|| {
+            log::set_logger(&CaptureLogger).unwrap();
+            log::set_max_level(log::LevelFilter::Trace);
+        });
+    }
+
+    const ROOTS: [&str; 2] = ["tv_settings", "audio_settings"];
+
+    #[tokio::test]
+    async fn settings_root_update() {
+        let dev = Device::new("name", "Vizio", "model", "192.0.2.1", "uuid")
+            .await
+            .unwrap();
+
+        // Command waits for the settings root to be written instead of panicking
+        let mut settings_root = dev.inner.settings_root.write().await;
+        let command = tokio::spawn(Command::new(dev.clone(), CommandDetail::GetCurrentInput));
+        tokio::task::yield_now().await;
+        *settings_root = ROOTS[0].into();
+        drop(settings_root);
+        assert_eq!(
+            command.await.unwrap().endpoint,
+            "/menu_native/dynamic/tv_settings/devices/current_input"
+        );
+
+        // Build commands while the settings root keeps changing
+        let writer = tokio::spawn({
+            let dev = dev.clone();
+            async move {
+                for i in 0..1000 {
+                    *dev.inner.settings_root.write().await = ROOTS[i % 2].into();
+                    tokio::task::yield_now().await;
+                }
+            }
+        });
+        let mut commands = Vec::new();
+        for _ in 0..1000 {
+            commands.push(tokio::spawn(Command::new(
+                dev.clone(),
+                CommandDetail::GetInputList,
+            )));
+        }
+        writer.await.unwrap();
+        for command in commands {
+            let endpoint = command.await.unwrap().endpoint;
+            assert!(ROOTS.iter().any(
+                |root| endpoint == format!("/menu_native/dynamic/{}/devices/name_input", root)
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn keylist_serialization() {
+        let dev = Device::new("name", "Vizio", "model", "192.0.2.1", "uuid")
+            .await
+            .unwrap();
+        *dev.inner.settings_root.write().await = ROOTS[0].into();
+
+        let command = Command::new(
+            dev,
+            CommandDetail::RemoteButtonPress(vec![
+                ButtonEvent::new(KeyEvent::Down, Button::Menu),
+                ButtonEvent::new(KeyEvent::Press, Button::VolumeUp),
+                ButtonEvent::new(KeyEvent::Up, Button::Menu),
+            ]),
+        )
+        .await;
+        assert_eq!(
+            serde_json::to_value(&command).unwrap(),
+            json!({
+                "_url": "/key_command/",
+                "KEYLIST": [
+                    {"CODESET": 4, "CODE": 8, "ACTION": "KEYDOWN"},
+                    {"CODESET": 5, "CODE": 1, "ACTION": "KEYPRESS"},
+                    {"CODESET": 4, "CODE": 8, "ACTION": "KEYUP"},
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn body_log_redacts_token() {
+        let body = r#"{"REQUEST":"MODIFY","VALUE":"token=Z2zscc1udl","HASHVAL":12}"#;
+        let logged = redacted_body(body, Some("Z2zscc1udl"));
+        assert!(!logged.contains("Z2zscc1udl"));
+        assert!(logged.contains('\n'));
+
+        // Same as the body sent, apart from the token
+        let logged: serde_json::Value = serde_json::from_str(&logged).unwrap();
+        assert_eq!(
+            logged,
+            json!({"REQUEST": "MODIFY", "VALUE": "token=<redacted>", "HASHVAL": 12})
+        );
+
+        // Bodies which are not json are logged as they are
+        assert_eq!(
+            redacted_body("Z2zscc1udl, not json", Some("Z2zscc1udl")),
+            "<redacted>, not json"
+        );
+        assert_eq!(redacted_body("not json", None), "not json");
+    }
+
+    #[tokio::test]
+    async fn key_press_log_redacts_token() {
+        capture_logs();
+
+        // Device API which takes any key press
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+        let api = warp::path("key_command")
+            .map(|| r#"{"STATUS": {"RESULT": "SUCCESS", "DETAIL": "Success"}}"#);
+        let (api_addr, server) = warp::serve(api)
+            .tls()
+            .cert(cert.serialize_pem().unwrap())
+            .key(cert.serialize_private_key_pem())
+            .bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        let dev = Device::new("name", "Vizio", "model", "127.0.0.1", "uuid")
+            .await
+            .unwrap();
+        *dev.inner.port.write().await = api_addr.port();
+        *dev.inner.auth_token.write().await = Some("Z2zscc1udl".into());
+        dev.set_log_request_bodies(true).await;
+
+        let start = LOGGED.lock().unwrap().len();
+        dev.key_press(Button::Menu).await.unwrap();
+        let logged = LOGGED.lock().unwrap()[start..].to_vec();
+
+        // The body was logged, but nothing logged has the token
+        assert!(logged
+            .iter()
+            .any(|msg| msg.starts_with("body:") && msg.contains("KEYPRESS")));
+        assert!(logged.iter().all(|msg| !msg.contains("Z2zscc1udl")));
+    }
+}
@@ -0,0 +1,1333 @@
+use super::{
+    settings, Button, Device, DeviceKind, DeviceTime, EditableSetting, HdrFormat, Result,
+    SettingType, SignalFormat, SliderInfo, SubSetting,
+};
+use crate::error::{ApiError, ClientError, Error};
+
+use std::time::Duration;
+
+/// Possible paths to the sleep timer relative to the settings root
+const SLEEP_TIMER: &[&str] = &["timers/sleep_timer", "system/timers/sleep_timer"];
+/// Possible paths to the picture menu relative to the settings root
+const PICTURE: &[&str] = &["picture", "picture_settings", "video"];
+/// Possible paths to the backlight relative to the settings root
+const BACKLIGHT: &[&str] = &["picture/backlight", "picture/picture_settings/backlight"];
+/// Possible paths to the brightness relative to the settings root
+const BRIGHTNESS: &[&str] = &["picture/brightness", "picture/picture_settings/brightness"];
+/// Possible paths to the volume on a TV relative to the settings root
+const TV_VOLUME: &[&str] = &["audio/volume", "audio/audio_settings/volume"];
+/// Possible paths to the volume on a soundbar relative to the settings root
+const SOUNDBAR_VOLUME: &[&str] = &["audio/volume", "speakers/volume"];
+/// Possible paths to HDMI-CEC on a TV relative to the settings root
+const TV_CEC: &[&str] = &["system/cec/cec_function", "system/cec/cec"];
+/// Possible paths to HDMI-CEC on a soundbar relative to the settings root
+const SOUNDBAR_CEC: &[&str] = &["system/cec", "cec/cec_function"];
+/// Possible paths to HDMI ARC on a TV relative to the settings root
+const TV_ARC: &[&str] = &["audio/arc", "audio/earc", "system/cec/arc"];
+/// Possible paths to HDMI ARC on a soundbar relative to the settings root
+const SOUNDBAR_ARC: &[&str] = &["audio/arc", "system/arc"];
+/// Possible paths to mute relative to the settings root
+const MUTE: &[&str] = &["audio/mute", "audio/audio_settings/mute"];
+/// List elements which mean a toggle is on
+const TOGGLE_ON: &[&str] = &["on", "enabled"];
+/// List elements which mean a toggle is off
+const TOGGLE_OFF: &[&str] = &["off", "disabled"];
+/// Possible paths to the HDR mode relative to the settings root
+const HDR_MODE: &[&str] = &[
+    "picture/hdr_mode",
+    "picture/picture_settings/hdr_mode",
+    "picture/hdr",
+];
+/// Possible paths to the aspect ratio relative to the settings root
+const ASPECT_RATIO: &[&str] = &[
+    "picture/picture_size",
+    "picture/aspect_ratio",
+    "picture/picture_format/picture_size",
+];
+/// Possible paths to the audio output relative to the settings root
+const AUDIO_OUTPUT: &[&str] = &[
+    "audio/audio_output",
+    "audio/speaker_output",
+    "audio/audio_settings/audio_output",
+];
+/// Possible paths to the audio track relative to the settings root
+const AUDIO_TRACK: &[&str] = &[
+    "audio/audio_language",
+    "audio/audio_track",
+    "audio/audio_settings/audio_language",
+    "channels/audio_language",
+];
+/// Possible paths to the energy saving mode relative to the settings root
+const ECO_MODE: &[&str] = &[
+    "picture/eco_mode",
+    "picture/energy_saving",
+    "system/energy_saving/eco_mode",
+];
+/// Possible paths to local dimming relative to the settings root. Older full array models call
+/// it Active Full Array.
+const LOCAL_DIMMING: &[&str] = &[
+    "picture/advanced_picture/local_dimming",
+    "picture/more_picture/local_dimming",
+    "picture/local_dimming",
+    "picture/active_full_array",
+];
+/// Possible paths to motion smoothing relative to the settings root. The device names it Motion
+/// Control, or Clear Action on models which only reduce blur.
+const MOTION_SMOOTHING: &[&str] = &[
+    "picture/advanced_picture/motion_control",
+    "picture/more_picture/motion_control",
+    "picture/motion_control",
+    "picture/clear_action",
+];
+/// Possible paths to the forced output resolution relative to the settings root
+const RESOLUTION: &[&str] = &[
+    "picture/advanced_picture/forced_resolution",
+    "picture/forced_resolution",
+    "system/forced_resolution",
+];
+/// Possible paths to the screensaver relative to the settings root, which is called ambient or
+/// art mode on some models
+const SCREENSAVER: &[&str] = &[
+    "system/screensaver",
+    "system/ambient_mode/screensaver",
+    "system/art_mode",
+];
+/// Possible paths to the store demo mode relative to the settings root, which moved from the
+/// system menu into Reset & Admin on later firmware
+const STORE_MODE: &[&str] = &[
+    "system/reset_and_admin/store_demo",
+    "system/reset_admin/store_demo",
+    "system/store_demo",
+];
+/// Possible paths to the current tuner channel relative to the settings root
+const CURRENT_CHANNEL: &[&str] = &["channels/current_channel", "tuner/current_channel"];
+/// Possible paths to the tuner's channels relative to the settings root
+const CHANNEL_LIST: &[&str] = &["channels/channel_list", "tuner/channel_list"];
+/// Name of the tuner input
+const TUNER_INPUT: &str = "TV";
+/// Possible paths to the electronic serial number relative to the settings root
+const ESN: &[&str] = &[
+    "system/system_information/uli_information/esn",
+    "system/system_information/tv_information/esn",
+];
+/// Possible paths to the panel usage hours relative to the settings root
+const USAGE_HOURS: &[&str] = &[
+    "system/system_information/tv_information/panel_hours",
+    "system/system_information/uli_information/panel_hours",
+    "system/system_information/tv_information/usage_hours",
+];
+/// Possible paths to the signal format of the current input relative to the settings root
+const SIGNAL_FORMAT: &[&str] = &[
+    "system/system_information/tv_information/resolution",
+    "system/system_information/uli_information/resolution",
+    "system/system_information/tv_information/video_format",
+];
+/// Possible paths to the current time relative to the settings root
+const CURRENT_TIME: &[&str] = &[
+    "system/time/current_time",
+    "system/time_and_local_settings/current_time",
+];
+/// Possible paths to the time zone relative to the settings root
+const TIME_ZONE: &[&str] = &[
+    "system/time/time_zone",
+    "system/time_and_local_settings/time_zone",
+    "system/time_zone",
+];
+/// Possible paths to the region relative to the settings root
+const REGION: &[&str] = &["system/country", "system/region", "system/locale"];
+
+/// A high level capability of a device, see [`supports()`](Device::supports)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Feature {
+    /// [`volume_percent()`](Device::volume_percent) and the other volume controls
+    Volume,
+    /// [`is_muted()`](Device::is_muted) and [`set_muted()`](Device::set_muted)
+    Mute,
+    /// [`cec_enabled()`](Device::cec_enabled) and [`set_cec()`](Device::set_cec)
+    Cec,
+    /// [`arc_enabled()`](Device::arc_enabled) and [`set_arc()`](Device::set_arc)
+    Arc,
+    /// [`backlight()`](Device::backlight) and [`set_backlight()`](Device::set_backlight)
+    Backlight,
+    /// [`brightness()`](Device::brightness) and [`set_brightness()`](Device::set_brightness)
+    Brightness,
+    /// [`aspect_ratio()`](Device::aspect_ratio) and [`set_aspect_ratio()`](Device::set_aspect_ratio)
+    AspectRatio,
+    /// [`audio_output()`](Device::audio_output) and [`set_audio_output()`](Device::set_audio_output)
+    AudioOutput,
+    /// [`audio_track()`](Device::audio_track) and [`set_audio_track()`](Device::set_audio_track)
+    AudioTrack,
+    /// [`eco_mode()`](Device::eco_mode) and [`set_eco_mode()`](Device::set_eco_mode)
+    EcoMode,
+    /// [`local_dimming()`](Device::local_dimming) and
+    /// [`set_local_dimming()`](Device::set_local_dimming)
+    LocalDimming,
+    /// [`motion_smoothing()`](Device::motion_smoothing) and
+    /// [`set_motion_smoothing()`](Device::set_motion_smoothing)
+    MotionSmoothing,
+    /// [`resolutions()`](Device::resolutions) and [`set_resolution()`](Device::set_resolution)
+    Resolution,
+    /// [`screensaver()`](Device::screensaver) and [`set_screensaver()`](Device::set_screensaver)
+    Screensaver,
+    /// [`sleep_timer()`](Device::sleep_timer) and [`set_sleep_timer()`](Device::set_sleep_timer)
+    SleepTimer,
+    /// [`current_channel()`](Device::current_channel) and the other channel controls
+    Tuner,
+    /// [`launch_app()`](Device::launch_app) and [`current_app()`](Device::current_app)
+    AppLaunch,
+}
+
+impl Device {
+    /// Check whether the device has a feature, so a UI can only show the controls which work
+    ///
+    /// This looks for the setting or endpoint behind the feature's methods, the same way they do,
+    /// instead of calling one and checking for [`ClientError::Unsupported`]. The answer is cached,
+    /// so only the first check of each feature sends requests.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::{Device, Feature};
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// if dev.supports(Feature::Tuner).await? {
+    ///     println!("On channel {}", dev.current_channel().await?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn supports(&self, feature: Feature) -> Result<bool> {
+        log::trace!("Supports");
+        log::debug!("supports feature: {:?}", feature);
+        if let Some(supported) = self.inner.supported_features.read().await.get(&feature) {
+            return Ok(*supported);
+        }
+
+        let supported = match feature {
+            Feature::Volume => self.has_setting(self.volume_paths().await).await?,
+            Feature::Mute => self.has_setting(MUTE).await?,
+            Feature::Cec => self.has_setting(self.cec_paths().await).await?,
+            Feature::Arc => self.has_setting(self.arc_paths().await).await?,
+            Feature::Backlight => self.has_setting(BACKLIGHT).await?,
+            Feature::Brightness => self.has_setting(BRIGHTNESS).await?,
+            Feature::AspectRatio => self.has_setting(ASPECT_RATIO).await?,
+            Feature::AudioOutput => self.has_setting(AUDIO_OUTPUT).await?,
+            Feature::AudioTrack => self.has_setting(AUDIO_TRACK).await?,
+            Feature::EcoMode => self.has_setting(ECO_MODE).await?,
+            Feature::LocalDimming => self.has_setting(LOCAL_DIMMING).await?,
+            Feature::MotionSmoothing => self.has_setting(MOTION_SMOOTHING).await?,
+            Feature::Resolution => self.has_setting(RESOLUTION).await?,
+            Feature::Screensaver => self.has_setting(SCREENSAVER).await?,
+            Feature::SleepTimer => self.has_setting(SLEEP_TIMER).await?,
+            Feature::Tuner => self.has_setting(CURRENT_CHANNEL).await?,
+            Feature::AppLaunch => match self.current_app_payload().await {
+                Ok(_) => true,
+                Err(Error::Api(ApiError::UriNotFound)) => false,
+                Err(e) => return Err(e),
+            },
+        };
+
+        self.inner
+            .supported_features
+            .write()
+            .await
+            .insert(feature, supported);
+        Ok(supported)
+    }
+
+    /// Get every writable setting in the picture menu along with its bounds, such as for
+    /// recording a calibration
+    ///
+    /// This is the same as [`editable_settings()`](Self::editable_settings) for the picture menu
+    /// alone, including its sub-menus. Settings are read with one request per menu and slider
+    /// rather than walking the whole settings tree. Nothing is cached, so every call reads the
+    /// current values from the device. Returns [`ClientError::Unsupported`] if the device has no
+    /// picture menu.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::{Bounds, Device};
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// for editable in dev.picture_settings().await? {
+    ///     if let Bounds::Slider(info) = editable.bounds {
+    ///         let value = editable.setting.value::<i32>().unwrap_or_default();
+    ///         println!("{}: {} ({} to {})", editable.setting.name(), value, info.min, info.max);
+    ///     }
+    /// }
+    /// // > Backlight: 50 (0 to 100)
+    /// // > ...
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn picture_settings(&self) -> Result<Vec<EditableSetting>> {
+        log::trace!("Picture Settings");
+        match settings::editable_in_menu(self.clone(), PICTURE).await {
+            Err(Error::Client(ClientError::SettingPathNotFound(_))) => {
+                Err(ClientError::Unsupported {
+                    feature: "picture settings",
+                }
+                .into())
+            }
+            other => other,
+        }
+    }
+
+    /// Get the backlight level
+    pub async fn backlight(&self) -> Result<i32> {
+        log::trace!("Get Backlight");
+        self.slider_value("backlight", BACKLIGHT).await
+    }
+
+    /// Set the backlight level
+    ///
+    /// The value is rounded to the nearest step of the slider's increment. Returns the value which
+    /// was actually set, or an error if it is outside the slider's bounds.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// // Dim for the night
+    /// dev.set_backlight(20).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_backlight(&self, value: i32) -> Result<i32> {
+        log::trace!("Set Backlight");
+        log::debug!("set_backlight value: {}", value);
+        self.set_slider_value("backlight", BACKLIGHT, value).await
+    }
+
+    /// Get the brightness level
+    pub async fn brightness(&self) -> Result<i32> {
+        log::trace!("Get Brightness");
+        self.slider_value("brightness", BRIGHTNESS).await
+    }
+
+    /// Set the brightness level
+    ///
+    /// Same as [`set_backlight()`](Self::set_backlight), the value is rounded to the nearest step
+    /// of the slider's increment and the value which was actually set is returned.
+    pub async fn set_brightness(&self, value: i32) -> Result<i32> {
+        log::trace!("Set Brightness");
+        log::debug!("set_brightness value: {}", value);
+        self.set_slider_value("brightness", BRIGHTNESS, value).await
+    }
+
+    /// Get the volume as a percent of the device's volume range
+    ///
+    /// Models use different volume scales, e.g. 0-31 or 0-100. This normalizes the volume to
+    /// 0-100 regardless of the scale.
+    pub async fn volume_percent(&self) -> Result<f32> {
+        log::trace!("Get Volume Percent");
+        let setting = self.feature("volume", self.volume_paths().await).await?;
+        let info = volume_slider(&setting).await?;
+        let value = setting
+            .value::<i32>()
+            .ok_or(ClientError::NoValue { feature: "volume" })?;
+        Ok(to_percent(&info, value))
+    }
+
+    /// Get the volume on the device's own scale, see [`volume_range()`](Self::volume_range)
+    ///
+    /// The volume setting is looked up once and cached, so later reads are a single request to
+    /// its endpoint. This keeps polling the volume cheap. If the endpoint can no longer be read,
+    /// e.g. after a firmware update moved it, the setting is looked up again.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// println!("Volume is {}", dev.volume().await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn volume(&self) -> Result<i32> {
+        log::trace!("Get Volume");
+        let cached = self.inner.volume_setting.read().await.clone();
+        let refreshed = match cached {
+            Some(setting) => {
+                let mut setting = setting.with_device(self.clone());
+                match setting.refresh().await {
+                    Ok(()) => Some(setting),
+                    Err(e) => {
+                        // The endpoint may have moved with a firmware update
+                        log::warn!("Cached volume setting could not be read: {}", e);
+                        self.inner.volume_setting.write().await.take();
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+        let setting = match refreshed {
+            Some(setting) => setting,
+            None => self.feature("volume", self.volume_paths().await).await?,
+        };
+        // Cached without the device, which would otherwise keep itself alive
+        *self.inner.volume_setting.write().await = Some(setting.clone().without_device());
+        setting
+            .value::<i32>()
+            .ok_or_else(|| ClientError::NoValue { feature: "volume" }.into())
+    }
+
+    /// Set the volume as a percent of the device's volume range
+    ///
+    /// `percent` is clamped to 0-100 and rounded to the nearest step on the device's volume
+    /// scale, so the volume which is set may differ slightly. Returns the percent which was
+    /// actually set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// // A device with a 0-31 volume scale is set to 16
+    /// let percent = dev.set_volume_percent(50.0).await?;
+    /// println!("Volume is now {:.1}%", percent);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_volume_percent(&self, percent: f32) -> Result<f32> {
+        log::trace!("Set Volume Percent");
+        log::debug!("set_volume_percent percent: {}", percent);
+        let setting = self.feature("volume", self.volume_paths().await).await?;
+        let info = volume_slider(&setting).await?;
+        let value = info.snap(from_percent(&info, percent));
+        setting.update(value).await?;
+        Ok(to_percent(&info, value))
+    }
+
+    /// Get the device's volume scale as `(min, max, increment)`
+    ///
+    /// This is read from the volume slider once and cached, so a UI can set up its volume control
+    /// without fetching the slider each time.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// let (min, max, increment) = dev.volume_range().await?;
+    /// println!("Volume goes from {} to {} in steps of {}", min, max, increment);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn volume_range(&self) -> Result<(i32, i32, i32)> {
+        log::trace!("Get Volume Range");
+        if let Some(range) = *self.inner.volume_range.read().await {
+            return Ok(range);
+        }
+
+        let setting = self.feature("volume", self.volume_paths().await).await?;
+        let info = volume_slider(&setting).await?;
+        let range = (info.min, info.max, info.increment);
+        *self.inner.volume_range.write().await = Some(range);
+        Ok(range)
+    }
+
+    /// Check whether the audio is muted
+    pub async fn is_muted(&self) -> Result<bool> {
+        log::trace!("Get Mute");
+        self.toggle("mute", MUTE).await
+    }
+
+    /// Mute or unmute the audio
+    ///
+    /// Unlike [`Button::MuteToggle`](crate::Button::MuteToggle), this sets a specific state.
+    pub async fn set_muted(&self, muted: bool) -> Result<()> {
+        log::trace!("Set Mute");
+        log::debug!("set_muted muted: {}", muted);
+        self.set_toggle("mute", MUTE, muted).await
+    }
+
+    /// Toggle mute and return whether the audio is now muted
+    ///
+    /// The current state is read from the device first, so the result is known even if the
+    /// device was muted some other way.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// let muted = dev.toggle_mute().await?;
+    /// println!("Muted: {}", muted);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn toggle_mute(&self) -> Result<bool> {
+        log::trace!("Toggle Mute");
+        let muted = !self.is_muted().await?;
+        self.set_muted(muted).await?;
+        Ok(muted)
+    }
+
+    /// Check whether HDMI-CEC is enabled
+    pub async fn cec_enabled(&self) -> Result<bool> {
+        log::trace!("Get CEC");
+        self.toggle("HDMI-CEC", self.cec_paths().await).await
+    }
+
+    /// Enable or disable HDMI-CEC
+    pub async fn set_cec(&self, enabled: bool) -> Result<()> {
+        log::trace!("Set CEC");
+        log::debug!("set_cec enabled: {}", enabled);
+        self.set_toggle("HDMI-CEC", self.cec_paths().await, enabled)
+            .await
+    }
+
+    /// Check whether HDMI ARC is enabled
+    pub async fn arc_enabled(&self) -> Result<bool> {
+        log::trace!("Get ARC");
+        self.toggle("HDMI ARC", self.arc_paths().await).await
+    }
+
+    /// Enable or disable HDMI ARC
+    pub async fn set_arc(&self, enabled: bool) -> Result<()> {
+        log::trace!("Set ARC");
+        log::debug!("set_arc enabled: {}", enabled);
+        self.set_toggle("HDMI ARC", self.arc_paths().await, enabled)
+            .await
+    }
+
+    /// Get the HDR formats the device supports
+    ///
+    /// These are read from the options of the device's HDR mode setting. Returns an empty list if
+    /// the device has no HDR mode setting, e.g. a soundbar.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::{Device, HdrFormat};
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// if dev.hdr_formats().await?.contains(&HdrFormat::DolbyVision) {
+    ///     println!("Dolby Vision is supported");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn hdr_formats(&self) -> Result<Vec<HdrFormat>> {
+        log::trace!("Get HDR Formats");
+        let setting = match settings::at_path(self.clone(), HDR_MODE).await {
+            Ok(setting) => setting,
+            Err(Error::Client(ClientError::SettingPathNotFound(_))) => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut formats = Vec::new();
+        for format in setting
+            .elements()
+            .await?
+            .iter()
+            .filter_map(|e| HdrFormat::parse(e))
+        {
+            if !formats.contains(&format) {
+                formats.push(format);
+            }
+        }
+        Ok(formats)
+    }
+
+    /// Get the device's electronic serial number (ESN)
+    ///
+    /// This is not the same as the [`serial_number`](super::DeviceInfo::serial_number) in
+    /// [`device_info()`](Self::device_info). Returns `None` if the device does not report an ESN.
+    pub async fn esn(&self) -> Result<Option<String>> {
+        log::trace!("Get ESN");
+        self.optional_string(ESN).await
+    }
+
+    /// Get the format of the video signal on the current input, e.g. "3840x2160@60Hz HDR10"
+    ///
+    /// This is for checking that a source is sending the expected format. Returns
+    /// [`SignalFormat::Unknown`] if there is no signal or the device does not report it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::{Device, SignalFormat};
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// if let SignalFormat::Video { width, height, .. } = dev.current_input_signal().await? {
+    ///     println!("{}x{}", width, height);
+    ///     // > 3840x2160
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn current_input_signal(&self) -> Result<SignalFormat> {
+        log::trace!("Get Current Input Signal");
+        Ok(match self.optional_string(SIGNAL_FORMAT).await? {
+            Some(format) => SignalFormat::parse(&format),
+            None => SignalFormat::Unknown,
+        })
+    }
+
+    /// Get the device's current time and time zone
+    ///
+    /// Returns `None` if the device does not report its time.
+    pub async fn device_time(&self) -> Result<Option<DeviceTime>> {
+        log::trace!("Get Device Time");
+        let time = match self.optional_string(CURRENT_TIME).await? {
+            Some(time) => time,
+            None => return Ok(None),
+        };
+        Ok(Some(DeviceTime {
+            time,
+            time_zone: self.optional_string(TIME_ZONE).await?,
+        }))
+    }
+
+    /// Get the number of hours the panel has been on
+    ///
+    /// Returns `None` if the device does not report its usage.
+    pub async fn usage_hours(&self) -> Result<Option<u32>> {
+        log::trace!("Get Usage Hours");
+        let setting = match settings::at_path(self.clone(), USAGE_HOURS).await {
+            Ok(setting) => setting,
+            Err(Error::Client(ClientError::SettingPathNotFound(_))) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        Ok(match setting.value::<String>() {
+            Some(hours) => parse_hours(&hours),
+            None => setting.value::<u32>(),
+        })
+    }
+
+    /// Get the aspect ratio, e.g. "Normal" or "Stretch"
+    pub async fn aspect_ratio(&self) -> Result<String> {
+        log::trace!("Get Aspect Ratio");
+        self.list_value("aspect ratio", ASPECT_RATIO).await
+    }
+
+    /// Set the aspect ratio
+    ///
+    /// Unlike [`Button::PicSize`](crate::Button::PicSize), which cycles through the options, this
+    /// sets a specific one. Returns an error if `name` is not one of the device's options.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// dev.set_aspect_ratio("Stretch").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_aspect_ratio<S: Into<String>>(&self, name: S) -> Result<()> {
+        let name: String = name.into();
+        log::trace!("Set Aspect Ratio");
+        log::debug!("set_aspect_ratio name: {}", name);
+        self.set_list_value("aspect ratio", ASPECT_RATIO, name)
+            .await
+    }
+
+    /// Get where the TV sends its sound, e.g. "TV Speakers" or "ARC"
+    ///
+    /// Returns [`ClientError::Unsupported`] if the device has no audio output setting.
+    pub async fn audio_output(&self) -> Result<String> {
+        log::trace!("Get Audio Output");
+        self.list_value("audio output", AUDIO_OUTPUT).await
+    }
+
+    /// Set where the TV sends its sound, such as to a receiver over HDMI ARC
+    ///
+    /// Returns an error if `mode` is not one of the device's options.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// dev.set_audio_output("ARC").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_audio_output<S: Into<String>>(&self, mode: S) -> Result<()> {
+        let mode: String = mode.into();
+        log::trace!("Set Audio Output");
+        log::debug!("set_audio_output mode: {}", mode);
+        self.set_list_value("audio output", AUDIO_OUTPUT, mode)
+            .await
+    }
+
+    /// Get the selected audio track, usually named for its language, e.g. "English"
+    ///
+    /// Returns [`ClientError::Unsupported`] if the device does not expose the audio track.
+    pub async fn audio_track(&self) -> Result<String> {
+        log::trace!("Get Audio Track");
+        self.list_value("audio track", AUDIO_TRACK).await
+    }
+
+    /// Get the audio tracks which can be selected, e.g. `["English", "Spanish"]`
+    ///
+    /// Returns [`ClientError::Unsupported`] if the device does not expose the audio track.
+    pub async fn audio_tracks(&self) -> Result<Vec<String>> {
+        log::trace!("Get Audio Tracks");
+        self.list_elements("audio track", AUDIO_TRACK).await
+    }
+
+    /// Select an audio track, one of [`audio_tracks()`](Self::audio_tracks)
+    ///
+    /// Returns an error if `name` is not one of the device's tracks.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// if dev.audio_tracks().await?.iter().any(|track| track == "Spanish") {
+    ///     dev.set_audio_track("Spanish").await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_audio_track<S: Into<String>>(&self, name: S) -> Result<()> {
+        let name: String = name.into();
+        log::trace!("Set Audio Track");
+        log::debug!("set_audio_track name: {}", name);
+        self.set_list_value("audio track", AUDIO_TRACK, name).await
+    }
+
+    /// Get the energy saving mode, e.g. "Off" or "Standard"
+    ///
+    /// Returns [`ClientError::Unsupported`] if the device has no energy saving setting.
+    pub async fn eco_mode(&self) -> Result<String> {
+        log::trace!("Get Eco Mode");
+        self.list_value("eco mode", ECO_MODE).await
+    }
+
+    /// Set the energy saving mode
+    ///
+    /// Energy saving dims the picture with the room's light, so it is commonly turned off when
+    /// calibrating. Returns an error if `name` is not one of the device's options.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// dev.set_eco_mode("Off").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_eco_mode<S: Into<String>>(&self, name: S) -> Result<()> {
+        let name: String = name.into();
+        log::trace!("Set Eco Mode");
+        log::debug!("set_eco_mode name: {}", name);
+        self.set_list_value("eco mode", ECO_MODE, name).await
+    }
+
+    /// Get the local dimming level, e.g. "Off" or "High"
+    ///
+    /// Returns [`ClientError::Unsupported`] if the device has no local dimming setting.
+    pub async fn local_dimming(&self) -> Result<String> {
+        log::trace!("Get Local Dimming");
+        self.list_value("local dimming", LOCAL_DIMMING).await
+    }
+
+    /// Set the local dimming level
+    ///
+    /// Local dimming dims zones of the backlight behind dark parts of the picture. Returns an
+    /// error if `name` is not one of the device's options.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// dev.set_local_dimming("Off").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_local_dimming<S: Into<String>>(&self, name: S) -> Result<()> {
+        let name: String = name.into();
+        log::trace!("Set Local Dimming");
+        log::debug!("set_local_dimming name: {}", name);
+        self.set_list_value("local dimming", LOCAL_DIMMING, name)
+            .await
+    }
+
+    /// Get the motion smoothing mode, e.g. "Off" or "Medium"
+    ///
+    /// Returns [`ClientError::Unsupported`] if the device has no motion smoothing setting.
+    pub async fn motion_smoothing(&self) -> Result<String> {
+        log::trace!("Get Motion Smoothing");
+        self.list_value("motion smoothing", MOTION_SMOOTHING).await
+    }
+
+    /// Set the motion smoothing mode
+    ///
+    /// Motion smoothing adds frames between those of the source, so it is commonly turned off
+    /// when calibrating. Returns an error if `name` is not one of the device's options.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// dev.set_motion_smoothing("Off").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_motion_smoothing<S: Into<String>>(&self, name: S) -> Result<()> {
+        let name: String = name.into();
+        log::trace!("Set Motion Smoothing");
+        log::debug!("set_motion_smoothing name: {}", name);
+        self.set_list_value("motion smoothing", MOTION_SMOOTHING, name)
+            .await
+    }
+
+    /// Get the resolution the picture is forced to, e.g. "Auto" or "1080p"
+    ///
+    /// Returns [`ClientError::Unsupported`] if the device can not force a resolution.
+    pub async fn resolution(&self) -> Result<String> {
+        log::trace!("Get Resolution");
+        self.list_value("resolution", RESOLUTION).await
+    }
+
+    /// Get the resolutions the picture can be forced to, e.g. `["Auto", "1080p", "2160p"]`
+    ///
+    /// Returns [`ClientError::Unsupported`] if the device can not force a resolution. See
+    /// [`current_input_signal()`](Self::current_input_signal) for the resolution of the signal
+    /// itself.
+    pub async fn resolutions(&self) -> Result<Vec<String>> {
+        log::trace!("Get Resolutions");
+        self.list_elements("resolution", RESOLUTION).await
+    }
+
+    /// Force the picture to a resolution, one of [`resolutions()`](Self::resolutions)
+    ///
+    /// This is for sources such as AV receivers which pick a poor resolution on their own.
+    /// Returns an error if `name` is not one of the device's resolutions.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// if dev.resolutions().await?.iter().any(|r| r == "1080p") {
+    ///     dev.set_resolution("1080p").await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_resolution<S: Into<String>>(&self, name: S) -> Result<()> {
+        let name: String = name.into();
+        log::trace!("Set Resolution");
+        log::debug!("set_resolution name: {}", name);
+        self.set_list_value("resolution", RESOLUTION, name).await
+    }
+
+    /// Get the screensaver shown while the device is idle, e.g. "Off" or "Art"
+    ///
+    /// Returns [`ClientError::Unsupported`] if the device has no screensaver setting, which is
+    /// the case for most models without an ambient or art mode.
+    pub async fn screensaver(&self) -> Result<String> {
+        log::trace!("Get Screensaver");
+        self.list_value("screensaver", SCREENSAVER).await
+    }
+
+    /// Set the screensaver shown while the device is idle
+    ///
+    /// Returns an error if `name` is not one of the device's options.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// // No art at night
+    /// dev.set_screensaver("Off").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_screensaver<S: Into<String>>(&self, name: S) -> Result<()> {
+        let name: String = name.into();
+        log::trace!("Set Screensaver");
+        log::debug!("set_screensaver name: {}", name);
+        self.set_list_value("screensaver", SCREENSAVER, name).await
+    }
+
+    /// Check whether the device is in store demo mode
+    ///
+    /// Display models run in this mode, which cycles the picture settings and may play a demo
+    /// video when idle. Returns [`ClientError::Unsupported`] if the device has no store mode
+    /// setting.
+    pub async fn store_mode(&self) -> Result<bool> {
+        log::trace!("Get Store Mode");
+        self.toggle("store mode", STORE_MODE).await
+    }
+
+    /// Enable or disable store demo mode
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// if dev.store_mode().await? {
+    ///     dev.set_store_mode(false).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_store_mode(&self, enabled: bool) -> Result<()> {
+        log::trace!("Set Store Mode");
+        log::debug!("set_store_mode enabled: {}", enabled);
+        self.set_toggle("store mode", STORE_MODE, enabled).await
+    }
+
+    /// Get the channel the tuner is on, e.g. "4.1"
+    ///
+    /// Returns [`ClientError::Unsupported`] if the device does not have a tuner.
+    pub async fn current_channel(&self) -> Result<String> {
+        log::trace!("Get Current Channel");
+        self.list_value("tuner", CURRENT_CHANNEL).await
+    }
+
+    /// Get the channels the tuner has found, e.g. `["2.1", "4.1", "4.2"]`
+    ///
+    /// Returns [`ClientError::Unsupported`] if the device does not have a tuner.
+    pub async fn channel_list(&self) -> Result<Vec<String>> {
+        log::trace!("Get Channel List");
+        self.list_elements("tuner", CHANNEL_LIST).await
+    }
+
+    /// Tune to a channel, e.g. "7" or "4.1"
+    ///
+    /// Sub-channels may be separated with a `.` or a `-`. The channel is entered with the number
+    /// pad of the virtual remote, so the tuner must be the current input.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// dev.change_input("TV").await?;
+    /// dev.tune_to_channel("4.1").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn tune_to_channel<S: AsRef<str>>(&self, channel: S) -> Result<()> {
+        log::trace!("Tune To Channel");
+        log::debug!("tune_to_channel channel: {}", channel.as_ref());
+        let channel = parse_channel(channel.as_ref())
+            .ok_or_else(|| ClientError::InvalidChannel(channel.as_ref().into()))?;
+
+        // The current input's value is the name of the input
+        if self.current_input().await?.friendly_name() != TUNER_INPUT {
+            return Err(ClientError::TunerNotActive.into());
+        }
+        for c in channel.chars() {
+            self.key_press(Button::digit(c).unwrap_or(Button::Dash))
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Get the region the device is set to, e.g. "United States"
+    ///
+    /// Returns `None` if the device does not have a region setting.
+    pub async fn region(&self) -> Result<Option<String>> {
+        log::trace!("Get Region");
+        self.optional_string(REGION).await
+    }
+
+    /// Get the duration the sleep timer is set to
+    ///
+    /// Returns `None` if the sleep timer is off.
+    pub async fn sleep_timer(&self) -> Result<Option<Duration>> {
+        log::trace!("Get Sleep Timer");
+        let setting = self.feature("sleep timer", SLEEP_TIMER).await?;
+
+        Ok(match setting.value::<String>() {
+            Some(label) => parse_duration(&label),
+            None => minutes(setting.value::<u64>().unwrap_or_default()),
+        })
+    }
+
+    /// Set the sleep timer
+    ///
+    /// Devices usually offer a handful of options, e.g. 30, 60 or 90 minutes, so the duration
+    /// passed in is rounded to the nearest option. Passing `None` turns the sleep timer off.
+    /// Returns the duration which was actually set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    /// use std::time::Duration;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// let set = dev.set_sleep_timer(Some(Duration::from_secs(50 * 60))).await?;
+    /// println!("{:?}", set);
+    /// // > Some(3600s)
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_sleep_timer(&self, duration: Option<Duration>) -> Result<Option<Duration>> {
+        log::trace!("Set Sleep Timer");
+        log::debug!("set_sleep_timer duration: {:?}", duration);
+        let setting = self.feature("sleep timer", SLEEP_TIMER).await?;
+
+        match setting.setting_type() {
+            SettingType::List | SettingType::XList => {
+                let options: Vec<(String, Option<Duration>)> = setting
+                    .elements()
+                    .await?
+                    .into_iter()
+                    .map(|label| {
+                        let duration = parse_duration(&label);
+                        (label, duration)
+                    })
+                    .collect();
+
+                let (label, set) = match duration {
+                    None => options.into_iter().find(|(_, option)| option.is_none()),
+                    Some(duration) => options
+                        .into_iter()
+                        .filter(|(_, option)| option.is_some())
+                        .min_by_key(|(_, option)| {
+                            let option = option.unwrap_or_default();
+                            (option.as_secs() as i64 - duration.as_secs() as i64).abs()
+                        }),
+                }
+                .ok_or_else(Error::setting_non_element)?;
+
+                setting.update(label).await?;
+                Ok(set)
+            }
+            _ => {
+                // Round to the nearest minute
+                let set = duration.map_or(0, |d| (d.as_secs() + 30) / 60);
+                setting.update(set as i32).await?;
+                Ok(minutes(set))
+            }
+        }
+    }
+
+    async fn cec_paths(&self) -> &'static [&'static str] {
+        match self.device_kind().await {
+            DeviceKind::TV => TV_CEC,
+            DeviceKind::SoundBar => SOUNDBAR_CEC,
+        }
+    }
+
+    async fn volume_paths(&self) -> &'static [&'static str] {
+        match self.device_kind().await {
+            DeviceKind::TV => TV_VOLUME,
+            DeviceKind::SoundBar => SOUNDBAR_VOLUME,
+        }
+    }
+
+    async fn arc_paths(&self) -> &'static [&'static str] {
+        match self.device_kind().await {
+            DeviceKind::TV => TV_ARC,
+            DeviceKind::SoundBar => SOUNDBAR_ARC,
+        }
+    }
+
+    /// Whether the device has a setting at any of `paths`
+    async fn has_setting(&self, paths: &[&str]) -> Result<bool> {
+        match settings::at_path(self.clone(), paths).await {
+            Ok(_) => Ok(true),
+            Err(Error::Client(ClientError::SettingPathNotFound(_))) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get the setting behind a convenience method, or [`ClientError::Unsupported`] if the device
+    /// does not have it at any of `paths`
+    async fn feature(&self, feature: &'static str, paths: &[&str]) -> Result<SubSetting> {
+        match settings::at_path(self.clone(), paths).await {
+            Err(Error::Client(ClientError::SettingPathNotFound(_))) => {
+                Err(ClientError::Unsupported { feature }.into())
+            }
+            other => other,
+        }
+    }
+
+    /// Read an on/off setting, which is either a boolean or a list such as "Off"/"On"
+    async fn toggle(&self, feature: &'static str, paths: &[&str]) -> Result<bool> {
+        let setting = self.feature(feature, paths).await?;
+        if let Some(value) = setting.value::<bool>() {
+            return Ok(value);
+        }
+
+        let value = setting
+            .value::<String>()
+            .ok_or(ClientError::NoValue { feature })?;
+        let lower = value.to_lowercase();
+        if TOGGLE_ON.contains(&lower.as_str()) {
+            Ok(true)
+        } else if TOGGLE_OFF.contains(&lower.as_str()) {
+            Ok(false)
+        } else {
+            Err(ClientError::NotAToggle(value).into())
+        }
+    }
+
+    /// Write an on/off setting, which is either a boolean or a list such as "Off"/"On"
+    async fn set_toggle(&self, feature: &'static str, paths: &[&str], on: bool) -> Result<()> {
+        let setting = self.feature(feature, paths).await?;
+        if setting.is_boolean() {
+            return setting.update(on).await;
+        }
+
+        let names = if on { TOGGLE_ON } else { TOGGLE_OFF };
+        let element = setting
+            .elements()
+            .await?
+            .into_iter()
+            .find(|element| names.contains(&element.to_lowercase().as_str()))
+            .ok_or(ClientError::WriteSettingsNotAnElement)?;
+        setting.update(element).await
+    }
+
+    /// Read a list setting's current element
+    async fn list_value(&self, feature: &'static str, paths: &[&str]) -> Result<String> {
+        self.feature(feature, paths)
+            .await?
+            .value::<String>()
+            .ok_or_else(|| ClientError::NoValue { feature }.into())
+    }
+
+    /// Read the elements a list setting can be set to
+    async fn list_elements(&self, feature: &'static str, paths: &[&str]) -> Result<Vec<String>> {
+        self.feature(feature, paths).await?.elements().await
+    }
+
+    /// Write one of a list setting's elements
+    async fn set_list_value(
+        &self,
+        feature: &'static str,
+        paths: &[&str],
+        name: String,
+    ) -> Result<()> {
+        self.feature(feature, paths).await?.update(name).await
+    }
+
+    /// Get a string setting which not every device has
+    async fn optional_string(&self, paths: &[&str]) -> Result<Option<String>> {
+        match settings::at_path(self.clone(), paths).await {
+            Ok(setting) => Ok(setting.value::<String>()),
+            Err(Error::Client(ClientError::SettingPathNotFound(_))) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn slider_value(&self, feature: &'static str, paths: &[&str]) -> Result<i32> {
+        self.feature(feature, paths)
+            .await?
+            .value::<i32>()
+            .ok_or_else(|| ClientError::NoValue { feature }.into())
+    }
+
+    async fn set_slider_value(
+        &self,
+        feature: &'static str,
+        paths: &[&str],
+        value: i32,
+    ) -> Result<i32> {
+        let setting = self.feature(feature, paths).await?;
+        let value = match setting.slider_info().await? {
+            Some(info) => info.snap(value),
+            None => value,
+        };
+        setting.update(value).await?;
+        Ok(value)
+    }
+}
+
+async fn volume_slider(setting: &SubSetting) -> Result<SliderInfo> {
+    setting
+        .slider_info()
+        .await?
+        .ok_or_else(|| ClientError::from("Volume has no slider info").into())
+}
+
+/// Convert a value on the slider to a percent of its range
+fn to_percent(info: &SliderInfo, value: i32) -> f32 {
+    let range = info.max - info.min;
+    if range <= 0 {
+        return 0.0;
+    }
+    (value - info.min) as f32 * 100.0 / range as f32
+}
+
+/// Convert a percent of the slider's range to the nearest value on the slider
+fn from_percent(info: &SliderInfo, percent: f32) -> i32 {
+    let percent = if percent.is_nan() {
+        0.0
+    } else {
+        percent.clamp(0.0, 100.0)
+    };
+    info.min + (percent * (info.max - info.min) as f32 / 100.0).round() as i32
+}
+
+/// Normalize a channel such as "4-1" to "4.1". Returns `None` if it is not a channel.
+fn parse_channel(channel: &str) -> Option<String> {
+    let mut parts = channel.trim().splitn(2, ['.', '-']);
+    let major = parts.next()?;
+    let minor = parts.next();
+
+    let is_number = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit());
+    match minor {
+        None if is_number(major) => Some(major.into()),
+        Some(minor) if is_number(major) && is_number(minor) => Some(format!("{}.{}", major, minor)),
+        _ => None,
+    }
+}
+
+fn minutes(minutes: u64) -> Option<Duration> {
+    match minutes {
+        0 => None,
+        m => Some(Duration::from_secs(m * 60)),
+    }
+}
+
+/// Parse a sleep timer option such as "30 Minutes" or "2 Hours". Options without a number, such
+/// as "Off", are `None`.
+fn parse_duration(label: &str) -> Option<Duration> {
+    let number: String = label
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    let number: u64 = number.parse().ok()?;
+
+    if label.to_lowercase().contains("hour") {
+        minutes(number * 60)
+    } else {
+        minutes(number)
+    }
+}
+
+/// Parse usage hours such as "2,418" or "2418 Hours"
+fn parse_hours(hours: &str) -> Option<u32> {
+    let number: String = hours
+        .trim()
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == ',')
+        .filter(|c| *c != ',')
+        .collect();
+    number.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_channel, parse_duration, parse_hours};
+
+    use std::time::Duration;
+
+    #[test]
+    fn sleep_timer_options() {
+        assert_eq!(parse_duration("Off"), None);
+        assert_eq!(
+            parse_duration("30 Minutes"),
+            Some(Duration::from_secs(1800))
+        );
+        assert_eq!(parse_duration("2 Hours"), Some(Duration::from_secs(7200)));
+    }
+
+    #[test]
+    fn usage_hours() {
+        assert_eq!(parse_hours("2418"), Some(2418));
+        assert_eq!(parse_hours("2,418 Hours"), Some(2418));
+        assert_eq!(parse_hours("N/A"), None);
+        assert_eq!(parse_hours(""), None);
+    }
+
+    #[test]
+    fn channels() {
+        assert_eq!(parse_channel("7"), Some("7".into()));
+        assert_eq!(parse_channel(" 4.1 "), Some("4.1".into()));
+        assert_eq!(parse_channel("4-1"), Some("4.1".into()));
+        assert_eq!(parse_channel(""), None);
+        assert_eq!(parse_channel("4."), None);
+        assert_eq!(parse_channel("4.1.2"), None);
+        assert_eq!(parse_channel("abc"), None);
+    }
+}
@@ -1,11 +1,14 @@
+use regex::Regex;
 use serde::{de, Deserialize, Deserializer};
 
-#[derive(Debug)]
+use std::convert::TryFrom;
+
+#[derive(Debug, Clone)]
 /// Various infomation about the device returned by [`device_info()`](super::Device::device_info)
 pub struct DeviceInfo {
-    /// Device name as known by Google Cast
+    /// Device name as known by Google Cast. See [`Device::cast_name()`](super::Device::cast_name).
     pub cast_name: String,
-    /// Physical inputs on the device
+    /// Physical inputs on the device. See [`physical_inputs()`](Self::physical_inputs).
     pub inputs: Vec<String>,
     /// Device's model name
     pub model_name: String,
@@ -14,11 +17,24 @@ pub struct DeviceInfo {
     /// Device's firmware version
     pub fw_version: String,
     /// URI of root settings
+    #[cfg_attr(test, allow(dead_code))]
     pub(super) settings_root: String,
     /// Device's chipset version
+    #[allow(dead_code)]
     pub(super) chipset: u32,
 }
 
+impl DeviceInfo {
+    /// Names of the hardware inputs on the device, e.g. "HDMI-1"
+    ///
+    /// These are a subset of the inputs from [`list_inputs()`](super::Device::list_inputs), which
+    /// also has inputs that are not hardware, such as SmartCast, along with each input's
+    /// friendly name and hashval. The names here match [`Input::name()`].
+    pub fn physical_inputs(&self) -> &[String] {
+        &self.inputs
+    }
+}
+
 impl<'de> Deserialize<'de> for DeviceInfo {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -78,9 +94,187 @@ impl Input {
         self.friendly_name.clone()
     }
 
-    pub(super) fn hashval(&self) -> u32 {
+    /// Input's hash value, which the device changes when the input changes
+    pub fn hashval(&self) -> u32 {
         self.hashval
     }
+
+    /// Returns true if the input is an HDMI port, whether it is named like "HDMI-1" or "HDMI 1"
+    pub fn is_hdmi(&self) -> bool {
+        hdmi_port(&self.name).is_some()
+    }
+
+    /// Kind of input, from its [`name()`](Self::name). See [`InputKind`].
+    pub fn kind(&self) -> InputKind {
+        InputKind::from_name(&self.name)
+    }
+}
+
+/// Kind of an input, see [`Input::kind()`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputKind {
+    /// HDMI port with its number, e.g. 1 for "HDMI-1". An HDMI input without a number is 0.
+    Hdmi(u8),
+    /// Composite video, named "COMP" on most devices
+    Composite,
+    /// Component video
+    Component,
+    /// The built in tuner, named "TV"
+    Tuner,
+    /// SmartCast, named "CAST"
+    Cast,
+    /// An input this library does not recognize, with its name
+    Other(String),
+}
+
+impl InputKind {
+    pub(super) fn from_name(name: &str) -> Self {
+        if let Some(port) = hdmi_port(name) {
+            if let Ok(port) = u8::try_from(port) {
+                return Self::Hdmi(port);
+            }
+        }
+
+        match name.trim().to_uppercase().as_str() {
+            "COMP" | "COMPOSITE" | "AV" => Self::Composite,
+            "COMPONENT" | "YPBPR" => Self::Component,
+            "TV" | "TUNER" => Self::Tuner,
+            "CAST" | "SMARTCAST" => Self::Cast,
+            _ => Self::Other(name.into()),
+        }
+    }
+}
+
+/// Number of an HDMI port from its input name, e.g. 2 for "HDMI-2", "HDMI 2", or "hdmi2". An HDMI
+/// input without a number is 0. Returns `None` if the input is not HDMI.
+pub(super) fn hdmi_port(name: &str) -> Option<u32> {
+    let name = name.trim();
+    match name.get(..4) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("hdmi") => {}
+        _ => return None,
+    }
+    let number: String = name[4..]
+        .trim_start_matches(|c: char| c == '-' || c == '_' || c.is_whitespace())
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    Some(number.parse().unwrap_or_default())
+}
+
+/// Inputs are equal if they have the same name and hash value
+impl PartialEq for Input {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.hashval == other.hashval
+    }
+}
+
+impl Eq for Input {}
+
+/// The device's clock, see [`device_time()`](super::Device::device_time)
+///
+/// The time is given as the device formats it, e.g. "2021-06-14 19:32".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceTime {
+    /// Current date and time on the device
+    pub time: String,
+    /// Time zone the device is set to, e.g. "Pacific", if it reports one
+    pub time_zone: Option<String>,
+}
+
+/// HDR format supported by the device, see [`hdr_formats()`](super::Device::hdr_formats)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HdrFormat {
+    /// HDR10
+    HDR10,
+    /// HDR10+
+    HDR10Plus,
+    /// Dolby Vision
+    DolbyVision,
+    /// Hybrid Log-Gamma
+    HLG,
+    /// A format this library does not recognize
+    Unknown(String),
+}
+
+impl HdrFormat {
+    /// Parse an HDR setting's option. Returns `None` for options which are not a format, such
+    /// as "Off" or "Auto".
+    pub(super) fn parse(option: &str) -> Option<Self> {
+        let plus = option.contains('+');
+        let name: String = option
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .collect::<String>()
+            .to_lowercase();
+
+        Some(match name.as_str() {
+            "off" | "on" | "auto" | "" => return None,
+            "hdr10" if plus => Self::HDR10Plus,
+            "hdr10plus" => Self::HDR10Plus,
+            "hdr10" => Self::HDR10,
+            "dolbyvision" => Self::DolbyVision,
+            "hlg" => Self::HLG,
+            _ => Self::Unknown(option.into()),
+        })
+    }
+}
+
+/// Video signal on the current input, see
+/// [`current_input_signal()`](super::Device::current_input_signal)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignalFormat {
+    /// A video signal, e.g. "3840x2160@60Hz HDR10"
+    Video {
+        /// Horizontal resolution in pixels
+        width: u32,
+        /// Vertical resolution in pixels
+        height: u32,
+        /// Refresh rate in Hz rounded to a whole number, if the device reports it
+        refresh_rate: Option<u32>,
+        /// HDR format of the signal, or `None` if it is SDR
+        hdr: Option<HdrFormat>,
+    },
+    /// There is no signal, or the device does not report it
+    Unknown,
+}
+
+impl SignalFormat {
+    /// Parse the signal format as the device reports it, such as "3840x2160@60Hz HDR10",
+    /// "1920x1080p 59.94Hz", or "No Signal"
+    pub(super) fn parse(format: &str) -> Self {
+        let captures = match Regex::new(
+            r"(?i)(\d+)\s*[x×]\s*(\d+)[pi]?(?:\s*@?\s*(\d+(?:\.\d+)?)\s*hz\b|\s*@\s*(\d+(?:\.\d+)?))?",
+        )
+        .unwrap()
+        .captures(format)
+        {
+            Some(captures) => captures,
+            None => return Self::Unknown,
+        };
+
+        let (width, height) = match (captures[1].parse(), captures[2].parse()) {
+            (Ok(width), Ok(height)) => (width, height),
+            _ => return Self::Unknown,
+        };
+        let refresh_rate = captures
+            .get(3)
+            .or_else(|| captures.get(4))
+            .and_then(|rate| rate.as_str().parse::<f32>().ok())
+            .map(|rate| rate.round() as u32);
+        // Whatever follows the resolution and refresh rate is the dynamic range
+        let hdr = match HdrFormat::parse(&format[captures.get(0).unwrap().end()..]) {
+            Some(HdrFormat::Unknown(range)) if range.trim().eq_ignore_ascii_case("sdr") => None,
+            Some(HdrFormat::Unknown(range)) => Some(HdrFormat::Unknown(range.trim().into())),
+            hdr => hdr,
+        };
+
+        Self::Video {
+            width,
+            height,
+            refresh_rate,
+            hdr,
+        }
+    }
 }
 
 fn parse_input_friendly<'de, D>(deserializer: D) -> Result<String, D::Error>
@@ -93,3 +287,95 @@ where
             .map_err(|_| de::Error::missing_field("NAME"))
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{hdmi_port, HdrFormat, InputKind, SignalFormat};
+
+    #[test]
+    fn hdmi_names() {
+        assert_eq!(hdmi_port("HDMI-1"), Some(1));
+        assert_eq!(hdmi_port("HDMI 2"), Some(2));
+        assert_eq!(hdmi_port("hdmi3"), Some(3));
+        assert_eq!(hdmi_port("HDMI"), Some(0));
+        assert_eq!(hdmi_port("CAST"), None);
+        assert_eq!(hdmi_port("COMP"), None);
+        assert_eq!(hdmi_port(""), None);
+        assert_eq!(hdmi_port("Ünput"), None);
+    }
+
+    #[test]
+    fn input_kinds() {
+        assert_eq!(InputKind::from_name("HDMI-0"), InputKind::Hdmi(0));
+        assert_eq!(InputKind::from_name("HDMI 3"), InputKind::Hdmi(3));
+        assert_eq!(InputKind::from_name("COMP"), InputKind::Composite);
+        assert_eq!(InputKind::from_name("Component"), InputKind::Component);
+        assert_eq!(InputKind::from_name("TV"), InputKind::Tuner);
+        assert_eq!(InputKind::from_name("CAST"), InputKind::Cast);
+        assert_eq!(
+            InputKind::from_name("HDMI-300"),
+            InputKind::Other("HDMI-300".into())
+        );
+        assert_eq!(InputKind::from_name("USB"), InputKind::Other("USB".into()));
+    }
+
+    #[test]
+    fn hdr_format_parse() {
+        assert_eq!(HdrFormat::parse("HDR10"), Some(HdrFormat::HDR10));
+        assert_eq!(HdrFormat::parse("HDR10+"), Some(HdrFormat::HDR10Plus));
+        assert_eq!(HdrFormat::parse("HDR10 Plus"), Some(HdrFormat::HDR10Plus));
+        assert_eq!(
+            HdrFormat::parse("Dolby Vision"),
+            Some(HdrFormat::DolbyVision)
+        );
+        assert_eq!(HdrFormat::parse("HLG"), Some(HdrFormat::HLG));
+        assert_eq!(HdrFormat::parse("Auto"), None);
+        assert_eq!(HdrFormat::parse("Off"), None);
+        assert_eq!(
+            HdrFormat::parse("Technicolor"),
+            Some(HdrFormat::Unknown("Technicolor".into()))
+        );
+    }
+
+    #[test]
+    fn signal_format_parse() {
+        assert_eq!(
+            SignalFormat::parse("3840x2160@60Hz HDR10"),
+            SignalFormat::Video {
+                width: 3840,
+                height: 2160,
+                refresh_rate: Some(60),
+                hdr: Some(HdrFormat::HDR10),
+            }
+        );
+        assert_eq!(
+            SignalFormat::parse("1920x1080p 59.94Hz"),
+            SignalFormat::Video {
+                width: 1920,
+                height: 1080,
+                refresh_rate: Some(60),
+                hdr: None,
+            }
+        );
+        assert_eq!(
+            SignalFormat::parse("3840 x 2160 @ 24 Dolby Vision"),
+            SignalFormat::Video {
+                width: 3840,
+                height: 2160,
+                refresh_rate: Some(24),
+                hdr: Some(HdrFormat::DolbyVision),
+            }
+        );
+        assert_eq!(
+            SignalFormat::parse("1280x720 SDR"),
+            SignalFormat::Video {
+                width: 1280,
+                height: 720,
+                refresh_rate: None,
+                hdr: None,
+            }
+        );
+        assert_eq!(SignalFormat::parse("No Signal"), SignalFormat::Unknown);
+        assert_eq!(SignalFormat::parse(""), SignalFormat::Unknown);
+    }
+}
@@ -1,35 +1,71 @@
 use super::discover::{ssdp, uaudp_followup, DEFAULT_SSDP_MAXTIME, SSDP_IP};
-use super::error::{Error, Result};
+use super::error::{ApiError, ClientError, Error, Result};
 
 mod apps;
 mod command;
+mod controls;
 mod info;
+mod power;
+mod quirks;
 mod remote;
 mod response;
 mod settings;
-
-pub use self::apps::App;
-pub use self::info::{DeviceInfo, Input};
-pub use self::remote::Button;
-pub use self::settings::{SettingType, SliderInfo, SubSetting};
-
-use self::apps::{AppList, Payload};
-use self::command::{Command, CommandDetail};
-use self::remote::KeyEvent;
+mod watch;
+
+pub use self::apps::{App, AppPayload};
+pub use self::command::{PoolStats, RequestType};
+pub use self::controls::Feature;
+pub use self::info::{DeviceInfo, DeviceTime, HdrFormat, Input, InputKind, SignalFormat};
+pub use self::power::{DeviceKind, PowerState};
+pub use self::quirks::ModelQuirks;
+pub use self::remote::{Button, CodeSet, KeyEvent, KeyResult};
+#[cfg(feature = "last-status")]
+pub use self::response::Status;
+pub use self::settings::{
+    Bounds, EditableSetting, EndpointBase, SettingType, SliderInfo, SubSetting, TaggedSetting,
+};
+pub use self::watch::SettingsWatch;
+
+use self::apps::AppList;
+use self::command::{Command, CommandDetail, PoolCounters};
 use self::response::Response;
-use self::settings::EndpointBase;
+use self::settings::SettingsBases;
 
 use reqwest::Client;
-use tokio::sync::RwLock;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tokio::sync::{mpsc, RwLock, Semaphore};
 
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::future::Future;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 #[allow(dead_code)]
 pub const PORT_OPTIONS: [u16; 2] = [7345, 9000];
 pub const DEFAULT_TIMEOUT: u64 = 3;
+pub const MAX_CLIENT_LEN: usize = 64;
+/// Default minimum milliseconds between KEYDOWN and KEYUP of a timed hold
+pub const DEFAULT_MIN_KEY_HOLD: u64 = 50;
+/// Default number of requests sent to a device at once
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 1;
+/// Default times to try every port before giving up, for devices which are still waking up
+pub const PORT_PROBE_ATTEMPTS: u32 = 3;
+/// Default milliseconds to wait between tries of every port
+pub const PORT_PROBE_DELAY: u64 = 500;
+/// Times to read device info while its settings root is blank, for devices which are still booting
+#[cfg(not(test))]
+pub const SETTINGS_ROOT_ATTEMPTS: u32 = 3;
+/// Milliseconds to wait between reads of device info with a blank settings root
+#[cfg(not(test))]
+pub const SETTINGS_ROOT_DELAY: u64 = 500;
+/// Milliseconds during which a repeated [`power_on()`](Device::power_on) or
+/// [`power_off()`](Device::power_off) is ignored, while the device may still report its old state
+pub const POWER_DEBOUNCE: u64 = 2000;
+/// Challenge type for which the device displays a pin
+pub const PIN_CHALLENGE: u32 = 1;
+pub const PIN_LEN: usize = 4;
 
 /// A SmartCast Device
 ///
@@ -43,7 +79,24 @@ pub struct Device {
     inner: Arc<DeviceRef>,
 }
 
+/// How many times every port is tried, and how long to wait between tries, when connecting
+#[derive(Debug, Clone, Copy)]
+pub(super) struct PortProbe {
+    attempts: u32,
+    delay: Duration,
+}
+
+impl Default for PortProbe {
+    fn default() -> Self {
+        Self {
+            attempts: PORT_PROBE_ATTEMPTS,
+            delay: Duration::from_millis(PORT_PROBE_DELAY),
+        }
+    }
+}
+
 impl Device {
+    #[cfg(test)]
     pub(super) async fn new<S: Into<String>>(
         name: S,
         manufacturer: S,
@@ -51,21 +104,50 @@ impl Device {
         ip_addr: S,
         uuid: S,
     ) -> Result<Self> {
-        log::trace!("Attempting to connect to API");
-
-        // Workaround for testing issues on loopback
-        let ip_addr = match ip_addr.into().as_str() {
-            "127.0.0.1" => "localhost",
-            other => other,
-        }
-        .to_string();
+        Self::with_client(
+            name,
+            manufacturer,
+            model,
+            ip_addr,
+            uuid,
+            Self::default_client()?,
+            PortProbe::default(),
+        )
+        .await
+    }
 
-        // Build Client
-        let client = reqwest::Client::builder()
+    /// Client used for the device API when one is not given
+    pub(super) fn default_client() -> Result<Client> {
+        // SmartCast devices do not compress responses
+        Ok(reqwest::Client::builder()
             .timeout(Duration::from_secs(DEFAULT_TIMEOUT))
             .danger_accept_invalid_certs(true)
             .pool_idle_timeout(Some(Duration::from_secs(5)))
-            .build()?;
+            .no_gzip()
+            .no_brotli()
+            .no_deflate()
+            .build()?)
+    }
+
+    pub(super) async fn with_client<S: Into<String>>(
+        name: S,
+        manufacturer: S,
+        model: S,
+        ip_addr: S,
+        uuid: S,
+        client: Client,
+        probe: PortProbe,
+    ) -> Result<Self> {
+        log::trace!("Attempting to connect to API");
+
+        let ip_addr: String = ip_addr.into();
+        // Workaround for testing issues on loopback. Only for unit tests, so a device genuinely
+        // at 127.0.0.1, such as an emulator, is connected to at the address given.
+        #[cfg(test)]
+        let ip_addr = match ip_addr.as_str() {
+            "127.0.0.1" => "localhost".to_string(),
+            _ => ip_addr,
+        };
 
         // Build Device
         let device = Self {
@@ -74,22 +156,43 @@ impl Device {
                 manufacturer: manufacturer.into(),
                 model: model.into(),
                 settings_root: RwLock::new(String::new()),
+                device_info: RwLock::new(None),
+                prefetched_settings: RwLock::new(None),
+                requires_auth: RwLock::new(None),
+                application_url: RwLock::new(None),
+                min_key_hold: RwLock::new(Duration::from_millis(DEFAULT_MIN_KEY_HOLD)),
+                request_limit: RwLock::new(Arc::new(Semaphore::new(
+                    DEFAULT_MAX_CONCURRENT_REQUESTS,
+                ))),
+                remote_codeset: RwLock::new(None),
+                held_keys: RwLock::new(Vec::new()),
+                remote_recorder: RwLock::new(None),
+                log_request_bodies: RwLock::new(false),
+                settings_base: RwLock::new(SettingsBases::default()),
+                menu_hash_writes: RwLock::new(None),
+                volume_range: RwLock::new(None),
+                volume_setting: RwLock::new(None),
+                supported_features: RwLock::new(HashMap::new()),
+                last_power_press: RwLock::new(None),
+                pool_stats: PoolCounters::default(),
+                #[cfg(feature = "last-status")]
+                last_status: RwLock::new(None),
                 ip_addr,
                 port: RwLock::new(0),
                 uuid: uuid.into(),
                 auth_token: RwLock::new(None),
-                app_list: RwLock::new(AppList::new(client.clone())),
+                app_list: RwLock::new(AppList::new()?),
                 client,
             }),
         };
 
-        device.initialize().await
+        device.initialize(probe).await
     }
 
-    async fn initialize(self) -> Result<Self> {
+    async fn initialize(self, probe: PortProbe) -> Result<Self> {
         log::trace!("Initializing");
         // Check port options
-        self.find_port().await?;
+        self.find_port(probe).await?;
 
         // Get settings root
         self.set_settings_root().await?;
@@ -97,9 +200,29 @@ impl Device {
         Ok(self)
     }
 
+    async fn find_port(&self, probe: PortProbe) -> Result<()> {
+        let mut attempt = 1;
+
+        loop {
+            match self.probe_ports().await {
+                Err(Error::Reqwest(e)) if e.is_connect() && attempt < probe.attempts => {
+                    log::warn!("Could not connect to any port, retrying");
+                    tokio::time::sleep(probe.delay).await;
+                    attempt += 1;
+                }
+                other => return other,
+            }
+        }
+    }
+
     #[cfg(not(test))]
-    async fn find_port(&self) -> Result<()> {
-        let mut iter = PORT_OPTIONS.iter().peekable();
+    async fn probe_ports(&self) -> Result<()> {
+        // Try the port the model is known to use first
+        let mut ports = PORT_OPTIONS.to_vec();
+        if let Some(preferred) = self.model_quirks().preferred_port {
+            ports.sort_by_key(|port| *port != preferred);
+        }
+        let mut iter = ports.iter().peekable();
 
         loop {
             if let Some(port) = iter.next() {
@@ -126,11 +249,30 @@ impl Device {
 
     #[cfg(not(test))]
     async fn set_settings_root(&self) -> Result<()> {
-        let device_info = self.device_info().await?;
         log::trace!("Set settings root URI");
 
+        // Every settings endpoint would be built with an empty segment. Some devices leave the
+        // root out of device info for a moment after waking up, so ask again before giving up.
+        let mut attempt = 1;
+        let device_info = loop {
+            let device_info = self.device_info().await?;
+            if !device_info.settings_root.trim().is_empty() {
+                break device_info;
+            }
+            if attempt >= SETTINGS_ROOT_ATTEMPTS {
+                log::error!("Device info has no settings root");
+                return Err(ClientError::MissingSettingsRoot.into());
+            }
+            log::warn!("Device info has no settings root, retrying...");
+            attempt += 1;
+            tokio::time::sleep(Duration::from_millis(SETTINGS_ROOT_DELAY)).await;
+        };
+
         let mut settings_root = self.inner.settings_root.write().await;
-        *settings_root = device_info.settings_root;
+        *settings_root = device_info.settings_root.clone();
+
+        let mut cached_info = self.inner.device_info.write().await;
+        *cached_info = Some(device_info);
 
         Ok(())
     }
@@ -141,22 +283,56 @@ impl Device {
     ///
     /// ```
     /// # async fn example() -> Result<(), smartcast::Error> {
-
     /// use smartcast::Device;
     ///
     /// let ip_addr = "192.168.0.14";
     /// let dev: Device = Device::from_ip(ip_addr).await?;
     /// println!("{}", dev.name());
     /// // > "Living Room TV"
-
     /// # Ok(())
     /// # }
     /// ```
     pub async fn from_ip<S: Into<String>>(ip_addr: S) -> Result<Self> {
-        let ip_addr: String = ip_addr.into();
+        Self::from_ip_probed(ip_addr.into(), PortProbe::default()).await
+    }
+
+    /// Same as [`from_ip()`](Self::from_ip), but try every port `attempts` times, waiting `delay`
+    /// between tries, before giving up
+    ///
+    /// A device which was just woken, such as by Wake-on-LAN, may serve its description before
+    /// its API is up. By default every port is tried 3 times, half a second apart. An `attempts`
+    /// of 0 is treated as 1.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    /// use std::time::Duration;
+    ///
+    /// // Give a slow device up to 10 seconds to start its API
+    /// let dev = Device::from_ip_with_probe("192.168.0.14", 10, Duration::from_secs(1)).await?;
+    /// println!("{}", dev.name());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn from_ip_with_probe<S: Into<String>>(
+        ip_addr: S,
+        attempts: u32,
+        delay: Duration,
+    ) -> Result<Self> {
+        let probe = PortProbe {
+            attempts: attempts.max(1),
+            delay,
+        };
+        Self::from_ip_probed(ip_addr.into(), probe).await
+    }
+
+    async fn from_ip_probed(ip_addr: String, probe: PortProbe) -> Result<Self> {
         log::info!("Attempt API connection to IP '{}'", ip_addr);
 
-        match uaudp_followup(&format!("http://{}:8008/ssdp/device-desc.xml", ip_addr)).await? {
+        let location = format!("http://{}:8008/ssdp/device-desc.xml", ip_addr);
+        match uaudp_followup(&location, &Self::default_client()?, probe).await? {
             Some(device) => Ok(device),
             None => {
                 log::error!("Device not found at '{}'", ip_addr);
@@ -165,20 +341,50 @@ impl Device {
         }
     }
 
-    /// Connect to a SmartCast device from the device's UUID
+    /// Connect to a SmartCast device from the url of its description xml
+    ///
+    /// [`from_ip()`](Self::from_ip) looks for the description at
+    /// `http://{ip}:8008/ssdp/device-desc.xml`, where SmartCast devices serve it. This is for
+    /// devices which serve it elsewhere, such as behind a port forward or on custom firmware. The
+    /// url is the same as the `LOCATION` header of the device's SSDP replies.
     ///
     /// # Example
     ///
     /// ```
     /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_url("http://192.168.0.14:18008/ssdp/device-desc.xml").await?;
+    /// println!("{}", dev.name());
+    /// // > "Living Room TV"
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn from_url<S: Into<String>>(location: S) -> Result<Self> {
+        let location: String = location.into();
+        log::info!("Attempt API connection to description at '{}'", location);
 
+        match uaudp_followup(&location, &Self::default_client()?, PortProbe::default()).await? {
+            Some(device) => Ok(device),
+            None => {
+                log::error!("Device not found at '{}'", location);
+                Err(ClientError::DeviceNotFoundUrl(location).into())
+            }
+        }
+    }
+
+    /// Connect to a SmartCast device from the device's UUID
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
     /// use smartcast::Device;
     ///
     /// let uuid = "cb72c9c8-2d45-65b6-424a-13fa25a650db";
     /// let dev: Device = Device::from_uuid(uuid).await?;
     /// println!("{}", dev.name());
     /// // > "Living Room TV"
-
     /// # Ok(())
     /// # }
     /// ```
@@ -186,7 +392,12 @@ impl Device {
         let uuid: String = uuid.into();
         log::info!("Attempt API connection to device with UUID '{}'", uuid);
 
-        let mut device_vec = ssdp(SSDP_IP, &format!("uuid:{}", uuid), DEFAULT_SSDP_MAXTIME).await?;
+        Self::from_uuid_via(SSDP_IP, uuid).await
+    }
+
+    /// Search for a device by UUID, sending the SSDP request to `ssdp_ip`
+    pub(crate) async fn from_uuid_via(ssdp_ip: &str, uuid: String) -> Result<Self> {
+        let mut device_vec = ssdp(ssdp_ip, &format!("uuid:{}", uuid), DEFAULT_SSDP_MAXTIME).await?;
         if !device_vec.is_empty() {
             Ok(device_vec.swap_remove(0))
         } else {
@@ -195,16 +406,164 @@ impl Device {
         }
     }
 
+    /// Connect to a SmartCast device from its last known IP, falling back to a search by UUID
+    /// if the device cannot be reached there. This is the most robust way to reconnect to a
+    /// device that was saved earlier, since the IP may have changed since.
+    ///
+    /// The search by UUID is also used if a different device, with another UUID, now has the IP.
+    /// Errors other than the IP not being reachable are returned without searching.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let ip = "192.168.0.14";
+    /// let uuid = "cb72c9c8-2d45-65b6-424a-13fa25a650db";
+    /// let dev: Device = Device::connect_best(ip, uuid).await?;
+    /// println!("{}", dev.ip());
+    /// // > "192.168.0.27"
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn connect_best(ip_addr: &str, uuid: &str) -> Result<Self> {
+        Self::connect_best_via(SSDP_IP, ip_addr, uuid).await
+    }
+
+    pub(crate) async fn connect_best_via(ssdp_ip: &str, ip_addr: &str, uuid: &str) -> Result<Self> {
+        match Self::from_ip(ip_addr).await {
+            Ok(device) if device.uuid() == uuid => return Ok(device),
+            Ok(device) => log::warn!(
+                "Found '{}' at '{}' instead, searching by UUID",
+                device.uuid(),
+                ip_addr
+            ),
+            Err(e)
+                if e.is_connect()
+                    || e.is_timeout()
+                    || matches!(e, Error::Client(ClientError::DeviceNotFoundIP(_))) =>
+            {
+                log::warn!(
+                    "Could not connect to '{}' ({}), searching by UUID instead",
+                    ip_addr,
+                    e
+                )
+            }
+            Err(e) => return Err(e),
+        }
+        Self::from_uuid_via(ssdp_ip, uuid.into()).await
+    }
+
+    /// Connect to the device again, such as after its IP changed, keeping the client's session
+    ///
+    /// The device is found the same way as with [`connect_best()`](Self::connect_best), from its
+    /// last known IP or by UUID. The auth token is carried over and verified with the device
+    /// again; if it is rejected, the new client has no token and must be paired. Cached settings
+    /// are carried over as well. Returns [`ClientError::DeviceNotFoundUUID`] if only a different
+    /// device was found, without sending it the token.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl").await?;
+    ///
+    /// // Later, after the device was given a new IP
+    /// let dev = dev.reconnect().await?;
+    /// println!("{} {:?}", dev.ip(), dev.auth_token().await);
+    /// // > 192.168.0.27 Some("Z2zscc1udl")
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn reconnect(&self) -> Result<Self> {
+        self.reconnect_via(SSDP_IP).await
+    }
+
+    pub(crate) async fn reconnect_via(&self, ssdp_ip: &str) -> Result<Self> {
+        log::info!("Reconnecting to '{}'", self.name());
+        let device = Self::connect_best_via(ssdp_ip, &self.ip(), &self.uuid()).await?;
+
+        // The session belongs to this device, the token must never be sent to another
+        if device.uuid() != self.uuid() {
+            log::error!(
+                "Found '{}' instead of '{}' when reconnecting",
+                device.uuid(),
+                self.uuid()
+            );
+            return Err(Error::device_not_found_uuid(self.uuid()));
+        }
+
+        let prefetched = self.inner.prefetched_settings.read().await.clone();
+        *device.inner.prefetched_settings.write().await = prefetched;
+        *device.inner.settings_base.write().await = *self.inner.settings_base.read().await;
+        *device.inner.menu_hash_writes.write().await = *self.inner.menu_hash_writes.read().await;
+        *device.inner.volume_range.write().await = *self.inner.volume_range.read().await;
+        // The volume setting is looked up again, its endpoint may have moved with the firmware
+
+        if let Some(token) = self.auth_token().await {
+            *device.inner.auth_token.write().await = Some(token);
+            if !device.verify_auth_token().await? {
+                log::warn!(
+                    "Auth token was rejected after reconnecting, the client must pair again"
+                );
+                device.inner.auth_token.write().await.take();
+            }
+        }
+
+        Ok(device)
+    }
+
     /// Get device's 'friendly' name
+    ///
+    /// This is the name from the device's description xml, which SSDP discovery reports. It may
+    /// differ from the name the device goes by in Google Cast, see [`cast_name()`](Self::cast_name).
     pub fn name(&self) -> String {
         self.inner.name.clone()
     }
 
+    /// Get the name the device goes by in Google Cast
+    ///
+    /// Apps which find or control the device through Google Cast should use this name, which
+    /// may differ from [`name()`](Self::name). It is read from the [`DeviceInfo`] cached when the
+    /// client connected, so no request is sent. Returns `None` if nothing has been cached yet.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// println!("{} / {:?}", dev.name(), dev.cast_name().await);
+    /// // > Living Room TV / Some("Living Room")
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn cast_name(&self) -> Option<String> {
+        self.inner
+            .device_info
+            .read()
+            .await
+            .as_ref()
+            .map(|info| info.cast_name.clone())
+    }
+
     /// Get device's model name
     pub fn model_name(&self) -> String {
         self.inner.model.clone()
     }
 
+    /// Get the quirks known for the device's model, such as which port and remote codes it uses
+    ///
+    /// These are applied automatically. See [`ModelQuirks`].
+    pub fn model_quirks(&self) -> ModelQuirks {
+        ModelQuirks::for_model(&self.inner.model)
+    }
+
     /// Get device's local IP
     pub fn ip(&self) -> String {
         self.inner.ip_addr.clone()
@@ -258,120 +617,407 @@ impl Device {
         }
     }
 
-    /// Get various information about the device in the form of [`DeviceInfo`]
-    pub async fn device_info(&self) -> Result<DeviceInfo> {
-        log::trace!("Get Device Info");
-        self.send_command(CommandDetail::GetDeviceInfo)
-            .await?
-            .into()
-    }
-
-    /// Begin the pairing process
+    /// Forget the client's auth token, such as after un-pairing or when the token was rejected
     ///
-    /// The device will enter pairing mode upon calling this method with a `Client Name` which will be displayed
-    /// in the device's "Mobile Devices" page, along with a `Client ID` which will be used to identify the client.
+    /// Commands which need pairing return [`ApiError::RequiresPairing`] afterwards, until a token
+    /// is set again with [`set_auth_token()`](Self::set_auth_token) or by pairing.
     ///
-    /// This method returns `pairing data` consisting of a `Pairing Token`, a `Challenge Type`, and the `Client ID` which
-    /// will need to be passed into [`finish_pair()`](Self::finish_pair)
-    /// or [`cancel_pair()`](Self::cancel_pair).
+    /// # Example
     ///
-    /// Note: It may not be necessary to pair your device if it is a soundbar.
-    pub async fn begin_pair<S: Into<String>>(
-        &self,
-        client_name: S,
-        client_id: S,
-    ) -> Result<(u32, u32, String)> {
-        let client_name: String = client_name.into();
-        let client_id: String = client_id.into();
-        log::trace!("Begin Pairing");
-        log::debug!("client_name: {}, client_id: {}", client_name, client_id);
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl").await?;
+    ///
+    /// dev.clear_auth_token().await;
+    /// assert_eq!(dev.auth_token().await, None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn clear_auth_token(&self) {
+        log::trace!("Clear auth token");
+        self.inner.auth_token.write().await.take();
+    }
 
-        self.send_command(CommandDetail::StartPairing {
-            client_name,
-            client_id: client_id.clone(),
-        })
-        .await?
-        .pairing()
-        .map(|(token, challenge)| (token, challenge, client_id))
+    /// Check whether the device accepts the client's current auth token
+    ///
+    /// Returns `true` if no token is set and the device does not need one, as is often the case
+    /// with soundbars.
+    pub async fn verify_auth_token(&self) -> Result<bool> {
+        log::trace!("Verify auth token");
+        match self.send_command(CommandDetail::GetCurrentInput).await {
+            Ok(_) => Ok(true),
+            Err(Error::Api(ApiError::RequiresPairing)) => Ok(false),
+            Err(e) => Err(e),
+        }
     }
 
-    /// Finish the pairing process
+    /// Pair with the device only if the current auth token does not already work
     ///
-    /// Upon calling this method with the `pairing data` returned from
-    /// [`begin_pair()`](Self::begin_pair) and the pin displayed
-    /// by the device, the pairing process will end and the client will be paired.
+    /// Pairing a client which is already paired is a common cause of `Blocked` errors. This checks
+    /// the current token with [`verify_auth_token()`](Self::verify_auth_token) first, and only
+    /// if it is rejected goes through [`begin_pair()`](Self::begin_pair) and
+    /// [`finish_pair()`](Self::finish_pair). `pin_provider` is called for the pin displayed by the
+    /// device; if it returns an error, pairing is canceled.
+    ///
+    /// Returns the auth token in use afterwards, which is unchanged if the client was already
+    /// paired and `None` if the device does not need one.
     ///
     /// # Example
     ///
     /// ```
-    /// # async fn example() -> Result<String, smartcast::Error> {
-
+    /// # async fn example() -> Result<(), smartcast::Error> {
     /// use smartcast::Device;
     /// use std::io::stdin;
     ///
-    /// let mut dev = Device::from_ip("192.168.0.14").await?;
-    ///
-    /// let client_name = "My App Name";
-    /// let client_id = "myapp-rs";
-    ///
-    /// // Begin Pairing
-    /// let pairing_data = dev.begin_pair(client_name, client_id).await?;
-    ///
-    /// // Input pin displayed on screen
-    /// let mut pin = String::new();
-    /// stdin().read_line(&mut pin);
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl").await.ok();
     ///
-    /// // Finish Pairing
-    /// let auth_token = dev.finish_pair(pairing_data, &pin).await?;
-    /// println!("{}", auth_token);
-    /// // > "Z2zscc1udl"
-
-    /// # Ok(auth_token)
+    /// let auth_token = dev
+    ///     .ensure_paired("My App Name", "myapp-rs", || async {
+    ///         let mut pin = String::new();
+    ///         stdin().read_line(&mut pin)?;
+    ///         Ok(pin)
+    ///     })
+    ///     .await?;
+    /// # Ok(())
     /// # }
     /// ```
-    pub async fn finish_pair<S: Into<String>>(
-        &mut self,
-        pairing_data: (u32, u32, String),
-        pin: S,
-    ) -> Result<String> {
-        let (pairing_token, challenge, client_id) = pairing_data;
-        // Strip non digits
-        let pin: String = pin.into().chars().filter(|c| c.is_digit(10)).collect();
-        log::trace!("Finsh Pairing");
-        log::debug!(
-            "pairing_token: {}, challenge: {}, client_id: {}, pin: {}",
-            pairing_token,
-            challenge,
-            client_id,
-            pin
-        );
+    pub async fn ensure_paired<S, F, Fut>(
+        &self,
+        client_name: S,
+        client_id: S,
+        pin_provider: F,
+    ) -> Result<Option<String>>
+    where
+        S: Into<String>,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<String>>,
+    {
+        log::trace!("Ensure paired");
+        if self.verify_auth_token().await? {
+            return Ok(self.auth_token().await);
+        }
 
-        self.send_command(CommandDetail::FinishPairing {
-            client_id,
-            pairing_token,
-            challenge,
-            response_value: pin,
-        })
-        .await?
-        .auth_token()
+        let pairing_data = self.begin_pair(client_name, client_id).await?;
+        let pin = match pin_provider().await {
+            Ok(pin) => pin,
+            Err(e) => {
+                log::warn!("No pin was given, canceling pairing");
+                self.cancel_pair(pairing_data).await?;
+                return Err(e);
+            }
+        };
+        let auth_token = self.clone().finish_pair(pairing_data, pin).await?;
+        self.set_auth_token(auth_token.clone()).await?;
+        Ok(Some(auth_token))
     }
 
-    /// Cancel the pairing process
+    /// Check whether the device must be paired before it can be controlled
     ///
-    /// Upon calling this method with the `pairing data` returned from
-    /// [`begin_pair()`](Self::begin_pair),
-    /// the pairing process will be canceled and the device will leave pairing mode.
+    /// Soundbars often accept commands without an auth token while TVs do not. This sends a
+    /// command which normally requires pairing without a token and checks whether the device
+    /// rejects it. The answer is cached, so later calls do not contact the device.
     ///
     /// # Example
     ///
     /// ```
     /// # async fn example() -> Result<(), smartcast::Error> {
-
     /// use smartcast::Device;
     ///
-    /// let mut dev = Device::from_ip("192.168.0.14").await?;
+    /// let dev = Device::from_ip("192.168.0.14").await?;
     ///
-    /// let client_name = "My App Name";
+    /// if dev.requires_auth().await? {
+    ///     let pairing_data = dev.begin_pair("My App Name", "myapp-rs").await?;
+    ///     // ...
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn requires_auth(&self) -> Result<bool> {
+        log::trace!("Requires auth");
+        if let Some(requires_auth) = *self.inner.requires_auth.read().await {
+            return Ok(requires_auth);
+        }
+
+        let requires_auth = match Command::new(self.clone(), CommandDetail::GetCurrentInput)
+            .await
+            .without_auth_token()
+            .send()
+            .await
+        {
+            Ok(_) => false,
+            Err(Error::Api(ApiError::RequiresPairing)) => true,
+            Err(e) => return Err(e),
+        };
+
+        *self.inner.requires_auth.write().await = Some(requires_auth);
+        Ok(requires_auth)
+    }
+
+    /// Get various information about the device in the form of [`DeviceInfo`]
+    pub async fn device_info(&self) -> Result<DeviceInfo> {
+        log::trace!("Get Device Info");
+        self.send_command(CommandDetail::GetDeviceInfo)
+            .await?
+            .into()
+    }
+
+    /// Get the device info exactly as the device reports it
+    ///
+    /// [`device_info()`](Self::device_info) only keeps the fields of [`DeviceInfo`], while
+    /// firmware often reports more, such as the board revision. This returns the whole `VALUE`
+    /// object of the response so those fields can be read too.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// let info = dev.device_info_raw().await?;
+    /// println!("{}", serde_json::to_string_pretty(&info)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn device_info_raw(&self) -> Result<Value> {
+        log::trace!("Get Raw Device Info");
+        self.send_command(CommandDetail::GetDeviceInfo)
+            .await?
+            .device_info_raw()
+    }
+
+    /// Prepare the client for use so the first calls made on it are fast
+    ///
+    /// This fetches and caches [`DeviceInfo`] if it has not been already. If `prefetch_settings` is
+    /// true, the root of the settings tree is also fetched and handed out by the next call to
+    /// [`settings()`](Self::settings). Calls after that fetch fresh settings as usual. Writing any
+    /// setting drops the prefetched root, so a value is never handed out from before a write.
+    ///
+    /// Calling this more than once is harmless; nothing is fetched that is already cached.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// dev.warm_up(true).await?;
+    /// let settings = dev.settings().await?; // No request is sent
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn warm_up(&self, prefetch_settings: bool) -> Result<()> {
+        log::trace!("Warm Up");
+
+        if self.inner.device_info.read().await.is_none() {
+            let device_info = self.device_info().await?;
+            let mut cached_info = self.inner.device_info.write().await;
+            *cached_info = Some(device_info);
+        }
+
+        if prefetch_settings && self.inner.prefetched_settings.read().await.is_none() {
+            // Stored without the device, which would otherwise keep itself alive
+            let settings = settings::root(self.clone())
+                .await?
+                .into_iter()
+                .map(SubSetting::without_device)
+                .collect();
+            let mut prefetched = self.inner.prefetched_settings.write().await;
+            *prefetched = Some(settings);
+        }
+
+        Ok(())
+    }
+
+    /// Get the [`DeviceInfo`] cached when the client connected or by [`warm_up()`](Self::warm_up)
+    ///
+    /// Unlike [`device_info()`](Self::device_info) this does not send a request. Returns `None` if
+    /// nothing has been cached yet.
+    pub async fn cached_device_info(&self) -> Option<DeviceInfo> {
+        self.inner.device_info.read().await.clone()
+    }
+
+    /// Begin the pairing process
+    ///
+    /// The device will enter pairing mode upon calling this method with a `Client Name` which will be displayed
+    /// in the device's "Mobile Devices" page, along with a `Client ID` which will be used to identify the client.
+    ///
+    /// This method returns `pairing data` consisting of a `Pairing Token`, a `Challenge Type`, and the `Client ID` which
+    /// will need to be passed into [`finish_pair()`](Self::finish_pair)
+    /// or [`cancel_pair()`](Self::cancel_pair).
+    ///
+    /// Both the `Client Name` and `Client ID` must be non-empty and at most 64 characters. The same
+    /// `Client ID` must be used for the rest of the pairing process.
+    ///
+    /// Note: It may not be necessary to pair your device if it is a soundbar.
+    pub async fn begin_pair<S: Into<String>>(
+        &self,
+        client_name: S,
+        client_id: S,
+    ) -> Result<(u32, u32, String)> {
+        let client_name: String = client_name.into();
+        let client_id: String = client_id.into();
+        log::trace!("Begin Pairing");
+        log::debug!("client_name: {}, client_id: {}", client_name, client_id);
+        validate_client(&client_name, &client_id)?;
+
+        self.send_command(CommandDetail::StartPairing {
+            client_name,
+            client_id: client_id.clone(),
+        })
+        .await?
+        .pairing()
+        .map(|(token, challenge)| (token, challenge, client_id))
+    }
+
+    /// Get the json body [`begin_pair()`](Self::begin_pair) would send, without sending it
+    ///
+    /// This is for tools which send the pairing requests over their own transport. The body is
+    /// sent with a PUT to `/pairing/start`. The reply can be read with
+    /// [`parse_pairing_response()`](Self::parse_pairing_response). Most users should call
+    /// [`begin_pair()`](Self::begin_pair) instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    ///
+    /// let body = dev.pairing_request_body("My App Name", "myapp-rs").await?;
+    /// // Send `body` and get the reply over another transport
+    /// # let reply = String::new();
+    /// let pairing_data = Device::parse_pairing_response(&reply, "myapp-rs")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn pairing_request_body<S: Into<String>>(
+        &self,
+        client_name: S,
+        client_id: S,
+    ) -> Result<Value> {
+        let client_name: String = client_name.into();
+        let client_id: String = client_id.into();
+        log::trace!("Pairing Request Body");
+        validate_client(&client_name, &client_id)?;
+
+        let command = Command::new(
+            self.clone(),
+            CommandDetail::StartPairing {
+                client_name,
+                client_id,
+            },
+        )
+        .await;
+        Ok(serde_json::to_value(&command)?)
+    }
+
+    /// Read the `pairing data` from the device's reply to a pairing request body
+    ///
+    /// `response` is the reply to the body from
+    /// [`pairing_request_body()`](Self::pairing_request_body), and `client_id` is the one used to
+    /// build it. The `pairing data` is the same as is returned by
+    /// [`begin_pair()`](Self::begin_pair).
+    pub fn parse_pairing_response<S: Into<String>>(
+        response: &str,
+        client_id: S,
+    ) -> Result<(u32, u32, String)> {
+        log::trace!("Parse Pairing Response");
+        response::process(response.into())?
+            .pairing()
+            .map(|(token, challenge)| (token, challenge, client_id.into()))
+    }
+
+    /// Finish the pairing process
+    ///
+    /// Upon calling this method with the `pairing data` returned from
+    /// [`begin_pair()`](Self::begin_pair) and the pin displayed
+    /// by the device, the pairing process will end and the client will be paired.
+    ///
+    /// Anything other than digits is ignored in the pin. If the device displayed a pin, an error
+    /// is returned without contacting the device when fewer than 4 digits are left.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<String, smartcast::Error> {
+    /// use smartcast::Device;
+    /// use std::io::stdin;
+    ///
+    /// let mut dev = Device::from_ip("192.168.0.14").await?;
+    ///
+    /// let client_name = "My App Name";
+    /// let client_id = "myapp-rs";
+    ///
+    /// // Begin Pairing
+    /// let pairing_data = dev.begin_pair(client_name, client_id).await?;
+    ///
+    /// // Input pin displayed on screen
+    /// let mut pin = String::new();
+    /// stdin().read_line(&mut pin);
+    ///
+    /// // Finish Pairing
+    /// let auth_token = dev.finish_pair(pairing_data, &pin).await?;
+    /// println!("{}", auth_token);
+    /// // > "Z2zscc1udl"
+    /// # Ok(auth_token)
+    /// # }
+    /// ```
+    pub async fn finish_pair<S: Into<String>>(
+        &mut self,
+        pairing_data: (u32, u32, String),
+        pin: S,
+    ) -> Result<String> {
+        let (pairing_token, challenge, client_id) = pairing_data;
+        // Strip non digits
+        let entered = pin.into();
+        let pin: String = entered.chars().filter(|c| c.is_ascii_digit()).collect();
+        log::trace!("Finsh Pairing");
+        log::debug!(
+            "pairing_token: {}, challenge: {}, client_id: {}, pin: {}",
+            pairing_token,
+            challenge,
+            client_id,
+            pin
+        );
+
+        if challenge == PIN_CHALLENGE && pin.len() < PIN_LEN {
+            return Err(ClientError::InvalidPin(entered).into());
+        }
+
+        self.send_command(CommandDetail::FinishPairing {
+            client_id,
+            pairing_token,
+            challenge,
+            response_value: pin,
+        })
+        .await?
+        .auth_token()
+    }
+
+    /// Cancel the pairing process
+    ///
+    /// Upon calling this method with the `pairing data` returned from
+    /// [`begin_pair()`](Self::begin_pair),
+    /// the pairing process will be canceled and the device will leave pairing mode.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let mut dev = Device::from_ip("192.168.0.14").await?;
+    ///
+    /// let client_name = "My App Name";
     /// let client_id = "myapp-rs";
     ///
     /// // Begin Pairing
@@ -379,7 +1025,6 @@ impl Device {
     ///
     /// // Cancel Pairing
     /// dev.cancel_pair(pairing_data).await?;
-
     /// # Ok(())
     /// # }
     /// ```
@@ -408,7 +1053,6 @@ impl Device {
     ///
     /// ```
     /// # async fn example() -> Result<(), smartcast::Error> {
-
     /// use smartcast::{Device, Button};
     ///
     /// let dev = Device::from_ip("192.168.0.14").await?;
@@ -418,24 +1062,120 @@ impl Device {
     /// if !dev.is_powered_on().await? {
     ///     dev.key_press(Button::PowerOn).await?;
     /// }
-
     /// # Ok(())
     /// # }
     /// ```
     pub async fn is_powered_on(&self) -> Result<bool> {
         log::trace!("Power status");
-        self.send_command(CommandDetail::GetPowerState)
+        Ok(self.power_state().await? == PowerState::On)
+    }
+
+    /// Get the device's [`PowerState`]
+    ///
+    /// Soundbars report more states than TVs, so the raw power mode is interpreted according to
+    /// [`device_kind()`](Self::device_kind). See [`PowerState`] for the mapping.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::{Device, PowerState};
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    ///
+    /// if dev.power_state().await? == PowerState::Standby {
+    ///     println!("Soundbar is waiting for a connection");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn power_state(&self) -> Result<PowerState> {
+        log::trace!("Power state");
+        let mode = self
+            .send_command(CommandDetail::GetPowerState)
             .await?
-            .power_state()
+            .power_mode()?;
+        Ok(PowerState::from_mode(self.device_kind().await, mode))
     }
 
-    /// Emulates a simple remote control button press
+    /// Power on the device, unless it is already on
+    ///
+    /// Unlike pressing [`Button::PowerToggle`], this is safe to call again when a call is retried
+    /// or repeated by a double click. The power state is read first and nothing is sent if the
+    /// device is already on. Devices may report their old state for a moment after a power
+    /// button press, so a repeated call within 2 seconds of the press does nothing as well.
     ///
     /// # Example
     ///
     /// ```
     /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// dev.power_on().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn power_on(&self) -> Result<()> {
+        log::trace!("Power on");
+        self.set_power(true).await
+    }
 
+    /// Power off the device, unless it is already off
+    ///
+    /// A soundbar in [`Standby`](PowerState::Standby) counts as off. See
+    /// [`power_on()`](Self::power_on).
+    pub async fn power_off(&self) -> Result<()> {
+        log::trace!("Power off");
+        self.set_power(false).await
+    }
+
+    async fn set_power(&self, on: bool) -> Result<()> {
+        let mut last_press = self.inner.last_power_press.write().await;
+        if let Some((last_on, at)) = *last_press {
+            if last_on == on && at.elapsed() < Duration::from_millis(POWER_DEBOUNCE) {
+                log::debug!("Power {} was just sent, ignoring", on);
+                return Ok(());
+            }
+        }
+
+        let powered_on = match self.power_state().await? {
+            PowerState::On => true,
+            PowerState::Off | PowerState::Standby => false,
+            // Let the device sort it out, the discrete buttons do nothing in the target state
+            PowerState::Unknown(_) => !on,
+        };
+        if powered_on == on {
+            log::debug!(
+                "Device is already powered {}",
+                if on { "on" } else { "off" }
+            );
+            return Ok(());
+        }
+
+        let button = if on {
+            Button::PowerOn
+        } else {
+            Button::PowerOff
+        };
+        self.key_press(button).await?;
+        *last_press = Some((on, Instant::now()));
+        Ok(())
+    }
+
+    /// Get the kind of device, a TV or a soundbar
+    pub async fn device_kind(&self) -> DeviceKind {
+        DeviceKind::from_settings_root(&self.settings_root().await)
+    }
+
+    /// Emulates a simple remote control button press
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
     /// use smartcast::{Device, Button};
     ///
     /// let mut dev = Device::from_ip("192.168.0.14").await?;
@@ -443,250 +1183,1305 @@ impl Device {
     ///
     /// // Increase Volume
     /// dev.key_press(Button::VolumeUp).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn key_press(&self, button: Button) -> Result<()> {
+        log::trace!("Virtual Remote Key Press");
+        self.virtual_remote(KeyEvent::Press, button).await.map(drop)
+    }
 
+    /// Same as [`key_press()`](Self::key_press), but returns what was sent. See [`KeyResult`].
+    ///
+    /// This is for diagnosing remote problems on devices which use the alternate directional
+    /// codes, where a press is otherwise silently resent with the other code set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::{Button, Device};
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// let result = dev.key_press_detailed(Button::Left).await?;
+    /// println!("{:?} (fallback: {}) {:?}", result.codeset, result.fallback, result.codes);
+    /// // > Secondary (fallback: true) [(3, 4)]
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn key_press_detailed(&self, button: Button) -> Result<KeyResult> {
+        log::trace!("Virtual Remote Key Press Detailed");
+        self.virtual_remote(KeyEvent::Press, button).await
+    }
+
+    /// Emulates holding down a remote control button
+    ///
+    /// If a duration is specified, the remote button will be held down for the duration.
+    /// Otherwise it will be held down indefinitely and [`key_up()`](Self::key_up) must be called.
+    ///
+    /// A duration shorter than the minimum set by [`set_min_key_hold()`](Self::set_min_key_hold)
+    /// is lengthened to it. The button is released even if the returned future is dropped while
+    /// it is held.
+    ///
+    /// Buttons still held when the last clone of the `Device` is dropped are released on a best
+    /// effort basis. The KEYUP is sent from a task spawned on the current tokio runtime, so it is
+    /// skipped when there is none and may be lost if the runtime shuts down first.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::{Device, Button};
+    /// use std::time::Duration;
+    ///
+    /// let mut dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// // Increase Volume for 5 seconds
+    /// dev.key_down(Button::VolumeUp, Some(Duration::from_secs(5))).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn key_down(&self, button: Button, duration: Option<Duration>) -> Result<()> {
+        log::trace!("Virtual Remote Key Down");
+        log::debug!("key_down duration: {:?}", duration);
+
+        match duration {
+            Some(duration) => {
+                let hold = duration.max(*self.inner.min_key_hold.read().await);
+                self.hold_for(button, hold).await
+            }
+            None => self.virtual_remote(KeyEvent::Down, button).await.map(drop),
+        }
+    }
+
+    /// Emulates a long press of a remote control button
+    ///
+    /// Some functions are only reached by holding a button, such as the quick menu from holding
+    /// [`Button::InputNext`]. The button is held down for `duration` and then released. This is
+    /// the same as [`key_down()`](Self::key_down) with a duration, which is meant for buttons that
+    /// repeat while held such as volume, except the duration is not lengthened to the
+    /// [minimum hold](Self::set_min_key_hold). The button is released even if the returned future
+    /// is dropped while it is held.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::{Button, Device};
+    /// use std::time::Duration;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// // Open the quick input menu
+    /// dev.long_press(Button::InputNext, Duration::from_secs(2)).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn long_press(&self, button: Button, duration: Duration) -> Result<()> {
+        log::trace!("Virtual Remote Long Press");
+        log::debug!("long_press duration: {:?}", duration);
+        self.hold_for(button, duration).await
+    }
+
+    /// Hold `button` down for `hold` then release it
+    async fn hold_for(&self, button: Button, hold: Duration) -> Result<()> {
+        self.virtual_remote(KeyEvent::Down, button).await?;
+
+        // Release in a task so the button is not left held if this future is dropped
+        let device = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(hold).await;
+            device.key_up(button).await
+        })
+        .await
+        .map_err(|e| Error::Other(format!("Key up task failed: {}", e)))?
+    }
+
+    /// Set the minimum time a button is held by [`key_down()`](Self::key_down) with a duration
+    ///
+    /// Some firmware drops a KEYDOWN and KEYUP which arrive too close together, so a shorter
+    /// duration is lengthened to this minimum. The default is 50 milliseconds.
+    pub async fn set_min_key_hold(&self, min_hold: Duration) {
+        log::trace!("Set minimum key hold");
+        log::debug!("set_min_key_hold min_hold: {:?}", min_hold);
+        *self.inner.min_key_hold.write().await = min_hold;
+    }
+
+    /// Log the body of every PUT request, such as pairing, key presses and settings writes
+    ///
+    /// This is for debugging the protocol. Bodies are logged pretty printed at the `debug` level
+    /// when a logger is attached, with the auth token redacted. Off by default.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::{Button, Device};
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_log_request_bodies(true).await;
+    ///
+    /// // Logs the KEYLIST sent
+    /// dev.key_press(Button::Menu).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_log_request_bodies(&self, enabled: bool) {
+        log::trace!("Set log request bodies");
+        log::debug!("set_log_request_bodies enabled: {}", enabled);
+        *self.inner.log_request_bodies.write().await = enabled;
+    }
+
+    pub(super) async fn logs_request_bodies(&self) -> bool {
+        *self.inner.log_request_bodies.read().await
+    }
+
+    /// Get statistics about the client's connections to the device
+    ///
+    /// The device may close a connection which sat idle, so a request which is reset is retried
+    /// once on a new connection. Many retries point at a network which drops idle connections.
+    pub fn pool_stats(&self) -> PoolStats {
+        self.inner.pool_stats.stats()
+    }
+
+    /// Set how many requests may be sent to the device at once
+    ///
+    /// Devices answer `Busy` or `Blocked` when they get several requests at the same time, so by
+    /// default requests to one device wait for each other. Requests to different devices are
+    /// not limited. A limit of 0 is treated as 1.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_max_concurrent_requests(2).await;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_max_concurrent_requests(&self, max: usize) {
+        log::trace!("Set max concurrent requests");
+        log::debug!("set_max_concurrent_requests max: {}", max);
+        *self.inner.request_limit.write().await = Arc::new(Semaphore::new(max.max(1)));
+    }
+
+    pub(super) async fn request_limit(&self) -> Arc<Semaphore> {
+        self.inner.request_limit.read().await.clone()
+    }
+
+    /// Emulates releasing a remote control button
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::{Device, Button};
+    /// use tokio::time::sleep;
+    /// use std::time::Duration;
+    ///
+    /// let mut dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// // Increase Volume for 5 seconds
+    /// dev.key_down(Button::VolumeUp, None).await?;
+    /// sleep(Duration::from_secs(5)).await;
+    /// dev.key_up(Button::VolumeUp).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn key_up(&self, button: Button) -> Result<()> {
+        log::trace!("Virtual Remote Key Up");
+        self.virtual_remote(KeyEvent::Up, button).await.map(drop)
+    }
+
+    /// Send several remote control events in a single request
+    ///
+    /// Some firmware treats events sent together as a simultaneous combination, such as the
+    /// shortcuts for service menus. The events are sent in order. Directional buttons use the
+    /// remote code set, see [`set_remote_codeset()`](Self::set_remote_codeset).
+    ///
+    /// Returns an error if `events` is empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::{Button, Device, KeyEvent};
+    ///
+    /// let mut dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// // Hold menu while pressing volume up
+    /// dev.key_combo(&[
+    ///     (KeyEvent::Down, Button::Menu),
+    ///     (KeyEvent::Press, Button::VolumeUp),
+    ///     (KeyEvent::Up, Button::Menu),
+    /// ])
+    /// .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn key_combo(&self, events: &[(KeyEvent, Button)]) -> Result<()> {
+        log::trace!("Virtual Remote Key Combo");
+        log::debug!("key_combo events: {:?}", events);
+
+        if events.is_empty() {
+            return Err(ClientError::EmptyKeyCombo.into());
+        }
+
+        self.send_keys(events).await.map(drop)
+    }
+
+    /// Pin the remote code set so every press uses the right directional codes on the first try
+    ///
+    /// Some devices use alternate codes for the directional pad. Until a code set is pinned, the
+    /// first directional press tries both and remembers the one the device accepts, so only that
+    /// press sends an extra request. See [`CodeSet`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::{Button, CodeSet, Device};
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// dev.set_remote_codeset(CodeSet::Secondary).await;
+    /// dev.key_press(Button::Left).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_remote_codeset(&self, codeset: CodeSet) {
+        log::trace!("Set remote code set");
+        log::debug!("set_remote_codeset codeset: {:?}", codeset);
+        *self.inner.remote_codeset.write().await = Some(codeset);
+    }
+
+    /// Call `recorder` with every remote control event the device accepts
+    ///
+    /// This is for recording macros. `recorder` gets each event from
+    /// [`key_press()`](Self::key_press), [`key_down()`](Self::key_down),
+    /// [`key_up()`](Self::key_up) and [`key_combo()`](Self::key_combo) along with when it was
+    /// sent, in the order they were sent. It is called from a task of its own, so a slow recorder
+    /// never holds up commands. Setting another recorder replaces this one, and
+    /// [`clear_remote_recorder()`](Self::clear_remote_recorder) removes it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::{Button, Device};
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// dev.set_remote_recorder(|event, button, at| {
+    ///     println!("{:?} {:?} at {:?}", event, button, at);
+    /// })
+    /// .await;
+    /// dev.key_press(Button::Menu).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_remote_recorder<F>(&self, recorder: F)
+    where
+        F: Fn(KeyEvent, Button, Instant) + Send + 'static,
+    {
+        log::trace!("Set remote recorder");
+        let (tx, mut rx) = mpsc::unbounded_channel::<(KeyEvent, Button, Instant)>();
+        tokio::spawn(async move {
+            while let Some((event, button, at)) = rx.recv().await {
+                recorder(event, button, at);
+            }
+        });
+        *self.inner.remote_recorder.write().await = Some(tx);
+    }
+
+    /// Stop calling the recorder set with [`set_remote_recorder()`](Self::set_remote_recorder)
+    pub async fn clear_remote_recorder(&self) {
+        log::trace!("Clear remote recorder");
+        self.inner.remote_recorder.write().await.take();
+    }
+
+    /// Get the remote code set which was pinned or detected, if any
+    pub async fn remote_codeset(&self) -> Option<CodeSet> {
+        *self.inner.remote_codeset.read().await
+    }
+
+    /// Stop this client from ever fetching the 3rd party app catalog
+    ///
+    /// The catalog used by [`current_app()`](Self::current_app) is downloaded from a CDN the first
+    /// time it is needed. After calling this no request is made to the CDN, or to the device for
+    /// the current app; [`current_app()`](Self::current_app) returns
+    /// [`ClientError::AppCatalogDisabled`] instead. This applies to every clone of the client.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.disable_app_catalog().await;
+    ///
+    /// assert!(dev.current_app().await.is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn disable_app_catalog(&self) {
+        log::trace!("Disable app catalog");
+        self.inner.app_list.write().await.disable();
+    }
+
+    /// Set how long to wait for the app catalog used by [`current_app()`](Self::current_app)
+    ///
+    /// The catalog is downloaded from a CDN, which can be much slower than the device itself, so
+    /// it has its own timeout of 15 seconds by default rather than the device's. This applies to
+    /// every clone of the client.
+    ///
+    /// To abort a download sooner, drop the future of [`current_app()`](Self::current_app) or
+    /// [`list_apps()`](Self::list_apps), e.g. with `tokio::time::timeout` or `tokio::select!`.
+    /// Nothing from an aborted download is kept, so the next call downloads the catalog again.
+    pub async fn set_app_catalog_timeout(&self, timeout: Duration) {
+        log::trace!("Set app catalog timeout");
+        log::debug!("set_app_catalog_timeout timeout: {:?}", timeout);
+        self.inner.app_list.write().await.set_timeout(timeout);
+    }
+
+    /// Get the DIAL `Application-URL` the device gave in its description, if any
+    pub async fn application_url(&self) -> Option<String> {
+        self.inner.application_url.read().await.clone()
+    }
+
+    pub(super) async fn set_application_url(&self, url: Option<String>) {
+        *self.inner.application_url.write().await = url;
+    }
+
+    /// Launch an app with the DIAL protocol
+    ///
+    /// Some apps which are not in the app catalog used by [`current_app()`](Self::current_app)
+    /// can be launched by their DIAL name, e.g. "YouTube". This sends a POST to
+    /// `{application_url}/{app_name}` as described in the DIAL specification. Returns
+    /// [`ClientError::NoApplicationUrl`] if the device did not give an
+    /// [`application_url()`](Self::application_url), or [`ClientError::DialLaunchFailed`] if the
+    /// device refuses, e.g. because it does not know the app.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.launch_dial_app("YouTube").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn launch_dial_app(&self, app_name: &str) -> Result<()> {
+        log::trace!("Launch DIAL App");
+        log::debug!("launch_dial_app app_name: {}", app_name);
+        let app_url = self
+            .application_url()
+            .await
+            .ok_or(ClientError::NoApplicationUrl)?;
+        let url = format!("{}/{}", app_url.trim_end_matches('/'), app_name);
+
+        let res = self
+            .inner
+            .client
+            .post(url)
+            .header("Content-Type", "text/plain; charset=utf-8")
+            .header("Content-Length", "0")
+            .send()
+            .await?;
+
+        if res.status().is_success() {
+            Ok(())
+        } else {
+            Err(ClientError::DialLaunchFailed(app_name.into(), res.status().as_u16()).into())
+        }
+    }
+
+    /// Get information about the app currently running on the device
+    ///
+    /// App info is sourced from a 3rd party. This method will return
+    /// `None` if the app data isn't available from that source, or
+    /// [`ClientError::AppCatalogDisabled`] after calling
+    /// [`disable_app_catalog()`](Self::disable_app_catalog).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let mut dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// if let Some(app) = dev.current_app().await? {
+    ///     println!("{:#?}", app);
+    ///     // > App {
+    ///     // >     name: "Netflix",
+    ///     // >     description: "Award-winning series, movies and more",
+    ///     // >     image_url: "http://{icon_url}",
+    ///     // > },
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn current_app(&self) -> Result<Option<App>> {
+        if self.inner.app_list.read().await.is_disabled() {
+            return Err(ClientError::AppCatalogDisabled.into());
+        }
+
+        // Get payload from device
+        let current_payload = self.current_app_payload().await?;
+
+        // Get app by payload
+        self.inner
+            .app_list
+            .write()
+            .await
+            .get_app(current_payload)
+            .await
+    }
+
+    /// Get what the device reports for the app it is running, without looking it up in the app
+    /// catalog
+    ///
+    /// When [`current_app()`](Self::current_app) returns `None` the app is missing from the
+    /// catalog. The payload can be reported so the app can be added, see [`AppPayload`]. This
+    /// does not use the catalog, so it works after
+    /// [`disable_app_catalog()`](Self::disable_app_catalog).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// if dev.current_app().await?.is_none() {
+    ///     println!("Unknown app: {}", dev.current_app_payload().await?);
+    ///     // > Unknown app: {"NAME_SPACE":4,"APP_ID":"999","MESSAGE":null}
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn current_app_payload(&self) -> Result<AppPayload> {
+        log::trace!("Get Current App Payload");
+        self.send_command(CommandDetail::GetCurrentApp)
+            .await?
+            .app_payload()
+    }
+
+    /// Get every app in the app catalog which can be launched with
+    /// [`launch_app()`](Self::launch_app), sorted by name
+    ///
+    /// The catalog is the same one used by [`current_app()`](Self::current_app), and is downloaded
+    /// the first time it is needed. Returns [`ClientError::AppCatalogDisabled`] if it was disabled.
+    pub async fn list_apps(&self) -> Result<Vec<App>> {
+        log::trace!("List Apps");
+        self.inner.app_list.write().await.apps().await
+    }
+
+    /// Launch an app from the app catalog, see [`list_apps()`](Self::list_apps)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// let apps = dev.list_apps().await?;
+    /// dev.launch_app(&apps[0]).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn launch_app(&self, app: &App) -> Result<()> {
+        log::trace!("Launch App");
+        log::debug!("launch_app app: {:?}", app);
+        let payload = app
+            .payload()
+            .ok_or_else(|| ClientError::AppNotFound(app.name(), Vec::new()))?;
+
+        self.send_command(CommandDetail::LaunchApp(serde_json::to_value(payload)?))
+            .await?;
+        Ok(())
+    }
+
+    /// Launch the app from the app catalog whose name best matches `name`, and return it
+    ///
+    /// Matching ignores case and accepts part of a name, so "netf" launches Netflix. An exact
+    /// name is preferred, then a name which starts with `name`, then one which contains it.
+    /// Returns [`ClientError::AmbiguousAppName`] with the names of the candidates if several apps
+    /// match equally well, or [`ClientError::AppNotFound`] with similar names if none do. Similar
+    /// names include misspellings, so "netflx" suggests Netflix. An empty `name` returns
+    /// [`ClientError::EmptyAppName`] without downloading the catalog.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// let app = dev.launch_app_by_name("netflix").await?;
+    /// println!("{}", app.name());
+    /// // > Netflix
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn launch_app_by_name(&self, name: &str) -> Result<App> {
+        log::trace!("Launch App By Name");
+        log::debug!("launch_app_by_name name: {}", name);
+
+        if name.trim().is_empty() {
+            return Err(ClientError::EmptyAppName.into());
+        }
+
+        let app = apps::find_by_name(self.list_apps().await?, name)?;
+        self.launch_app(&app).await?;
+        Ok(app)
+    }
+
+    /// Get the current device input
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let mut dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// println!("{}", dev.current_input().await?.friendly_name());
+    /// // > "Nintendo Switch"
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn current_input(&self) -> Result<Input> {
+        log::trace!("Get Current Input");
+        self.send_command(CommandDetail::GetCurrentInput)
+            .await
+            .map(|response| response.into())?
+    }
+
+    /// Get the device's inputs, each with whether it is the current input
+    ///
+    /// The input list and current input are requested together, so they overlap if the device
+    /// allows more than one request at a time, see
+    /// [`set_max_concurrent_requests()`](Self::set_max_concurrent_requests). With the default of
+    /// one request at a time, this takes as long as calling [`list_inputs()`](Self::list_inputs)
+    /// then [`current_input()`](Self::current_input).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// for (input, is_current) in dev.inputs_with_current().await? {
+    ///     println!("{} {}", if is_current { "*" } else { " " }, input.name());
+    /// }
+    /// // >   HDMI-1
+    /// // > * HDMI-2
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn inputs_with_current(&self) -> Result<Vec<(Input, bool)>> {
+        log::trace!("Inputs With Current");
+        let (inputs, current) = tokio::join!(self.list_inputs(), self.current_input());
+        let current = current?.friendly_name();
+
+        Ok(inputs?
+            .into_iter()
+            .map(|input| {
+                let is_current = input.name() == current;
+                (input, is_current)
+            })
+            .collect())
+    }
+
+    /// Get list of available inputs
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::{Device, Input};
+    ///
+    /// let mut dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// let inputs: Vec<Input> = dev.list_inputs().await?;
+    ///
+    /// println!("{}", inputs[0].friendly_name());
+    /// // > "Nintendo Switch"
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list_inputs(&self) -> Result<Vec<Input>> {
+        log::trace!("List Inputs");
+        self.send_command(CommandDetail::GetInputList)
+            .await
+            .map(|response| response.into())?
+    }
+
+    /// Get the HDMI inputs of the device, in order of their port number
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// println!("{} HDMI ports", dev.hdmi_inputs().await?.len());
+    /// // > 4 HDMI ports
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn hdmi_inputs(&self) -> Result<Vec<Input>> {
+        log::trace!("HDMI Inputs");
+        let mut inputs: Vec<Input> = self
+            .list_inputs()
+            .await?
+            .into_iter()
+            .filter(Input::is_hdmi)
+            .collect();
+        inputs.sort_by_key(|input| info::hdmi_port(&input.name()));
+        Ok(inputs)
+    }
+
+    /// Get the number of inputs, see [`list_inputs()`](Self::list_inputs)
+    pub async fn input_count(&self) -> Result<usize> {
+        log::trace!("Input Count");
+        Ok(self.list_inputs().await?.len())
+    }
+
+    /// Changes the input of the device
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let mut dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// println!("{}", dev.current_input().await?.friendly_name());
+    /// // > "Nintendo Switch"
+    ///
+    /// dev.change_input("HDMI-2").await?;
+    /// println!("{}", dev.current_input().await?.friendly_name());
+    /// // > "Playstation 4"
+    /// # Ok(())
+    /// # }
+    /// ```
+    /// Note: the input's default name must be passed in, not the input's custom name -- e.g.
+    /// "HDMI-2" instead of "Playstation 4"
+    pub async fn change_input<S: Into<String>>(&self, name: S) -> Result<()> {
+        let name: String = name.into();
+        log::trace!("Change Input");
+        log::debug!("change_input name: {}", name);
+
+        self.send_command(CommandDetail::ChangeInput {
+            name,
+            hashval: self.current_input().await?.hashval(),
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Get the root of the device's [`Settings`](SubSetting).
+    pub async fn settings(&self) -> Result<Vec<SubSetting>> {
+        log::trace!("Settings Root");
+        if let Some(settings) = self.inner.prefetched_settings.write().await.take() {
+            log::trace!("Using prefetched settings");
+            return Ok(settings
+                .into_iter()
+                .map(|setting| setting.with_device(self.clone()))
+                .collect());
+        }
+        settings::root(self.clone()).await
+    }
+
+    /// Get every [`SubSetting`] in the device's settings tree, including menus.
+    ///
+    /// Settings are returned depth first, so a menu is followed by its contents.
+    pub async fn all_settings(&self) -> Result<Vec<SubSetting>> {
+        log::trace!("All Settings");
+        settings::all(self.clone()).await
+    }
+
+    /// Search the device's settings tree for [`SubSetting`]s whose name contains `query`
+    ///
+    /// Matching ignores case. Each setting is returned with its path of names from the settings
+    /// root, e.g. `"Picture > Backlight"`, in the order the tree is walked depth first.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// for (path, setting) in dev.search_settings("backlight").await? {
+    ///     println!("{}: {:?}", path, setting.value::<i32>());
+    ///     // > Picture > Backlight: Some(50)
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn search_settings<S: AsRef<str>>(
+        &self,
+        query: S,
+    ) -> Result<Vec<(String, SubSetting)>> {
+        log::trace!("Search Settings");
+        log::debug!("search_settings query: {}", query.as_ref());
+        settings::search(self.clone(), query.as_ref()).await
+    }
+
+    /// Find a [`SubSetting`] anywhere in the device's settings tree by its `CNAME`
+    ///
+    /// `CNAME`s are the segments of a setting's endpoint, e.g. `backlight` in
+    /// `/menu_native/dynamic/tv_settings/picture/backlight`. They stay the same when the device's
+    /// language changes, unlike [`name()`](SubSetting::name). If more than one setting has the
+    /// `CNAME`, the first one found is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// let backlight = dev.find_setting_by_cname("backlight").await?;
+    /// println!("{}: {:?}", backlight.name(), backlight.value::<i32>());
+    /// // > Backlight: Some(50)
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn find_setting_by_cname<S: AsRef<str>>(&self, cname: S) -> Result<SubSetting> {
+        log::trace!("Find Setting By CNAME");
+        log::debug!("cname: {}", cname.as_ref());
+        settings::by_cname(self.clone(), cname.as_ref()).await
+    }
+
+    /// Write every [`SubSetting`] in `settings` which has a [`staged`](SubSetting::stage) value
+    ///
+    /// This lets many settings be edited locally and then saved at once. The settings in a menu
+    /// are re-read between writes since each write changes their hashvals. Returns a result for
+    /// each setting in the same order as `settings`, where a setting with nothing staged is `Ok`.
+    /// Settings which were written are no longer [dirty](SubSetting::is_dirty), while those which
+    /// failed keep their staged value so they can be flushed again.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// let mut settings = vec![
+    ///     dev.find_setting_by_cname("backlight").await?,
+    ///     dev.find_setting_by_cname("brightness").await?,
+    /// ];
+    /// settings[0].stage(20);
+    /// settings[1].stage(45);
+    ///
+    /// let results = dev.flush_changes(&mut settings).await;
+    /// for (setting, res) in settings.iter().zip(results) {
+    ///     if let Err(e) = res {
+    ///         println!("{} was not saved: {}", setting.name(), e);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn flush_changes(&self, settings: &mut [SubSetting]) -> Vec<Result<()>> {
+        log::trace!("Flush Changes");
+        settings::flush(settings).await
+    }
+
+    /// Watch the device's settings for changes, such as those made with the physical remote
+    ///
+    /// The settings tree is polled every `interval` with one `HASHONLY` request per menu, which
+    /// has the hashvals of the menu's settings but not their values. Only menus whose hashvals
+    /// changed are read again to find what changed. Changed settings are returned by the
+    /// [`SettingsWatch`] one at a time. Changes made before this method is called are not
+    /// reported.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    /// use std::time::Duration;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// let mut watch = dev.watch_settings(Duration::from_secs(5)).await?;
+    /// loop {
+    ///     let setting = watch.next_change().await?;
+    ///     println!("{} changed to {:?}", setting.name(), setting.value::<serde_json::Value>());
+    /// }
+    /// // > Backlight changed to Some(Number(20))
+    /// # }
+    /// ```
+    pub async fn watch_settings(&self, interval: Duration) -> Result<SettingsWatch> {
+        log::trace!("Watch Settings");
+        SettingsWatch::start(self.clone(), interval).await
+    }
+
+    /// Get every writable setting in the device's settings tree along with its bounds. See [`EditableSetting`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::{Bounds, Device};
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// for editable in dev.editable_settings().await? {
+    ///     match editable.bounds {
+    ///         Bounds::Slider(info) => println!("{}: {} to {}", editable.setting.name(), info.min, info.max),
+    ///         Bounds::Elements(elements) => println!("{}: {:?}", editable.setting.name(), elements),
+    ///         Bounds::Unbounded => println!("{}", editable.setting.name()),
+    ///     }
+    /// }
+    /// // > Tint: -50 to 50
+    /// // > Picture Mode: ["Vivid", "Bright", "Calibrated", "Calibrated Dark*", "Game", "Sports"]
+    /// // > ...
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn key_press(&self, button: Button) -> Result<()> {
-        log::trace!("Virtual Remote Key Press");
-        self.virtual_remote(KeyEvent::Press, button).await.map(drop)
+    pub async fn editable_settings(&self) -> Result<Vec<EditableSetting>> {
+        log::trace!("Editable Settings");
+        Ok(self
+            .all_settings_tagged()
+            .await?
+            .into_iter()
+            .filter(|tagged| tagged.editable)
+            .map(|tagged| EditableSetting {
+                setting: tagged.setting,
+                bounds: tagged.bounds,
+            })
+            .collect())
     }
 
-    /// Emulates holding down a remote control button
+    /// Get every setting in the device's settings tree, tagged with whether it can be changed. See
+    /// [`TaggedSetting`].
     ///
-    /// If a duration is specified, the remote button will be held down for the duration.
-    /// Otherwise it will be held down indefinitely and [`key_up()`](Self::key_up) must be called.
+    /// This is for showing a full settings page where read-only settings, such as system
+    /// information, are displayed but cannot be edited. Editable settings come with their bounds,
+    /// the same as from [`editable_settings()`](Self::editable_settings). Menus are left out.
     ///
     /// # Example
     ///
     /// ```
     /// # async fn example() -> Result<(), smartcast::Error> {
-
-    /// use smartcast::{Device, Button};
-    /// use std::time::Duration;
+    /// use smartcast::Device;
     ///
-    /// let mut dev = Device::from_ip("192.168.0.14").await?;
+    /// let dev = Device::from_ip("192.168.0.14").await?;
     /// dev.set_auth_token("Z2zscc1udl");
     ///
-    /// // Increase Volume for 5 seconds
-    /// dev.key_down(Button::VolumeUp, Some(Duration::from_secs(5))).await?;
-
+    /// for tagged in dev.all_settings_tagged().await? {
+    ///     let lock = if tagged.editable { "" } else { " (read-only)" };
+    ///     println!("{}{}", tagged.setting.name(), lock);
+    /// }
+    /// // > Picture Mode
+    /// // > ...
+    /// // > ESN (read-only)
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn key_down(&self, button: Button, duration: Option<Duration>) -> Result<()> {
-        log::trace!("Virtual Remote Key Down");
-        log::debug!("key_down duration: {:?}", duration);
-
-        self.virtual_remote(KeyEvent::Down, button).await?;
-        if let Some(duration) = duration {
-            // Sleep for duration
-            tokio::time::sleep(duration).await;
-            self.key_up(button).await?;
+    pub async fn all_settings_tagged(&self) -> Result<Vec<TaggedSetting>> {
+        log::trace!("All Settings Tagged");
+        let mut tagged = Vec::new();
+        for setting in self.all_settings().await? {
+            if setting.setting_type() != SettingType::Menu {
+                tagged.push(TaggedSetting::new(setting).await?);
+            }
         }
-        Ok(())
+        Ok(tagged)
     }
 
-    /// Emulates releasing a remote control button
+    /// Get the whole settings tree as pretty printed json, for pasting into a bug report
+    ///
+    /// Every setting from [`all_settings_tagged()`](Self::all_settings_tagged) is listed with its
+    /// name, `CNAME`, endpoint, type, value, and bounds. Values of settings which may be secret,
+    /// such as passwords, are replaced with `"<redacted>"`.
     ///
     /// # Example
     ///
     /// ```
     /// # async fn example() -> Result<(), smartcast::Error> {
-
-    /// use smartcast::{Device, Button};
-    /// use tokio::time::sleep;
-    /// use std::time::Duration;
+    /// use smartcast::Device;
     ///
-    /// let mut dev = Device::from_ip("192.168.0.14").await?;
+    /// let dev = Device::from_ip("192.168.0.14").await?;
     /// dev.set_auth_token("Z2zscc1udl");
     ///
-    /// // Increase Volume for 5 seconds
-    /// dev.key_down(Button::VolumeUp, None).await?;
-    /// sleep(Duration::from_secs(5)).await;
-    /// dev.key_up(Button::VolumeUp).await?;
-
+    /// println!("{}", dev.dump_settings_json().await?);
+    /// // > [
+    /// // >   {
+    /// // >     "bounds": {
+    /// // >       "center": 50,
+    /// // >       ...
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn key_up(&self, button: Button) -> Result<()> {
-        log::trace!("Virtual Remote Key Up");
-        self.virtual_remote(KeyEvent::Up, button).await.map(drop)
+    pub async fn dump_settings_json(&self) -> Result<String> {
+        log::trace!("Dump Settings Json");
+        let settings: Vec<Value> = self
+            .all_settings_tagged()
+            .await?
+            .iter()
+            .map(TaggedSetting::dump)
+            .collect();
+        Ok(serde_json::to_string_pretty(&settings)?)
     }
 
-    /// Get information about the app currently running on the device
+    /// Send a request to an arbitrary endpoint on the device and get the full response
     ///
-    /// App info is sourced from a 3rd party. This method will return
-    /// `None` if the app data isn't available from that source.
+    /// This is useful for endpoints which are not otherwise supported, such as those specific to
+    /// certain firmware. The `body` of a [`Put`](RequestType::Put) request is sent as-is, so it
+    /// should include the `_url` and any other fields the endpoint expects. A `body` passed with a
+    /// [`Get`](RequestType::Get) request is ignored.
+    ///
+    /// The request is sent with the device's auth token unless `auth_token` is given, in which case
+    /// that token is used for this request only. The device's token is left unchanged, so this can
+    /// be used to check a token before setting it.
     ///
     /// # Example
     ///
     /// ```
     /// # async fn example() -> Result<(), smartcast::Error> {
-
-    /// use smartcast::Device;
+    /// use smartcast::{Device, RequestType};
     ///
-    /// let mut dev = Device::from_ip("192.168.0.14").await?;
+    /// let dev = Device::from_ip("192.168.0.14").await?;
     /// dev.set_auth_token("Z2zscc1udl");
     ///
-    /// if let Some(app) = dev.current_app().await? {
-    ///     println!("{:#?}", app);
-    ///     // > App {
-    ///     // >     name: "Netflix",
-    ///     // >     description: "Award-winning series, movies and more",
-    ///     // >     image_url: "http://{icon_url}",
-    ///     // > },
-    /// }
-
+    /// let res = dev
+    ///     .custom_request(RequestType::Get, "/state/device/power_mode", None, None)
+    ///     .await?;
+    /// println!("{}", res["ITEMS"][0]["VALUE"]);
+    /// // > 1
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn current_app(&self) -> Result<Option<App>> {
-        // Get payload from device
-        let current_payload: Payload = self
-            .send_command(CommandDetail::GetCurrentApp)
-            .await?
-            .app_payload()?;
-
-        // Get app by payload
-        self.inner
-            .app_list
-            .write()
+    pub async fn custom_request<S: Into<String>>(
+        &self,
+        method: RequestType,
+        endpoint: S,
+        body: Option<Value>,
+        auth_token: Option<&str>,
+    ) -> Result<Value> {
+        let endpoint: String = endpoint.into();
+        log::trace!("Custom Request");
+        log::debug!("method: {:?}, endpoint: {}", method, endpoint);
+
+        Command::new(self.clone(), CommandDetail::Custom(method, endpoint, body))
             .await
-            .get_app(current_payload)
+            .with_auth_token(auth_token.map(Into::into))
+            .send()
             .await
+            .map(Value::from)
     }
 
-    /// Get the current device input
+    /// Send a request to an arbitrary endpoint on the device and deserialize the payload into your own type
+    ///
+    /// Same as [`custom_request()`](Self::custom_request), but the payload is extracted from the
+    /// response first. If the response has an `ITEM` object, that is deserialized into `T`.
+    /// Otherwise the `ITEMS` array is used, so `T` will usually be a `Vec`.
     ///
     /// # Example
     ///
     /// ```
     /// # async fn example() -> Result<(), smartcast::Error> {
-
-    /// use smartcast::Device;
+    /// use smartcast::{Device, RequestType};
     ///
-    /// let mut dev = Device::from_ip("192.168.0.14").await?;
+    /// #[derive(serde::Deserialize)]
+    /// struct PowerMode {
+    ///     #[serde(rename = "VALUE")]
+    ///     value: u8,
+    /// }
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
     /// dev.set_auth_token("Z2zscc1udl");
     ///
-    /// println!("{}", dev.current_input().await?.friendly_name());
-    /// // > "Nintendo Switch"
-
+    /// let modes: Vec<PowerMode> = dev
+    ///     .custom_request_as(RequestType::Get, "/state/device/power_mode", None, None)
+    ///     .await?;
+    /// println!("{}", modes[0].value);
+    /// // > 1
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn current_input(&self) -> Result<Input> {
-        log::trace!("Get Current Input");
-        self.send_command(CommandDetail::GetCurrentInput)
+    pub async fn custom_request_as<T, S>(
+        &self,
+        method: RequestType,
+        endpoint: S,
+        body: Option<Value>,
+        auth_token: Option<&str>,
+    ) -> Result<T>
+    where
+        T: DeserializeOwned,
+        S: Into<String>,
+    {
+        let endpoint: String = endpoint.into();
+        log::trace!("Custom Request As");
+        log::debug!("method: {:?}, endpoint: {}", method, endpoint);
+
+        Command::new(self.clone(), CommandDetail::Custom(method, endpoint, body))
             .await
-            .map(|response| response.into())?
+            .with_auth_token(auth_token.map(Into::into))
+            .send()
+            .await?
+            .payload()
     }
 
-    /// Get list of available inputs
+    /// Read the raw response for a setting from the static settings base
+    ///
+    /// `path` is relative to the settings root, e.g. `"picture/sharpness"`. The static base
+    /// describes a setting: the bounds and markers of sliders, and the choices of lists on some
+    /// firmware. Its values are not kept up to date. This is for comparing what a device reports
+    /// at each base; most users should use [`settings()`](Self::settings) instead.
     ///
     /// # Example
     ///
     /// ```
     /// # async fn example() -> Result<(), smartcast::Error> {
-
-    /// use smartcast::{Device, Input};
+    /// use smartcast::Device;
     ///
-    /// let mut dev = Device::from_ip("192.168.0.14").await?;
+    /// let dev = Device::from_ip("192.168.0.14").await?;
     /// dev.set_auth_token("Z2zscc1udl");
     ///
-    /// let inputs: Vec<Input> = dev.list_inputs().await?;
-    ///
-    /// println!("{}", inputs[0].friendly_name());
-    /// // > "Nintendo Switch"
-
+    /// let res = dev.read_setting_static("picture/brightness").await?;
+    /// println!("{}", res["ITEMS"][0]["MAXIMUM"]);
+    /// // > 100
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn list_inputs(&self) -> Result<Vec<Input>> {
-        log::trace!("List Inputs");
-        self.send_command(CommandDetail::GetInputList)
+    pub async fn read_setting_static<S: AsRef<str>>(&self, path: S) -> Result<Value> {
+        log::trace!("Read Setting Static");
+        self.read_setting(EndpointBase::Static, path.as_ref()).await
+    }
+
+    /// Read the raw response for a setting from the dynamic settings base
+    ///
+    /// `path` is relative to the settings root, e.g. `"picture/sharpness"`. The dynamic base has
+    /// the current value and hashval of a setting, and the contents of menus. Some firmware puts
+    /// slider bounds here instead of, or as well as, at the
+    /// [static base](Self::read_setting_static).
+    pub async fn read_setting_dynamic<S: AsRef<str>>(&self, path: S) -> Result<Value> {
+        log::trace!("Read Setting Dynamic");
+        self.read_setting(EndpointBase::Dynamic, path.as_ref())
             .await
-            .map(|response| response.into())?
     }
 
-    /// Changes the input of the device
+    /// Set the settings base to read slider info and list elements from first
+    ///
+    /// Firmware differs in which [`EndpointBase`] has slider bounds and list elements, and on
+    /// some firmware one of them never does. Both are tried, so trying the wrong one first costs
+    /// an extra request for every setting read. By default the base which has them is learned
+    /// separately for slider info and list elements, from the first setting read, and tried first
+    /// from then on. It is learned again if a setting is only found at the other base. A base
+    /// which is set is used for both and never replaced. `None` forgets the set and learned bases
+    /// so they are learned again.
     ///
     /// # Example
     ///
     /// ```
     /// # async fn example() -> Result<(), smartcast::Error> {
-
-    /// use smartcast::Device;
+    /// use smartcast::{Device, EndpointBase};
     ///
-    /// let mut dev = Device::from_ip("192.168.0.14").await?;
+    /// let dev = Device::from_ip("192.168.0.14").await?;
     /// dev.set_auth_token("Z2zscc1udl");
     ///
-    /// println!("{}", dev.current_input().await?.friendly_name());
-    /// // > "Nintendo Switch"
-    ///
-    /// dev.change_input("HDMI-2").await?;
-    /// println!("{}", dev.current_input().await?.friendly_name());
-    /// // > "Playstation 4"
-
+    /// // This firmware only has slider bounds at the dynamic base
+    /// dev.set_settings_base(Some(EndpointBase::Dynamic)).await;
+    /// let editable = dev.editable_settings().await?;
     /// # Ok(())
     /// # }
     /// ```
-    /// Note: the input's default name must be passed in, not the input's custom name -- e.g.
-    /// "HDMI-2" instead of "Playstation 4"
-    pub async fn change_input<S: Into<String>>(&self, name: S) -> Result<()> {
-        let name: String = name.into();
-        log::trace!("Change Input");
-        log::debug!("change_input name: {}", name);
+    pub async fn set_settings_base(&self, base: Option<EndpointBase>) {
+        log::trace!("Set settings base");
+        log::debug!("set_settings_base base: {:?}", base);
+        *self.inner.settings_base.write().await = SettingsBases {
+            set: base,
+            ..SettingsBases::default()
+        };
+    }
 
-        self.send_command(CommandDetail::ChangeInput {
-            name,
-            hashval: self.current_input().await?.hashval(),
-        })
-        .await?;
-        Ok(())
+    /// Get the settings base which is read from first for the slider info of a
+    /// [`Slider`](SettingType::Slider), or the elements of a [`List`](SettingType::List) or
+    /// [`XList`](SettingType::XList), whether it was set or learned. See
+    /// [`set_settings_base()`](Self::set_settings_base).
+    ///
+    /// Returns `None` if no setting of the type has been read which showed where the device
+    /// keeps them, or for other types of setting.
+    pub async fn settings_base(&self, setting_type: &SettingType) -> Option<EndpointBase> {
+        self.inner.settings_base.read().await.get(setting_type)
     }
 
-    /// Get the root of the device's [`Settings`](SubSetting).
-    pub async fn settings(&self) -> Result<Vec<SubSetting>> {
-        log::trace!("Settings Root");
-        settings::root(self.clone()).await
+    /// Remember `base` as the one to read `setting_type` from first, unless one was set
+    pub(super) async fn learn_settings_base(&self, setting_type: &SettingType, base: EndpointBase) {
+        self.inner
+            .settings_base
+            .write()
+            .await
+            .learn(setting_type, base);
     }
 
-    pub(super) fn settings_root(&self) -> String {
-        if let Ok(settings_root) = self.inner.settings_root.try_read() {
-            settings_root.clone()
-        } else {
-            // Same as port(), settings_root shouldn't ever be written outside initialization
-            // so use try_read() to avoid awaiting and panic if it is locked
-            panic!("Unable to settings root for read");
-        }
+    /// Whether the device only takes writes with the hash of a setting's menu, or `None` if no
+    /// write has shown it yet
+    pub(super) async fn menu_hash_writes(&self) -> Option<bool> {
+        *self.inner.menu_hash_writes.read().await
     }
 
-    async fn virtual_remote(&self, event: KeyEvent, button: Button) -> Result<()> {
+    pub(super) async fn learn_menu_hash_writes(&self, required: bool) {
+        *self.inner.menu_hash_writes.write().await = Some(required);
+    }
+
+    /// Get the raw `STATUS` of the last response from the device
+    ///
+    /// This is for diagnosing firmware which responds in unexpected ways, and is only available
+    /// with the `last-status` feature. Returns `None` if nothing has been sent yet or the last
+    /// response had no status.
+    #[cfg(feature = "last-status")]
+    pub async fn last_status(&self) -> Option<Status> {
+        self.inner.last_status.read().await.clone()
+    }
+
+    #[cfg(feature = "last-status")]
+    pub(super) async fn record_status(&self, response: &str) {
+        *self.inner.last_status.write().await = response::status(response);
+    }
+
+    async fn read_setting(&self, base: EndpointBase, path: &str) -> Result<Value> {
+        log::debug!("read_setting base: {:?}, path: {}", base, path);
+        let endpoint = format!("/{}/{}", self.settings_root().await, path.trim_matches('/'));
+        self.send_command(CommandDetail::ReadSettings(base, endpoint))
+            .await
+            .map(Value::from)
+    }
+
+    pub(super) async fn discard_prefetched_settings(&self) {
+        self.inner.prefetched_settings.write().await.take();
+    }
+
+    pub(super) async fn settings_root(&self) -> String {
+        self.inner.settings_root.read().await.clone()
+    }
+
+    async fn virtual_remote(&self, event: KeyEvent, button: Button) -> Result<KeyResult> {
         log::trace!("Virtual Remote Handler");
         log::debug!("Event: {:?}, Button: {:?}", event, button);
+        self.send_keys(&[(event, button)]).await
+    }
+
+    /// Send key events, keeping track of the buttons left held and passing them to the recorder
+    async fn send_keys(&self, events: &[(KeyEvent, Button)]) -> Result<KeyResult> {
+        let sent_at = Instant::now();
+        let result = self.send_keys_with_codesets(events).await?;
 
-        match (
-            self.send_command(CommandDetail::RemoteButtonPress(event, button))
-                .await,
-            button.alt(),
-        ) {
-            (Ok(_), _) => Ok(()),
-            (Err(e), Some(button_alt)) if e.is_api() => self
-                .send_command(CommandDetail::RemoteButtonPress(event, button_alt))
+        if let Some(recorder) = self.inner.remote_recorder.read().await.as_ref() {
+            for &(event, button) in events {
+                // Only fails if the recorder task is gone, in which case there is nothing to do
+                let _ = recorder.send((event, button, sent_at));
+            }
+        }
+
+        let mut held = self.inner.held_keys.write().await;
+        for &(event, button) in events {
+            match event {
+                KeyEvent::Down if !held.contains(&button) => held.push(button),
+                KeyEvent::Up => held.retain(|held_button| *held_button != button),
+                _ => {}
+            }
+        }
+        Ok(result)
+    }
+
+    /// Send key events, trying each remote code set until the device accepts one
+    async fn send_keys_with_codesets(&self, events: &[(KeyEvent, Button)]) -> Result<KeyResult> {
+        // Only the directional pad differs between code sets
+        if events.iter().all(|(_, button)| button.alt().is_none()) {
+            let button_events = CodeSet::Default.button_events(events);
+            self.send_command(CommandDetail::RemoteButtonPress(button_events.clone()))
+                .await?;
+            return Ok(KeyResult::new(CodeSet::Default, false, &button_events));
+        }
+
+        // Models known to use the alternate codes get them first
+        let codesets = match *self.inner.remote_codeset.read().await {
+            Some(codeset) => vec![codeset],
+            None if self.model_quirks().alt_directional_codes => {
+                vec![CodeSet::Secondary, CodeSet::Default]
+            }
+            None => vec![CodeSet::Default, CodeSet::Secondary],
+        };
+
+        let mut rejected = None;
+        for (tried, codeset) in codesets.into_iter().enumerate() {
+            let button_events = codeset.button_events(events);
+            match self
+                .send_command(CommandDetail::RemoteButtonPress(button_events.clone()))
                 .await
-                .map(drop),
-            (Err(other), _) => Err(other),
+            {
+                Ok(_) => {
+                    *self.inner.remote_codeset.write().await = Some(codeset);
+                    if tried > 0 {
+                        log::debug!("Remote codes accepted after falling back to {:?}", codeset);
+                    }
+                    return Ok(KeyResult::new(codeset, tried > 0, &button_events));
+                }
+                Err(e) if e.is_api() => rejected = Some(e),
+                Err(other) => return Err(other),
+            }
         }
+        Err(rejected.expect("a code set is always tried"))
     }
 
-    fn send_command(&self, detail: CommandDetail) -> impl Future<Output = Result<Response>> {
+    async fn send_command(&self, detail: CommandDetail) -> Result<Response> {
         log::debug!("send_command detail: '{:?}'", detail);
-        Command::new(self.clone(), detail).send()
+        Command::new(self.clone(), detail).await.send().await
     }
 
     #[cfg(test)]
-    async fn find_port(&self) -> Result<()> {
+    async fn probe_ports(&self) -> Result<()> {
         Ok(())
     }
 
@@ -696,18 +2491,36 @@ impl Device {
     }
 
     #[cfg(test)]
+    /// Get device's manufacturer
     pub fn manufacturer(&self) -> String {
         self.inner.manufacturer.clone()
     }
 }
 
+/// Pairing client names and IDs must be non-empty and at most [`MAX_CLIENT_LEN`] characters
+fn validate_client(client_name: &str, client_id: &str) -> Result<()> {
+    if client_name.trim().is_empty() || client_name.chars().count() > MAX_CLIENT_LEN {
+        return Err(ClientError::InvalidClientName(client_name.into()).into());
+    }
+    if client_id.trim().is_empty() || client_id.chars().count() > MAX_CLIENT_LEN {
+        return Err(ClientError::InvalidClientId(client_id.into()).into());
+    }
+    Ok(())
+}
+
 impl Debug for Device {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut d = f.debug_struct("Device");
         d.field("name", &self.name());
         d.field("manufacturer", &self.inner.manufacturer.clone());
         d.field("model", &self.model_name());
-        d.field("settings_root", &self.settings_root());
+        d.field(
+            "settings_root",
+            &match self.inner.settings_root.try_read() {
+                Ok(settings_root) => settings_root.clone(),
+                Err(_) => "***Locked***".into(),
+            },
+        );
         d.field("ip_addr", &self.ip());
         d.field("port", &self.port());
         d.field("uuid", &self.uuid());
@@ -722,12 +2535,62 @@ impl Debug for Device {
     }
 }
 
+impl Drop for Device {
+    fn drop(&mut self) {
+        // Only the last clone releases held buttons. Without other clones nothing can be
+        // holding the lock.
+        let held = match Arc::get_mut(&mut self.inner) {
+            Some(inner) => std::mem::take(inner.held_keys.get_mut()),
+            None => return,
+        };
+        if held.is_empty() {
+            return;
+        }
+
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                log::debug!("Releasing held buttons on drop: {:?}", held);
+                // The task's clone is dropped with nothing held so this does not recurse
+                let device = self.clone();
+                handle.spawn(async move {
+                    for button in held {
+                        if let Err(e) = device.key_up(button).await {
+                            log::warn!("Could not release {:?} on drop: {}", button, e);
+                        }
+                    }
+                });
+            }
+            Err(_) => log::warn!("No runtime to release held buttons on drop: {:?}", held),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct DeviceRef {
     name: String,
     manufacturer: String,
     model: String,
     settings_root: RwLock<String>,
+    device_info: RwLock<Option<DeviceInfo>>,
+    prefetched_settings: RwLock<Option<Vec<SubSetting>>>,
+    requires_auth: RwLock<Option<bool>>,
+    application_url: RwLock<Option<String>>,
+    min_key_hold: RwLock<Duration>,
+    request_limit: RwLock<Arc<Semaphore>>,
+    remote_codeset: RwLock<Option<CodeSet>>,
+    held_keys: RwLock<Vec<Button>>,
+    remote_recorder: RwLock<Option<mpsc::UnboundedSender<(KeyEvent, Button, Instant)>>>,
+    log_request_bodies: RwLock<bool>,
+    settings_base: RwLock<SettingsBases>,
+    /// Whether the device ignores writes with a setting's own hash, once a write has shown it
+    menu_hash_writes: RwLock<Option<bool>>,
+    volume_range: RwLock<Option<(i32, i32, i32)>>,
+    volume_setting: RwLock<Option<SubSetting>>,
+    supported_features: RwLock<HashMap<Feature, bool>>,
+    last_power_press: RwLock<Option<(bool, Instant)>>,
+    pool_stats: PoolCounters,
+    #[cfg(feature = "last-status")]
+    last_status: RwLock<Option<Status>>,
     ip_addr: String,
     port: RwLock<u16>,
     uuid: String,
@@ -751,3 +2614,471 @@ impl PartialEq for Device {
                 == *other.inner.auth_token.try_read().unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{settings, ApiError, AppList, Button, Device, PoolStats};
+    use crate::{ClientError, Error};
+
+    use serde_json::{json, Value};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use warp::Filter;
+
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    async fn offline_device() -> Device {
+        // Nothing listens on TEST-NET-1 so any request which is sent will fail
+        Device::new("name", "Vizio", "model", "192.0.2.1", "uuid")
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn begin_pair_empty_client_id() {
+        let dev = offline_device().await;
+        assert!(matches!(
+            dev.begin_pair("name", "").await,
+            Err(Error::Client(ClientError::InvalidClientId(_)))
+        ));
+        assert!(matches!(
+            dev.begin_pair("name", "   ").await,
+            Err(Error::Client(ClientError::InvalidClientId(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn begin_pair_bad_client_name() {
+        let dev = offline_device().await;
+        assert!(matches!(
+            dev.begin_pair("", "id").await,
+            Err(Error::Client(ClientError::InvalidClientName(_)))
+        ));
+        assert!(matches!(
+            dev.begin_pair("n".repeat(65).as_str(), "id").await,
+            Err(Error::Client(ClientError::InvalidClientName(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn finish_pair_empty_pin() {
+        let mut dev = offline_device().await;
+        assert!(matches!(
+            dev.finish_pair((1, 1, "id".into()), "").await,
+            Err(Error::Client(ClientError::InvalidPin(_)))
+        ));
+        assert!(matches!(
+            dev.finish_pair((1, 1, "id".into()), " abcd \n").await,
+            Err(Error::Client(ClientError::InvalidPin(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn finish_pair_short_pin() {
+        let mut dev = offline_device().await;
+        assert!(matches!(
+            dev.finish_pair((1, 1, "id".into()), "12-3").await,
+            Err(Error::Client(ClientError::InvalidPin(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn pairing_request_body() {
+        let dev = offline_device().await;
+        let body = dev.pairing_request_body("name", "id").await.unwrap();
+        assert_eq!(body["DEVICE_NAME"], "name");
+        assert_eq!(body["DEVICE_ID"], "id");
+
+        assert!(matches!(
+            dev.pairing_request_body("name", "").await,
+            Err(Error::Client(ClientError::InvalidClientId(_)))
+        ));
+    }
+
+    #[test]
+    fn parse_pairing_response() {
+        let response = r#"{
+            "STATUS": {"RESULT": "SUCCESS", "DETAIL": "Success"},
+            "ITEM": {"PAIRING_REQ_TOKEN": 123, "CHALLENGE_TYPE": 1}
+        }"#;
+        assert_eq!(
+            Device::parse_pairing_response(response, "id").unwrap(),
+            (123, 1, "id".into())
+        );
+
+        let response = r#"{"STATUS": {"RESULT": "BLOCKED", "DETAIL": "Blocked"}}"#;
+        assert!(matches!(
+            Device::parse_pairing_response(response, "id"),
+            Err(Error::Api(ApiError::Blocked(_)))
+        ));
+    }
+
+    /// Forward a connection to `server`. If `reset` is set when the client next sends
+    /// something, the connection is reset instead, like a connection the device closed while it
+    /// was idle.
+    async fn proxy(mut client: TcpStream, mut server: TcpStream, reset: Arc<AtomicBool>) {
+        let mut client_buf = [0; 4096];
+        let mut server_buf = [0; 4096];
+        loop {
+            tokio::select! {
+                read = client.read(&mut client_buf) => match read {
+                    Ok(0) | Err(_) => return,
+                    Ok(_) if reset.swap(false, Ordering::SeqCst) => {
+                        // Closing with a zero linger sends a reset
+                        #[allow(deprecated)]
+                        client.set_linger(Some(Duration::ZERO)).unwrap();
+                        return;
+                    }
+                    Ok(len) => {
+                        if server.write_all(&client_buf[..len]).await.is_err() {
+                            return;
+                        }
+                    }
+                },
+                read = server.read(&mut server_buf) => match read {
+                    Ok(0) | Err(_) => return,
+                    Ok(len) => {
+                        if client.write_all(&server_buf[..len]).await.is_err() {
+                            return;
+                        }
+                    }
+                },
+            }
+        }
+    }
+
+    /// Device whose API is reached through [`proxy()`], with the flag which resets its next
+    /// connection and the number of `PUT` requests the API got
+    async fn proxied_device() -> (Device, Arc<AtomicBool>, Arc<AtomicUsize>) {
+        // Device API
+        let puts = Arc::new(AtomicUsize::new(0));
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+        let api = warp::put()
+            .map({
+                let puts = puts.clone();
+                move || {
+                    puts.fetch_add(1, Ordering::SeqCst);
+                }
+            })
+            .untuple_one()
+            .or(warp::any())
+            .unify()
+            .map(|| {
+                r#"{"STATUS": {"RESULT": "SUCCESS", "DETAIL": "Success"}, "ITEMS": [{"VALUE": 1}]}"#
+            });
+        let (api_addr, server) = warp::serve(api)
+            .tls()
+            .cert(cert.serialize_pem().unwrap())
+            .key(cert.serialize_private_key_pem())
+            .bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        // Proxy which can reset connections
+        let reset = Arc::new(AtomicBool::new(false));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy_port = listener.local_addr().unwrap().port();
+        tokio::spawn({
+            let reset = reset.clone();
+            async move {
+                while let Ok((client, _)) = listener.accept().await {
+                    let server = TcpStream::connect(api_addr).await.unwrap();
+                    tokio::spawn(proxy(client, server, reset.clone()));
+                }
+            }
+        });
+
+        let dev = Device::new("name", "Vizio", "model", "127.0.0.1", "uuid")
+            .await
+            .unwrap();
+        *dev.inner.port.write().await = proxy_port;
+        *dev.inner.settings_root.write().await = "tv_settings".into();
+        (dev, reset, puts)
+    }
+
+    #[tokio::test]
+    async fn stale_connection_retry() {
+        let (dev, reset, _) = proxied_device().await;
+
+        assert!(dev.is_powered_on().await.unwrap());
+        assert_eq!(dev.pool_stats().stale_retries, 0);
+
+        // The pooled connection is reset on the next request
+        reset.store(true, Ordering::SeqCst);
+        assert!(dev.is_powered_on().await.unwrap());
+        assert_eq!(
+            dev.pool_stats(),
+            PoolStats {
+                requests: 2,
+                stale_retries: 1
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn volume_cache_rebuilt() {
+        // Device API whose volume moves to another path, like after a firmware update
+        let moved = Arc::new(AtomicBool::new(false));
+        let requests = Arc::new(AtomicUsize::new(0));
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+        let api = warp::path::full().map({
+            let moved = moved.clone();
+            let requests = requests.clone();
+            move |path: warp::path::FullPath| {
+                requests.fetch_add(1, Ordering::SeqCst);
+                let (endpoint, value) = match moved.load(Ordering::SeqCst) {
+                    false => ("/tv_settings/audio/volume", 10),
+                    true => ("/tv_settings/audio/audio_settings/volume", 20),
+                };
+                let res = if path.as_str().ends_with(endpoint) {
+                    json!({
+                        "STATUS": {"RESULT": "SUCCESS", "DETAIL": "Success"},
+                        "HASHLIST": [1, 2],
+                        "ITEMS": [{
+                            "CNAME": "volume",
+                            "NAME": "Volume",
+                            "TYPE": "T_VALUE_ABS_V1",
+                            "HASHVAL": 3,
+                            "VALUE": value,
+                            "CENTER": 0,
+                            "INCREMENT": 1,
+                            "MAXIMUM": 100,
+                            "MINIMUM": 0
+                        }]
+                    })
+                } else {
+                    json!({"STATUS": {"RESULT": "URI_NOT_FOUND", "DETAIL": "Uri not found"}})
+                };
+                warp::reply::json(&res)
+            }
+        });
+        let (api_addr, server) = warp::serve(api)
+            .tls()
+            .cert(cert.serialize_pem().unwrap())
+            .key(cert.serialize_private_key_pem())
+            .bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        let dev = Device::new("name", "Vizio", "model", "127.0.0.1", "uuid")
+            .await
+            .unwrap();
+        *dev.inner.port.write().await = api_addr.port();
+        *dev.inner.settings_root.write().await = "tv_settings".into();
+
+        assert_eq!(dev.volume().await.unwrap(), 10);
+        moved.store(true, Ordering::SeqCst);
+
+        // The cached endpoint is gone, so the cache is dropped and built again in the same read
+        assert_eq!(dev.volume().await.unwrap(), 20);
+
+        let start = requests.load(Ordering::SeqCst);
+        assert_eq!(dev.volume().await.unwrap(), 20);
+        assert_eq!(requests.load(Ordering::SeqCst) - start, 1);
+    }
+
+    #[tokio::test]
+    async fn slider_without_bounds() {
+        // Device API whose slider has no bounds at the dynamic endpoint, and whose static endpoint
+        // fails until it is fixed
+        let fixed = Arc::new(AtomicBool::new(false));
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+        let api = warp::path::full().map({
+            let fixed = fixed.clone();
+            move |path: warp::path::FullPath| {
+                let res = if path.as_str().starts_with("/menu_native/static")
+                    && !fixed.load(Ordering::SeqCst)
+                {
+                    json!({"STATUS": {"RESULT": "URI_NOT_FOUND", "DETAIL": "Uri not found"}})
+                } else if path.as_str().ends_with("/tv_settings/picture/backlight") {
+                    json!({
+                        "STATUS": {"RESULT": "SUCCESS", "DETAIL": "Success"},
+                        "HASHLIST": [1, 2],
+                        "ITEMS": [{
+                            "CNAME": "backlight",
+                            "NAME": "Backlight",
+                            "TYPE": "T_VALUE_ABS_V1",
+                            "HASHVAL": 3,
+                            "VALUE": 50
+                        }]
+                    })
+                } else {
+                    json!({"STATUS": {"RESULT": "URI_NOT_FOUND", "DETAIL": "Uri not found"}})
+                };
+                warp::reply::json(&res)
+            }
+        });
+        let (api_addr, server) = warp::serve(api)
+            .tls()
+            .cert(cert.serialize_pem().unwrap())
+            .key(cert.serialize_private_key_pem())
+            .bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        let dev = Device::new("name", "Vizio", "model", "127.0.0.1", "uuid")
+            .await
+            .unwrap();
+        *dev.inner.port.write().await = api_addr.port();
+        *dev.inner.settings_root.write().await = "tv_settings".into();
+
+        let setting = settings::at_path(dev.clone(), &["picture/backlight"])
+            .await
+            .unwrap();
+        assert!(matches!(
+            setting.slider_info().await,
+            Err(Error::Api(ApiError::UriNotFound))
+        ));
+
+        fixed.store(true, Ordering::SeqCst);
+        assert!(setting.slider_info().await.unwrap().is_none());
+        assert!(matches!(
+            setting.validate(40).await,
+            Err(Error::Client(ClientError::SliderNoBounds(name))) if name == "Backlight"
+        ));
+        assert!(matches!(
+            setting.update(40).await,
+            Err(Error::Client(ClientError::SliderNoBounds(name))) if name == "Backlight"
+        ));
+    }
+
+    #[tokio::test]
+    async fn list_without_value() {
+        // Device API whose eco mode list has elements but no value
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+        let api = warp::path::full().map(|path: warp::path::FullPath| {
+            let res = if path.as_str().ends_with("/tv_settings/picture/eco_mode") {
+                json!({
+                    "STATUS": {"RESULT": "SUCCESS", "DETAIL": "Success"},
+                    "HASHLIST": [1, 2],
+                    "ITEMS": [{
+                        "CNAME": "eco_mode",
+                        "NAME": "Eco Mode",
+                        "TYPE": "T_LIST_V1",
+                        "HASHVAL": 3,
+                        "ELEMENTS": ["Off", "Standard"]
+                    }]
+                })
+            } else {
+                json!({"STATUS": {"RESULT": "URI_NOT_FOUND", "DETAIL": "Uri not found"}})
+            };
+            warp::reply::json(&res)
+        });
+        let (api_addr, server) = warp::serve(api)
+            .tls()
+            .cert(cert.serialize_pem().unwrap())
+            .key(cert.serialize_private_key_pem())
+            .bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        let dev = Device::new("name", "Vizio", "model", "127.0.0.1", "uuid")
+            .await
+            .unwrap();
+        *dev.inner.port.write().await = api_addr.port();
+        *dev.inner.settings_root.write().await = "tv_settings".into();
+
+        assert!(matches!(
+            dev.eco_mode().await,
+            Err(Error::Client(ClientError::NoValue {
+                feature: "eco mode"
+            }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn stale_connection_put_not_resent() {
+        let (dev, reset, puts) = proxied_device().await;
+
+        assert!(dev.is_powered_on().await.unwrap());
+
+        // The device may have acted on a PUT before its connection was reset, so sending it again
+        // could press the key twice
+        reset.store(true, Ordering::SeqCst);
+        assert!(dev.key_press(Button::Menu).await.is_err());
+        assert_eq!(puts.load(Ordering::SeqCst), 0);
+        assert_eq!(
+            dev.pool_stats(),
+            PoolStats {
+                requests: 2,
+                stale_retries: 0
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn launch_app_by_name() {
+        // App catalog
+        let payloads = json!([
+            {
+                "id": "1",
+                "chipsets": {"*": [{"app_type_payload": "{\"NAME_SPACE\":3,\"APP_ID\":\"1\",\"MESSAGE\":null}"}]}
+            },
+            {
+                "id": "2",
+                "chipsets": {"*": [{"app_type_payload": "{\"NAME_SPACE\":2,\"APP_ID\":\"3\",\"MESSAGE\":null}"}]}
+            }
+        ]);
+        let apps = json!([
+            {
+                "id": "1",
+                "name": "Netflix",
+                "mobileAppInfo": {"description": "", "app_icon_image_url": ""}
+            },
+            {
+                "id": "2",
+                "name": "Prime Video",
+                "mobileAppInfo": {"description": "", "app_icon_image_url": ""}
+            }
+        ]);
+        let catalog = warp::path("payloads")
+            .map(move || warp::reply::json(&payloads))
+            .or(warp::path("apps").map(move || warp::reply::json(&apps)));
+        let (catalog_addr, server) = warp::serve(catalog).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        // Device API which records the launched app
+        let launched = Arc::new(Mutex::new(None));
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+        let api = warp::path!("app" / "launch").and(warp::body::json()).map({
+            let launched = launched.clone();
+            move |body: Value| {
+                *launched.lock().unwrap() = Some(body["VALUE"].clone());
+                r#"{"STATUS": {"RESULT": "SUCCESS", "DETAIL": "Success"}}"#
+            }
+        });
+        let (api_addr, server) = warp::serve(api)
+            .tls()
+            .cert(cert.serialize_pem().unwrap())
+            .key(cert.serialize_private_key_pem())
+            .bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        let dev = Device::new("name", "Vizio", "model", "127.0.0.1", "uuid")
+            .await
+            .unwrap();
+        *dev.inner.port.write().await = api_addr.port();
+        *dev.inner.app_list.write().await = AppList::with_urls(
+            format!("http://{}/payloads", catalog_addr),
+            format!("http://{}/apps", catalog_addr),
+        )
+        .unwrap();
+
+        let app = dev.launch_app_by_name("prime").await.unwrap();
+        assert_eq!(app.name(), "Prime Video");
+        assert_eq!(
+            launched.lock().unwrap().take(),
+            Some(json!({"NAME_SPACE": 2, "APP_ID": "3", "MESSAGE": null}))
+        );
+
+        assert!(matches!(
+            dev.launch_app_by_name("hulu").await,
+            Err(Error::Client(ClientError::AppNotFound(_, _)))
+        ));
+        assert!(launched.lock().unwrap().is_none());
+
+        assert!(matches!(
+            dev.launch_app_by_name(" ").await,
+            Err(Error::Client(ClientError::EmptyAppName))
+        ));
+        assert!(launched.lock().unwrap().is_none());
+    }
+}
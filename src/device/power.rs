@@ -0,0 +1,98 @@
+/// The kind of SmartCast device, see [`device_kind()`](super::Device::device_kind)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceKind {
+    /// A TV, with settings under `tv_settings`
+    TV,
+    /// A soundbar, with settings under `audio_settings`
+    SoundBar,
+}
+
+impl DeviceKind {
+    pub(super) fn from_settings_root(settings_root: &str) -> Self {
+        match settings_root {
+            "audio_settings" => Self::SoundBar,
+            _ => Self::TV,
+        }
+    }
+}
+
+/// Power state of the device, see [`power_state()`](super::Device::power_state)
+///
+/// The device reports its power mode as an integer whose meaning depends on the kind of device:
+///
+/// | Value | [`TV`](DeviceKind::TV) | [`SoundBar`](DeviceKind::SoundBar) |
+/// |-------|------------------------|------------------------------------|
+/// | 0     | [`Off`](Self::Off)     | [`Off`](Self::Off)                 |
+/// | 1     | [`On`](Self::On)       | [`On`](Self::On)                   |
+/// | 2     | [`Unknown`](Self::Unknown) | [`Standby`](Self::Standby)     |
+///
+/// Any other value is [`Unknown`](Self::Unknown).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerState {
+    /// Powered off
+    Off,
+    /// Powered on
+    On,
+    /// Soundbar only: off, but still listening for a Bluetooth or cast connection to wake it
+    Standby,
+    /// A power mode this library does not recognize
+    Unknown(u32),
+}
+
+impl PowerState {
+    pub(super) fn from_mode(kind: DeviceKind, mode: u32) -> Self {
+        match (kind, mode) {
+            (_, 0) => Self::Off,
+            (_, 1) => Self::On,
+            (DeviceKind::SoundBar, 2) => Self::Standby,
+            (_, other) => Self::Unknown(other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DeviceKind, PowerState};
+
+    #[test]
+    fn device_kind() {
+        assert_eq!(
+            DeviceKind::from_settings_root("tv_settings"),
+            DeviceKind::TV
+        );
+        assert_eq!(
+            DeviceKind::from_settings_root("audio_settings"),
+            DeviceKind::SoundBar
+        );
+    }
+
+    #[test]
+    fn tv_power_modes() {
+        assert_eq!(PowerState::from_mode(DeviceKind::TV, 0), PowerState::Off);
+        assert_eq!(PowerState::from_mode(DeviceKind::TV, 1), PowerState::On);
+        assert_eq!(
+            PowerState::from_mode(DeviceKind::TV, 2),
+            PowerState::Unknown(2)
+        );
+    }
+
+    #[test]
+    fn soundbar_power_modes() {
+        assert_eq!(
+            PowerState::from_mode(DeviceKind::SoundBar, 0),
+            PowerState::Off
+        );
+        assert_eq!(
+            PowerState::from_mode(DeviceKind::SoundBar, 1),
+            PowerState::On
+        );
+        assert_eq!(
+            PowerState::from_mode(DeviceKind::SoundBar, 2),
+            PowerState::Standby
+        );
+        assert_eq!(
+            PowerState::from_mode(DeviceKind::SoundBar, 7),
+            PowerState::Unknown(7)
+        );
+    }
+}
@@ -0,0 +1,111 @@
+/// Behavior which differs between device models, see [`model_quirks()`](super::Device::model_quirks)
+///
+/// Vizio model names end with a letter for the model year, e.g. the "F" in "P65-F1" is 2018.
+/// Quirks are looked up by that year. Models which are not recognized get the default, where
+/// the library falls back to trying each option in turn.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ModelQuirks {
+    /// Year the model was released, if it could be read from the model name
+    pub model_year: Option<u16>,
+    /// Port the API is served on, which is tried first when connecting
+    pub preferred_port: Option<u16>,
+    /// Whether the directional pad uses the alternate remote codes, which are then sent first
+    /// instead of after the usual codes are rejected
+    pub alt_directional_codes: bool,
+}
+
+/// Model year for each year letter
+const MODEL_YEARS: &[(char, u16)] = &[
+    ('D', 2016),
+    ('E', 2017),
+    ('F', 2018),
+    ('G', 2019),
+    ('H', 2020),
+    ('J', 2021),
+];
+
+/// Quirks of models released up to and including a year, checked in order
+const QUIRKS_BY_YEAR: &[(u16, ModelQuirks)] = &[
+    (
+        2016,
+        ModelQuirks {
+            model_year: None,
+            preferred_port: Some(9000),
+            alt_directional_codes: true,
+        },
+    ),
+    (
+        2017,
+        ModelQuirks {
+            model_year: None,
+            preferred_port: Some(9000),
+            alt_directional_codes: false,
+        },
+    ),
+    (
+        u16::MAX,
+        ModelQuirks {
+            model_year: None,
+            preferred_port: Some(7345),
+            alt_directional_codes: false,
+        },
+    ),
+];
+
+impl ModelQuirks {
+    pub(super) fn for_model(model: &str) -> Self {
+        let model_year = match model_year(model) {
+            Some(year) => year,
+            None => return Self::default(),
+        };
+
+        QUIRKS_BY_YEAR
+            .iter()
+            .find(|(up_to, _)| model_year <= *up_to)
+            .map(|(_, quirks)| Self {
+                model_year: Some(model_year),
+                ..*quirks
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Read the model year from the letter after the dash, e.g. "P65-F1" or "E55-E2"
+fn model_year(model: &str) -> Option<u16> {
+    let (_, suffix) = model.trim().rsplit_once('-')?;
+    let letter = suffix.chars().next()?.to_ascii_uppercase();
+    MODEL_YEARS
+        .iter()
+        .find(|(year_letter, _)| *year_letter == letter)
+        .map(|(_, year)| *year)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ModelQuirks;
+
+    #[test]
+    fn known_models() {
+        let quirks = ModelQuirks::for_model("E50-D1");
+        assert_eq!(quirks.model_year, Some(2016));
+        assert_eq!(quirks.preferred_port, Some(9000));
+        assert!(quirks.alt_directional_codes);
+
+        let quirks = ModelQuirks::for_model("M55-E0");
+        assert_eq!(quirks.model_year, Some(2017));
+        assert_eq!(quirks.preferred_port, Some(9000));
+        assert!(!quirks.alt_directional_codes);
+
+        let quirks = ModelQuirks::for_model("P65-F1");
+        assert_eq!(quirks.model_year, Some(2018));
+        assert_eq!(quirks.preferred_port, Some(7345));
+        assert!(!quirks.alt_directional_codes);
+    }
+
+    #[test]
+    fn unknown_models() {
+        assert_eq!(ModelQuirks::for_model("SB3621"), ModelQuirks::default());
+        assert_eq!(ModelQuirks::for_model("X65-Z9"), ModelQuirks::default());
+        assert_eq!(ModelQuirks::for_model(""), ModelQuirks::default());
+    }
+}
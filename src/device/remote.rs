@@ -1,10 +1,12 @@
-use std::fmt::Debug;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
 
-/// Button interactions used in `key(up|down|press)()` in [super::Device]
+use std::fmt::{Debug, Display};
+
+/// Button interactions used in [`Device::key_combo()`](super::Device::key_combo)
 ///
 /// Must include a [`Button`] to specify what you want to interact with
-#[derive(Debug, Clone, Copy)]
-pub(super) enum KeyEvent {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyEvent {
     /// Hold the button down
     Down,
     /// Release the button after a hold
@@ -13,14 +15,92 @@ pub(super) enum KeyEvent {
     Press,
 }
 
-impl ToString for KeyEvent {
-    fn to_string(&self) -> String {
+impl Display for KeyEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Down => "KEYDOWN",
-            Self::Up => "KEYUP",
-            Self::Press => "KEYPRESS",
+            Self::Down => write!(f, "KEYDOWN"),
+            Self::Up => write!(f, "KEYUP"),
+            Self::Press => write!(f, "KEYPRESS"),
+        }
+    }
+}
+
+/// Remote codes a device uses for the directional pad, see
+/// [`set_remote_codeset()`](super::Device::set_remote_codeset)
+///
+/// Every other button has the same code in both sets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeSet {
+    /// The usual codes
+    Default,
+    /// The alternate codes some devices use for left, up, and right
+    Secondary,
+}
+
+impl CodeSet {
+    /// Build `KEYLIST` entries with this code set's codes
+    pub(super) fn button_events(self, events: &[(KeyEvent, Button)]) -> Vec<ButtonEvent> {
+        events
+            .iter()
+            .map(|&(event, button)| match (self, button.alt()) {
+                (Self::Secondary, Some(button_alt)) => ButtonEvent::new(event, button_alt),
+                _ => ButtonEvent::new(event, button),
+            })
+            .collect()
+    }
+}
+
+/// What was sent for a key press, returned by
+/// [`key_press_detailed()`](super::Device::key_press_detailed)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyResult {
+    /// Code set the device accepted. Keys outside the directional pad are always sent with
+    /// [`Default`](CodeSet::Default) codes.
+    pub codeset: CodeSet,
+    /// Whether the device rejected the code set tried first, so the other one was sent
+    pub fallback: bool,
+    /// `CODESET` and `CODE` of each key event which was sent, in order
+    pub codes: Vec<(u8, u8)>,
+}
+
+impl KeyResult {
+    pub(super) fn new(codeset: CodeSet, fallback: bool, events: &[ButtonEvent]) -> Self {
+        Self {
+            codeset,
+            fallback,
+            codes: events.iter().map(|e| (e.codeset, e.code)).collect(),
+        }
+    }
+}
+
+/// A single entry of a virtual remote `KEYLIST`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct ButtonEvent {
+    codeset: u8,
+    code: u8,
+    action: KeyEvent,
+}
+
+impl ButtonEvent {
+    pub fn new(action: KeyEvent, button: Button) -> Self {
+        Self {
+            codeset: button.codeset(),
+            code: button.code(),
+            action,
         }
-        .to_string()
+    }
+}
+
+impl Serialize for ButtonEvent {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut event = serializer.serialize_struct("ButtonEvent", 3)?;
+        event.serialize_field("CODESET", &self.codeset)?;
+        event.serialize_field("CODE", &self.code)?;
+        event.serialize_field("ACTION", &self.action.to_string())?;
+        event.end()
     }
 }
 
@@ -33,7 +113,7 @@ impl From<KeyEvent> for Vec<KeyEvent> {
 /// Remote control "buttons" you can interact with using [`Device::key_press()`](super::Device::key_press),
 /// [`Device::key_down()`](super::Device::key_down), or [`Device::key_up()`](super::Device::key_up)
 #[allow(unused)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Button {
     /// Seek Forward
     SeekFwd,
@@ -101,11 +181,45 @@ pub enum Button {
     PowerOn,
     /// Toggle power
     PowerToggle,
+    /// Number pad 0
+    Num0,
+    /// Number pad 1
+    Num1,
+    /// Number pad 2
+    Num2,
+    /// Number pad 3
+    Num3,
+    /// Number pad 4
+    Num4,
+    /// Number pad 5
+    Num5,
+    /// Number pad 6
+    Num6,
+    /// Number pad 7
+    Num7,
+    /// Number pad 8
+    Num8,
+    /// Number pad 9
+    Num9,
+    /// Number pad dash, separates a sub-channel
+    Dash,
 }
 
 impl Button {
     pub(super) fn codeset(&self) -> u8 {
         match self {
+            Self::Num0
+            | Self::Num1
+            | Self::Num2
+            | Self::Num3
+            | Self::Num4
+            | Self::Num5
+            | Self::Num6
+            | Self::Num7
+            | Self::Num8
+            | Self::Num9
+            | Self::Dash => 0,
+
             Self::SeekFwd | Self::SeekBack | Self::Pause | Self::Play => 2,
 
             Self::Down
@@ -142,6 +256,19 @@ impl Button {
 
     pub(super) fn code(&self) -> u8 {
         match self {
+            // Code set 0
+            Self::Num0 => 48,
+            Self::Num1 => 49,
+            Self::Num2 => 50,
+            Self::Num3 => 51,
+            Self::Num4 => 52,
+            Self::Num5 => 53,
+            Self::Num6 => 54,
+            Self::Num7 => 55,
+            Self::Num8 => 56,
+            Self::Num9 => 57,
+            Self::Dash => 45,
+
             // Code set 2
             Self::SeekFwd => 0,
             Self::SeekBack => 1,
@@ -195,6 +322,23 @@ impl Button {
         }
     }
 
+    /// Number pad button for a digit
+    pub(super) fn digit(digit: char) -> Option<Self> {
+        match digit {
+            '0' => Some(Self::Num0),
+            '1' => Some(Self::Num1),
+            '2' => Some(Self::Num2),
+            '3' => Some(Self::Num3),
+            '4' => Some(Self::Num4),
+            '5' => Some(Self::Num5),
+            '6' => Some(Self::Num6),
+            '7' => Some(Self::Num7),
+            '8' => Some(Self::Num8),
+            '9' => Some(Self::Num9),
+            _ => None,
+        }
+    }
+
     pub(super) fn alt(&self) -> Option<Self> {
         match self {
             Self::Left => Some(Self::LeftAlt),
@@ -1,9 +1,20 @@
-use super::{DeviceInfo, Input, Payload, SliderInfo, SubSetting};
+use super::{AppPayload, DeviceInfo, Input, SliderInfo, SubSetting};
 use crate::error::{ApiError, Error, Result};
 
 use serde::Deserialize;
 use serde_json::Value;
 
+/// `HASHLIST` of a settings response. The first hash is for the menu as a whole, which settings
+/// without a `HASHVAL` of their own are written with.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub(super) struct HashList(Vec<u32>);
+
+impl HashList {
+    pub fn menu(&self) -> Option<u32> {
+        self.0.first().copied()
+    }
+}
+
 #[derive(Debug)]
 pub(super) struct Response {
     pub value: Value,
@@ -44,10 +55,23 @@ impl Response {
         .map_err(|e| e.into())
     }
 
+    /// Deserialize `ITEM` if the response has one, otherwise `ITEMS`
+    pub fn payload<T>(mut self) -> Result<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        if self.value.get("ITEM").is_some() {
+            self.first_item(None)
+        } else {
+            self.items()
+        }
+    }
+
+    /// Some firmware sends the pairing values as quoted strings rather than numbers
     pub fn pairing(mut self) -> Result<(u32, u32)> {
         Ok((
-            self.first_item(Some("PAIRING_REQ_TOKEN"))?,
-            self.first_item(Some("CHALLENGE_TYPE"))?,
+            lenient_u32(self.first_item(Some("PAIRING_REQ_TOKEN"))?)?,
+            lenient_u32(self.first_item(Some("CHALLENGE_TYPE"))?)?,
         ))
     }
 
@@ -55,14 +79,18 @@ impl Response {
         self.first_item(Some("AUTH_TOKEN"))
     }
 
-    pub fn power_state(mut self) -> Result<bool> {
-        Ok(self.first_item::<i32>(Some("VALUE"))? == 1)
+    pub fn power_mode(mut self) -> Result<u32> {
+        self.first_item(Some("VALUE"))
     }
 
     pub fn device_info(mut self) -> Result<DeviceInfo> {
         self.first_item(Some("VALUE"))
     }
 
+    pub fn device_info_raw(mut self) -> Result<Value> {
+        self.first_item(Some("VALUE"))
+    }
+
     pub fn current_input(mut self) -> Result<Input> {
         self.first_item(None)
     }
@@ -71,10 +99,39 @@ impl Response {
         self.items()
     }
 
+    /// `HASHLIST` of a settings response, empty if it has none
+    pub fn hashlist(&self) -> HashList {
+        self.value
+            .get("HASHLIST")
+            .and_then(|hashlist| serde_json::from_value(hashlist.clone()).ok())
+            .unwrap_or_default()
+    }
+
     pub fn settings(mut self) -> Result<Vec<SubSetting>> {
         self.items()
     }
 
+    /// `CNAME` and `HASHVAL` of each item in a `HASHONLY` read, which has no values
+    pub fn hashes(mut self) -> Result<Vec<(String, Option<u32>)>> {
+        #[derive(Deserialize)]
+        struct HashOnly {
+            #[serde(rename = "CNAME")]
+            cname: String,
+            #[serde(rename = "HASHVAL")]
+            hashval: Option<u32>,
+        }
+
+        Ok(self
+            .items::<Vec<HashOnly>>()?
+            .into_iter()
+            .map(|item| (item.cname, item.hashval))
+            .collect())
+    }
+
+    pub fn setting(mut self) -> Result<SubSetting> {
+        self.first_item(None)
+    }
+
     pub fn slider_info(mut self) -> Option<SliderInfo> {
         self.first_item(None).ok()
     }
@@ -83,7 +140,18 @@ impl Response {
         self.first_item(Some("ELEMENTS"))
     }
 
-    pub fn app_payload(mut self) -> Result<Payload> {
+    /// `ELEMENTS` paired with the underlying `VALUES` of an `XList`. Lists without separate
+    /// values store the label itself.
+    pub fn element_pairs(mut self) -> Result<Vec<(String, String)>> {
+        let labels: Vec<String> = self.first_item(Some("ELEMENTS"))?;
+        let values = match self.first_item::<Vec<String>>(Some("VALUES")) {
+            Ok(values) if values.len() == labels.len() => values,
+            _ => labels.clone(),
+        };
+        Ok(labels.into_iter().zip(values).collect())
+    }
+
+    pub fn app_payload(mut self) -> Result<AppPayload> {
         self.first_item(Some("VALUE"))
     }
 }
@@ -124,12 +192,33 @@ impl From<Response> for Option<SliderInfo> {
     }
 }
 
-impl From<Response> for Result<Payload> {
+impl From<Response> for Result<AppPayload> {
     fn from(response: Response) -> Self {
         response.app_payload()
     }
 }
 
+/// The raw `STATUS` of a response from the device
+#[cfg(feature = "last-status")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Status {
+    /// `STATUS.RESULT`, e.g. "SUCCESS"
+    pub result: String,
+    /// `STATUS.DETAIL`, which is empty if the device did not give one
+    pub detail: String,
+}
+
+/// Read the `STATUS` of a response. Returns `None` if the response is not json or has no status.
+#[cfg(feature = "last-status")]
+pub(super) fn status(response: &str) -> Option<Status> {
+    let response: Value = serde_json::from_str(response).ok()?;
+    let status = response.get("STATUS")?;
+    Some(Status {
+        result: status["RESULT"].as_str()?.into(),
+        detail: status["DETAIL"].as_str().unwrap_or_default().into(),
+    })
+}
+
 pub(super) fn process(response: String) -> Result<Response> {
     let response: Value = match serde_json::from_str(&response) {
         Ok(res) => res,
@@ -141,6 +230,7 @@ pub(super) fn process(response: String) -> Result<Response> {
         .to_string()
         .to_lowercase()
         .replace("\"", "");
+    let detail = detail(&response, &result);
 
     Err(match result.as_str() {
         // Command was successful so return the response
@@ -149,11 +239,11 @@ pub(super) fn process(response: String) -> Result<Response> {
         // Anything else is an error
         "invalid_parameter" => ApiError::InvalidParameter,
         "uri_not_found" => ApiError::UriNotFound,
-        "max_challenges_exceeded" => ApiError::MaxChallengesExceeded,
-        "pairing_denied" => ApiError::PairingDenied,
-        "value_out_of_range" => ApiError::ValueOutOfRange,
-        "challenge_incorrect" => ApiError::ChallengeIncorrect,
-        "blocked" => ApiError::Blocked,
+        "max_challenges_exceeded" => ApiError::MaxChallengesExceeded(detail),
+        "pairing_denied" => ApiError::PairingDenied(detail),
+        "value_out_of_range" => ApiError::ValueOutOfRange(detail),
+        "challenge_incorrect" => ApiError::ChallengeIncorrect(detail),
+        "blocked" => ApiError::Blocked(detail),
         "failure" => ApiError::Failure,
         "aborted" => ApiError::Aborted,
         "busy" => ApiError::Busy,
@@ -173,10 +263,117 @@ pub(super) fn process(response: String) -> Result<Response> {
         "net_unknown_error" => ApiError::NetUnknown,
         _ => format!(
             "Status Result: {} Detail: {}",
-            response["STATUS"]["RESULT"].to_string(),
-            response["STATUS"]["DETAIL"].to_string()
+            response["STATUS"]["RESULT"], response["STATUS"]["DETAIL"]
         )
         .into(),
     }
     .into())
 }
+
+/// Get the status `DETAIL` of a response. Devices often repeat the result in the detail, in which
+/// case there is nothing more to say.
+fn detail(response: &Value, result: &str) -> Option<String> {
+    response["STATUS"]["DETAIL"]
+        .as_str()
+        .map(str::trim)
+        .filter(|detail| !detail.is_empty() && detail.to_lowercase().replace(' ', "_") != result)
+        .map(Into::into)
+}
+
+/// Read a `u32` that may have been sent as either a number or a string of digits
+fn lenient_u32(value: Value) -> Result<u32> {
+    match value {
+        Value::String(s) => s
+            .trim()
+            .parse()
+            .map_err(|_| Error::Client(format!("'{}' is not a valid number", s).into())),
+        value => serde_json::from_value(value).map_err(|e| e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{process, HashList, Response};
+    use crate::{ApiError, Error};
+
+    fn status(result: &str, detail: &str) -> String {
+        format!(
+            r#"{{"STATUS": {{"RESULT": "{}", "DETAIL": "{}"}}}}"#,
+            result, detail
+        )
+    }
+
+    #[test]
+    fn blocked_detail() {
+        let err = process(status("BLOCKED", "Another device is pairing")).unwrap_err();
+        match err {
+            Error::Api(ApiError::Blocked(detail)) => {
+                assert_eq!(detail.as_deref(), Some("Another device is pairing"))
+            }
+            e => panic!("unexpected error: {:?}", e),
+        }
+        assert_eq!(
+            process(status("BLOCKED", "Another device is pairing"))
+                .unwrap_err()
+                .to_string(),
+            "Command was blocked (Another device is pairing)"
+        );
+    }
+
+    #[test]
+    fn pairing_detail_repeats_result() {
+        let err = process(status("PAIRING_DENIED", "Pairing Denied")).unwrap_err();
+        assert!(matches!(err, Error::Api(ApiError::PairingDenied(None))));
+
+        let err = process(status("BLOCKED", "")).unwrap_err();
+        assert!(matches!(err, Error::Api(ApiError::Blocked(None))));
+    }
+
+    #[test]
+    fn hashlist_menu() {
+        let response = Response {
+            value: serde_json::json!({"HASHLIST": [1123, 4567], "ITEMS": []}),
+        };
+        assert_eq!(response.hashlist(), HashList(vec![1123, 4567]));
+        assert_eq!(response.hashlist().menu(), Some(1123));
+
+        let response = Response {
+            value: serde_json::json!({"ITEMS": []}),
+        };
+        assert_eq!(response.hashlist().menu(), None);
+    }
+
+    #[test]
+    fn pairing_token_number_or_string() {
+        let response = Response {
+            value: serde_json::json!({
+                "ITEM": {"PAIRING_REQ_TOKEN": 123456, "CHALLENGE_TYPE": 1}
+            }),
+        };
+        assert_eq!(response.pairing().unwrap(), (123456, 1));
+
+        let response = Response {
+            value: serde_json::json!({
+                "ITEMS": [{"PAIRING_REQ_TOKEN": "123456", "CHALLENGE_TYPE": "1"}]
+            }),
+        };
+        assert_eq!(response.pairing().unwrap(), (123456, 1));
+
+        let response = Response {
+            value: serde_json::json!({
+                "ITEM": {"PAIRING_REQ_TOKEN": "abc", "CHALLENGE_TYPE": 1}
+            }),
+        };
+        assert!(matches!(response.pairing(), Err(Error::Client(_))));
+    }
+
+    #[cfg(feature = "last-status")]
+    #[test]
+    fn raw_status() {
+        let status = super::status(&status("BLOCKED", "Blocked by another client")).unwrap();
+        assert_eq!(status.result, "BLOCKED");
+        assert_eq!(status.detail, "Blocked by another client");
+
+        assert!(super::status("not json").is_none());
+    }
+}
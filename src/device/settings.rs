@@ -1,32 +1,85 @@
 use super::{CommandDetail, Device, Response};
-use crate::error::{ClientError, Error, Result};
+use crate::error::{ApiError, ClientError, Error, Result};
 
 use async_trait::async_trait;
 use serde::{de, Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Value};
 
+use std::collections::HashSet;
 use std::fmt::{self, Debug};
 use std::result::Result as StdResult;
 
 #[async_trait]
 pub trait Write<T> {
-    async fn write(&self, new_value: T) -> Result<()>;
+    /// Check `new_value` against the setting and get the value to send to the device
+    async fn check(&self, new_value: T) -> Result<Value>;
 }
 
-#[derive(Debug, Clone)]
+/// Base the device serves settings from, see [`set_settings_base()`](super::Device::set_settings_base)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EndpointBase {
+    /// `/menu_native/static`, which has fixed information such as slider bounds
     Static,
+    /// `/menu_native/dynamic`, which has current values and menu contents
     Dynamic,
 }
 
 impl EndpointBase {
-    pub fn as_str(&self) -> String {
+    pub(super) fn as_str(&self) -> String {
         String::from("/menu_native")
             + match self {
                 Self::Static => "/static",
                 Self::Dynamic => "/dynamic",
             }
     }
+
+    fn other(self) -> Self {
+        match self {
+            Self::Static => Self::Dynamic,
+            Self::Dynamic => Self::Static,
+        }
+    }
+}
+
+/// Settings bases to read slider info and list elements from first, see
+/// [`set_settings_base()`](super::Device::set_settings_base)
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct SettingsBases {
+    /// Base which was set, used for both
+    pub set: Option<EndpointBase>,
+    /// Base learned from reading slider info
+    pub slider: Option<EndpointBase>,
+    /// Base learned from reading list elements
+    pub list: Option<EndpointBase>,
+}
+
+impl SettingsBases {
+    /// Base to read first for the slider info or elements of a setting of `setting_type`
+    pub fn get(&self, setting_type: &SettingType) -> Option<EndpointBase> {
+        let learned = match setting_type {
+            SettingType::Slider => self.slider,
+            SettingType::List | SettingType::XList => self.list,
+            _ => None,
+        };
+        self.set.or(learned)
+    }
+
+    /// Remember `base` as the one to read `setting_type` from first, replacing one learned
+    /// before. A base which was set is kept.
+    pub fn learn(&mut self, setting_type: &SettingType, base: EndpointBase) {
+        if self.set.is_some() {
+            return;
+        }
+        let learned = match setting_type {
+            SettingType::Slider => &mut self.slider,
+            SettingType::List | SettingType::XList => &mut self.list,
+            _ => return,
+        };
+        if *learned != Some(base) {
+            log::debug!("Learned settings base for {:?}: {:?}", setting_type, base);
+            *learned = Some(base);
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -36,6 +89,9 @@ pub enum SettingType {
     Slider,
     /// Common setting with some value
     Value,
+    /// Setting with some value which the device keeps in sync with other settings, e.g. the same
+    /// value across picture modes. It is written the same way as a [`Value`](Self::Value).
+    ValueSync,
     /// Menu containing more [`SubSetting`]s
     Menu,
     /// List of possible values which should be displayed. Use [`elements()`](SubSetting::elements) to get list data.
@@ -56,6 +112,7 @@ impl<'de> Deserialize<'de> for SettingType {
             "T_VALUE_ABS_V1" => SettingType::Slider,
             "T_LIST_V1" => SettingType::List,
             "T_VALUE_V1" => SettingType::Value,
+            "T_VALUE_SYNC_V1" => SettingType::ValueSync,
             "T_MENU_V1" => SettingType::Menu,
             "T_LIST_X_V1" => SettingType::XList,
             other => SettingType::Other(other.into()),
@@ -87,6 +144,172 @@ pub struct SliderInfo {
     pub center: Option<i32>,
 }
 
+impl SliderInfo {
+    /// Round `value` to the nearest value the slider can be set to, i.e. `min` plus a multiple
+    /// of `increment`. A value within the slider's bounds is rounded to a value within bounds.
+    pub fn snap(&self, value: i32) -> i32 {
+        if self.increment <= 1 {
+            return value;
+        }
+        let steps = ((value - self.min) as f64 / self.increment as f64).round() as i32;
+        let snapped = self.min + steps * self.increment;
+        if snapped > self.max && value <= self.max {
+            snapped - self.increment
+        } else {
+            snapped
+        }
+    }
+}
+
+/// Slider info from the static endpoint, with anything it is missing taken from the dynamic endpoint
+fn merge_slider_info(info: SliderInfo, dynamic: SliderInfo) -> SliderInfo {
+    SliderInfo {
+        dec_marker: if info.dec_marker.is_empty() {
+            dynamic.dec_marker
+        } else {
+            info.dec_marker
+        },
+        inc_marker: if info.inc_marker.is_empty() {
+            dynamic.inc_marker
+        } else {
+            info.inc_marker
+        },
+        center: info.center.or(dynamic.center),
+        ..info
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Possible values which can be written to a setting
+pub enum Bounds {
+    /// Value must be within the slider's bounds. See [`SliderInfo`].
+    Slider(SliderInfo),
+    /// Value must be one of the list's elements
+    Elements(Vec<String>),
+    /// Any value of the same type as the current value
+    Unbounded,
+}
+
+#[derive(Debug, Clone)]
+/// A writable [`SubSetting`] along with its [`Bounds`], returned by [`editable_settings()`](Device::editable_settings)
+/// and [`picture_settings()`](Device::picture_settings)
+pub struct EditableSetting {
+    /// The setting
+    pub setting: SubSetting,
+    /// Bounds of the setting's value
+    pub bounds: Bounds,
+}
+
+impl EditableSetting {
+    pub(super) async fn new(setting: SubSetting) -> Result<Self> {
+        let bounds = match setting.setting_type() {
+            SettingType::Slider => match setting.slider_info().await? {
+                Some(info) => Bounds::Slider(info),
+                None => Bounds::Unbounded,
+            },
+            SettingType::List | SettingType::XList => Bounds::Elements(setting.elements().await?),
+            _ => Bounds::Unbounded,
+        };
+        Ok(Self { setting, bounds })
+    }
+
+    /// Same as [`new()`](Self::new) for a setting which was not expanded, so a `Value` may still
+    /// be a slider. The slider info is read once for both.
+    async fn resolve(mut setting: SubSetting) -> Result<Self> {
+        if setting.object_type != SettingType::Value {
+            return Self::new(setting).await;
+        }
+
+        setting.object_type = SettingType::Slider;
+        let bounds = match setting.probe_slider_info().await? {
+            Some(info) => Bounds::Slider(info),
+            None => {
+                setting.object_type = SettingType::Value;
+                Bounds::Unbounded
+            }
+        };
+        Ok(Self { setting, bounds })
+    }
+}
+
+/// Words in a `CNAME` whose setting's value is left out of
+/// [`dump_settings_json()`](Device::dump_settings_json)
+const REDACTED_WORDS: &[&str] = &["password", "passphrase", "psk", "pin", "token", "secret"];
+
+#[derive(Debug, Clone)]
+/// A [`SubSetting`] tagged with whether it can be changed, returned by
+/// [`all_settings_tagged()`](Device::all_settings_tagged)
+pub struct TaggedSetting {
+    /// The setting
+    pub setting: SubSetting,
+    /// Whether the setting can be changed. See [`is_writable()`](SubSetting::is_writable).
+    pub editable: bool,
+    /// Bounds of the setting's value. Always [`Unbounded`](Bounds::Unbounded) for settings which
+    /// are not editable, since their bounds are not read.
+    pub bounds: Bounds,
+}
+
+impl TaggedSetting {
+    pub(super) async fn new(setting: SubSetting) -> Result<Self> {
+        if !setting.is_writable() {
+            return Ok(Self {
+                setting,
+                editable: false,
+                bounds: Bounds::Unbounded,
+            });
+        }
+
+        let EditableSetting { setting, bounds } = EditableSetting::new(setting).await?;
+        Ok(Self {
+            setting,
+            editable: true,
+            bounds,
+        })
+    }
+
+    /// Describe the setting as json for a bug report, leaving out a value which may be secret
+    pub(super) fn dump(&self) -> Value {
+        let setting = &self.setting;
+        let cname = setting.cname();
+        let value = if cname
+            .to_lowercase()
+            .split('_')
+            .any(|word| REDACTED_WORDS.contains(&word))
+        {
+            Value::String("<redacted>".into())
+        } else {
+            setting.value.clone().unwrap_or(Value::Null)
+        };
+        let bounds = match &self.bounds {
+            Bounds::Slider(info) => json!({
+                "min": info.min,
+                "max": info.max,
+                "increment": info.increment,
+                "center": info.center,
+                "dec_marker": info.dec_marker,
+                "inc_marker": info.inc_marker,
+            }),
+            Bounds::Elements(elements) => json!(elements),
+            Bounds::Unbounded => Value::Null,
+        };
+
+        json!({
+            "name": setting.name(),
+            "cname": cname,
+            "endpoint": setting.endpoint(),
+            "type": match setting.setting_type() {
+                SettingType::Other(other) => other,
+                known => format!("{:?}", known),
+            },
+            "value": value,
+            "hidden": setting.hidden(),
+            "read_only": setting.read_only(),
+            "editable": self.editable,
+            "bounds": bounds,
+        })
+    }
+}
+
 #[derive(Clone, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 /// Settings for a Device
@@ -170,6 +393,11 @@ pub struct SubSetting {
     value: Option<Value>, // Not a serde_json Value; the field named value
     #[serde(skip)]
     device: Option<Device>,
+    #[serde(skip)]
+    pending: Option<Value>,
+    /// Hash of the menu the setting is in, from the `HASHLIST` it was read with
+    #[serde(skip)]
+    menu_hashval: Option<u32>,
 }
 
 impl fmt::Debug for SubSetting {
@@ -191,7 +419,6 @@ impl SubSetting {
     ///
     /// ```
     /// # async fn example() -> Result<(), smartcast::Error> {
-
     /// use smartcast::{Device, SubSetting};
     ///
     /// let mut dev = Device::from_ip("192.168.0.14").await?;
@@ -235,7 +462,6 @@ impl SubSetting {
     /// // > },
     /// // > ...
     /// // > ]
-
     /// # Ok(())
     /// # }
     /// ```
@@ -245,19 +471,15 @@ impl SubSetting {
             return Ok(vec![self.clone()]);
         }
 
-        let mut settings: Vec<SubSetting> = self.dynamic_response().await?.settings()?;
+        let response = self.dynamic_response().await?;
+        let menu_hashval = response.hashlist().menu();
+        let mut settings: Vec<SubSetting> = response.settings()?;
 
         // Add device reference and update endpoint
         for s in settings.iter_mut() {
             s.add_parent_data(self);
-
-            // Some value types are actually sliders so try to update accordingly
-            if s.object_type == SettingType::Value {
-                s.object_type = SettingType::Slider;
-                if s.slider_info().await?.is_none() {
-                    s.object_type = SettingType::Value;
-                }
-            }
+            s.menu_hashval = menu_hashval;
+            s.resolve_slider().await?;
         }
         Ok(settings)
     }
@@ -277,6 +499,14 @@ impl SubSetting {
         self.readonly
     }
 
+    /// Returns true if the setting can be changed with [`update()`](Self::update).
+    pub fn is_writable(&self) -> bool {
+        !matches!(self.object_type, SettingType::Menu)
+            && !self.readonly
+            && self.value.is_some()
+            && self.write_hashval().is_some()
+    }
+
     /// Type of the settings object. See [`SettingType`].
     pub fn setting_type(&self) -> SettingType {
         self.object_type.clone()
@@ -315,7 +545,6 @@ impl SubSetting {
     ///
     /// ```
     /// # async fn example() -> Result<(), smartcast::Error> {
-
     /// use smartcast::{Device, SubSetting};
     ///
     /// let mut dev = Device::from_ip("192.168.0.14").await?;
@@ -341,7 +570,6 @@ impl SubSetting {
     ///     println!("{}", value);
     /// }
     /// // > Calibrated
-
     /// # Ok(())
     /// # }
     /// ```
@@ -362,13 +590,19 @@ impl SubSetting {
     /// * The setting is `read-only`.
     /// * The value passed in is not the same type as the value currently in the setting.
     /// * In the case of a `Slider`, the value passed in is higher than the max or lower than the min.
+    ///   Values within bounds are rounded to the slider's increment. See [`SliderInfo::snap()`]
+    ///   and [`update_strict()`](Self::update_strict).
     /// * In the case of a `List` or `XList`, the value passed in is not present in the setting's [`Elements`](Self::elements).
+    ///   An `XList` element may be given by its label or its value, see [`element_pairs()`](Self::element_pairs).
     /// * The [`setting type`](Self::setting_type) is not a `Slider`, `List`, `Xlist`, or `Value`.
     ///
+    /// Settings which the device confirms on screen before applying, such as a factory reset, are
+    /// not supported. The confirmation sequence is not documented and guessing at it risks
+    /// triggering a destructive action, so such settings should be changed on the device itself.
+    ///
     /// # Example
     /// ```
     /// # async fn example() -> Result<(), smartcast::Error> {
-
     /// use smartcast::{Device, SettingType, SubSetting};
     ///
     /// let dev = Device::from_ip("192.168.0.14").await?;
@@ -392,7 +626,6 @@ impl SubSetting {
     ///         _ => {},
     ///     }
     /// }
-
     /// # Ok(())
     /// # }
     /// ```
@@ -402,35 +635,83 @@ impl SubSetting {
         T: Serialize + for<'de> Deserialize<'de> + Debug,
     {
         log::trace!("Update SubSetting");
+        self.write(new_value).await.map(drop)
+    }
 
-        // Check object is not read only and is not Menu
-        if matches!(self.object_type, SettingType::Menu)
-            || self.readonly
-            || self.value.is_none()
-            || self.hashval.is_none()
-        {
-            Err(ClientError::WriteSettingsReadOnly.into())
-        }
-        // Check new value type matches current type
-        else if let Err(_e) = serde_json::from_value::<T>(self.value.clone().unwrap()) {
-            Err(Error::setting_type_bad_match(
-                self.value.clone().unwrap(),
-                serde_json::json!(new_value),
-            ))
-        }
-        // Continue with Setting update
-        else {
-            self.write(new_value).await
+    /// Run the same checks as [`update()`](Self::update) without writing anything
+    ///
+    /// Returns the error `update()` would return for `new_value`, if any, so a UI can reject
+    /// invalid input before saving it. The slider info or list elements are read from the device
+    /// when the check needs them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    /// dev.set_auth_token("Z2zscc1udl");
+    ///
+    /// let setting = dev.find_setting_by_cname("backlight").await?;
+    /// if setting.validate(150).await.is_err() {
+    ///     println!("150 is not a valid backlight level");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn validate<T>(&self, new_value: T) -> Result<()>
+    where
+        SubSetting: Write<T>,
+        T: Serialize + for<'de> Deserialize<'de> + Debug,
+    {
+        log::trace!("Validate SubSetting");
+        self.check_type(&new_value)?;
+        self.check(new_value).await.map(|_| ())
+    }
+
+    /// Same as [`update()`](Self::update), but a `Slider` value which is not on the slider's
+    /// increment is an error instead of being rounded.
+    pub async fn update_strict(&self, new_value: i32) -> Result<()> {
+        if let Some(info) = self.slider_info().await? {
+            if (info.min..=info.max).contains(&new_value) && info.snap(new_value) != new_value {
+                return Err(Error::setting_off_increment(info.increment, new_value));
+            }
         }
+        self.update(new_value).await
+    }
+
+    /// Stage `new_value` to be written later with [`flush_changes()`](Device::flush_changes)
+    ///
+    /// Nothing is sent to the device until then, and staging again replaces the earlier value.
+    /// The value is checked the same way as with [`update()`](Self::update) when it is written.
+    pub fn stage<T>(&mut self, new_value: T)
+    where
+        SubSetting: Write<T>,
+        T: Serialize,
+    {
+        self.pending = Some(serde_json::json!(new_value));
+    }
+
+    /// Returns true if a value was [`stage`](Self::stage)d and has not been written yet.
+    pub fn is_dirty(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Drop the staged value, if any.
+    pub fn discard(&mut self) {
+        self.pending = None;
     }
 
     /// If the setting object is a `Slider`, get the slider info. See [`SliderInfo`].
     ///
+    /// Devices report the slider info at the static endpoint, the dynamic endpoint, or both. The
+    /// static info is preferred, with anything it is missing filled in from the dynamic info.
+    ///
     /// # Example
     ///
     /// ```
     /// # async fn example() -> Result<(), smartcast::Error> {
-
     /// use smartcast::{Device, SubSetting};
     ///
     /// let dev = Device::from_ip("192.168.0.14").await?;
@@ -464,19 +745,52 @@ impl SubSetting {
     /// // >     min: -50,
     /// // >     center: 0,
     /// // > }
-
     /// # Ok(())
     /// # }
     /// ```
     pub async fn slider_info(&self) -> Result<Option<SliderInfo>> {
         log::trace!("Get Slider Info");
-        if self.object_type == SettingType::Slider {
-            match self.static_response().await?.slider_info() {
-                Some(info) => Ok(Some(info)),
-                None => Ok(self.dynamic_response().await?.slider_info()),
+        if self.object_type != SettingType::Slider {
+            return Ok(None);
+        }
+
+        // Firmware differs in which endpoint has the slider info, and either may be missing. The
+        // one which had it before is tried first.
+        let device = self.device.clone().unwrap();
+        let first = device
+            .settings_base(&SettingType::Slider)
+            .await
+            .unwrap_or(EndpointBase::Static);
+        let first_info = self.response(first).await.map(Response::slider_info);
+        // Firmware which splits slider info between the endpoints leaves the center out of the
+        // one with the bounds, so it is only complete with a center
+        if let Ok(Some(info)) = &first_info {
+            device
+                .learn_settings_base(&SettingType::Slider, first)
+                .await;
+            if info.center.is_some() {
+                return first_info;
             }
-        } else {
-            Ok(None)
+        }
+        let second_info = self
+            .response(first.other())
+            .await
+            .map(Response::slider_info);
+        if !matches!(first_info, Ok(Some(_))) && matches!(second_info, Ok(Some(_))) {
+            device
+                .learn_settings_base(&SettingType::Slider, first.other())
+                .await;
+        }
+
+        let (static_info, dynamic_info) = match first {
+            EndpointBase::Static => (first_info, second_info),
+            EndpointBase::Dynamic => (second_info, first_info),
+        };
+        match (static_info, dynamic_info) {
+            (Ok(Some(info)), Ok(Some(dynamic))) => Ok(Some(merge_slider_info(info, dynamic))),
+            (Ok(Some(info)), _) | (_, Ok(Some(info))) => Ok(Some(info)),
+            (Err(e), _) => Err(e),
+            _ => Ok(None),
         }
     }
 
@@ -486,7 +800,6 @@ impl SubSetting {
     ///
     /// ```
     /// # async fn example() -> Result<(), smartcast::Error> {
-
     /// use smartcast::{Device, SubSetting};
     ///
     /// let dev = Device::from_ip("192.168.0.14").await?;
@@ -517,22 +830,59 @@ impl SubSetting {
     /// // >     "Game",
     /// // >     "Sports",
     /// // > ],
-
     /// # Ok(())
     /// # }
     /// ```
     pub async fn elements(&self) -> Result<Vec<String>> {
         log::trace!("Get Elements");
         if self.object_type == SettingType::List || self.object_type == SettingType::XList {
-            match self.dynamic_response().await?.elements() {
-                Ok(elements) => Ok(elements),
-                Err(_) => Ok(self.static_response().await?.elements().unwrap_or_default()),
-            }
+            self.list_response(Response::elements).await
         } else {
             Ok(Vec::new())
         }
     }
 
+    /// If the setting object is a `List` or `XList`, get its elements paired with the value the
+    /// device stores for each, as `(label, value)`.
+    ///
+    /// An `XList` may display a label which differs from the value it stores. For a `List`, or an
+    /// `XList` without separate values, the value is the label itself. [`update()`](Self::update)
+    /// accepts either the label or the value and always writes the value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::Device;
+    ///
+    /// let dev = Device::from_ip("192.168.0.14").await?;
+    ///
+    /// let setting = dev.find_setting_by_cname("color_temperature").await?;
+    /// println!("{:?}", setting.element_pairs().await?);
+    /// // > [("Cool", "COOL"), ("Normal", "NORMAL"), ("Warm", "WARM")]
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn element_pairs(&self) -> Result<Vec<(String, String)>> {
+        log::trace!("Get Element Pairs");
+        if self.object_type == SettingType::List || self.object_type == SettingType::XList {
+            self.list_response(Response::element_pairs).await
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    /// Get the setting's `CNAME`, the last segment of its endpoint
+    ///
+    /// Unlike [`name()`](Self::name), this is not translated on localized devices.
+    pub fn cname(&self) -> String {
+        self.endpoint
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+            .to_string()
+    }
+
     pub(super) fn endpoint(&self) -> String {
         self.endpoint.clone()
     }
@@ -540,41 +890,323 @@ impl SubSetting {
     /// Get Setting value at the dynamic endpoint
     async fn dynamic_response(&self) -> Result<Response> {
         log::trace!("Get Dynamic Response");
+        self.response(EndpointBase::Dynamic).await
+    }
+
+    /// Get setting value at the endpoint under `base`
+    async fn response(&self, base: EndpointBase) -> Result<Response> {
         let device = self.device.clone().unwrap();
-        Ok(device
-            .send_command(CommandDetail::ReadSettings(
-                EndpointBase::Dynamic,
-                self.endpoint(),
-            ))
-            .await?)
+        device
+            .send_command(CommandDetail::ReadSettings(base, self.endpoint()))
+            .await
     }
 
-    /// Get setting value at the static endpoint
-    async fn static_response(&self) -> Result<Response> {
-        log::trace!("Get Static Response");
+    /// Read a list's elements with `read`, from the endpoint which had them before if known
+    async fn list_response<T: Default>(&self, read: fn(Response) -> Result<T>) -> Result<T> {
         let device = self.device.clone().unwrap();
-        Ok(device
-            .send_command(CommandDetail::ReadSettings(
-                EndpointBase::Static,
-                self.endpoint(),
-            ))
-            .await?)
+        // Elements are usually at the dynamic endpoint
+        let first = device
+            .settings_base(&self.object_type)
+            .await
+            .unwrap_or(EndpointBase::Dynamic);
+        if let Ok(found) = self.response(first).await.and_then(read) {
+            device.learn_settings_base(&self.object_type, first).await;
+            return Ok(found);
+        }
+
+        match read(self.response(first.other()).await?) {
+            Ok(found) => {
+                device
+                    .learn_settings_base(&self.object_type, first.other())
+                    .await;
+                Ok(found)
+            }
+            Err(_) => Ok(T::default()),
+        }
     }
 
     /// Get the top level settings menu
     async fn root(device: Device) -> Result<Vec<SubSetting>> {
         log::trace!("Get Settings Root");
-        let root = SubSetting {
-            endpoint: format!("/{}", device.settings_root()),
+        Self::root_menu(device).await.expand().await
+    }
+
+    /// Menu at the settings root
+    pub(super) async fn root_menu(device: Device) -> SubSetting {
+        Self::menu_at(device, "").await
+    }
+
+    /// Menu at `path` relative to the settings root, which may not exist
+    async fn menu_at(device: Device, path: &str) -> SubSetting {
+        let mut endpoint = format!("/{}", device.settings_root().await);
+        if !path.is_empty() {
+            endpoint = format!("{}/{}", endpoint, path.trim_matches('/'));
+        }
+        SubSetting {
+            endpoint,
             hashval: None,
             hidden: false,
             name: "".into(),
             readonly: false,
             object_type: SettingType::Menu,
             value: None,
-            device: Some(device.clone()),
+            device: Some(device),
+            pending: None,
+            menu_hashval: None,
+        }
+    }
+
+    /// Same as [`expand()`](Self::expand) without checking whether values are sliders, so only
+    /// one request is sent
+    pub(super) async fn children(&self) -> Result<Vec<SubSetting>> {
+        let response = self.dynamic_response().await?;
+        let menu_hashval = response.hashlist().menu();
+        let mut settings: Vec<SubSetting> = response.settings()?;
+        for s in settings.iter_mut() {
+            s.add_parent_data(self);
+            s.menu_hashval = menu_hashval;
+        }
+        Ok(settings)
+    }
+
+    /// Get the `CNAME` and hashval of every setting in the menu with a `HASHONLY` read, which
+    /// leaves out their values
+    pub(super) async fn hashes(&self) -> Result<Vec<(String, Option<u32>)>> {
+        let device = self.device.clone().unwrap();
+        device
+            .send_command(CommandDetail::ReadSettings(
+                EndpointBase::Dynamic,
+                format!("{}?hashonly=TRUE", self.endpoint()),
+            ))
+            .await?
+            .hashes()
+    }
+
+    /// Get a setting directly from its path relative to the settings root
+    async fn at_path(device: Device, path: &str) -> Result<SubSetting> {
+        log::trace!("Get Setting at Path");
+        log::debug!("at_path path: {}", path);
+        let endpoint = format!(
+            "/{}/{}",
+            device.settings_root().await,
+            path.trim_matches('/')
+        );
+
+        let response = device
+            .send_command(CommandDetail::ReadSettings(
+                EndpointBase::Dynamic,
+                endpoint.clone(),
+            ))
+            .await?;
+        let menu_hashval = response.hashlist().menu();
+        let mut setting = response.setting()?;
+        setting.device = Some(device);
+        setting.endpoint = endpoint;
+        setting.menu_hashval = menu_hashval;
+        setting.resolve_slider().await?;
+        Ok(setting)
+    }
+
+    /// Same setting, sent through `device` instead
+    pub(super) fn with_device(mut self, device: Device) -> Self {
+        self.device = Some(device);
+        self
+    }
+
+    /// Drop the device, so the setting can be stored in the device without keeping it alive.
+    /// It must be given one again with [`with_device()`](Self::with_device) before it is used.
+    pub(super) fn without_device(mut self) -> Self {
+        self.device = None;
+        self
+    }
+
+    pub(super) fn hashval(&self) -> Option<u32> {
+        self.hashval
+    }
+
+    /// Hash the setting is written with. Some firmware leaves out the `HASHVAL` of nested
+    /// settings, which are written with the hash of their menu instead.
+    fn write_hashval(&self) -> Option<u32> {
+        self.hashval.or(self.menu_hashval)
+    }
+
+    /// Endpoint of the menu the setting is in
+    fn menu(&self) -> &str {
+        self.endpoint
+            .rsplit_once('/')
+            .map(|(menu, _)| menu)
+            .unwrap_or_default()
+    }
+
+    /// Read the value and hashval from the device again
+    pub(super) async fn refresh(&mut self) -> Result<()> {
+        let response = self.dynamic_response().await?;
+        self.menu_hashval = response.hashlist().menu();
+        let current = response.setting()?;
+        self.hashval = current.hashval;
+        self.value = current.value;
+        Ok(())
+    }
+
+    /// Write the staged value and mark the setting clean if it was written
+    async fn write_pending(&mut self) -> Result<()> {
+        let pending = match self.pending.clone() {
+            Some(pending) => pending,
+            None => return Ok(()),
+        };
+        // The device stores what the checks turn the value into, e.g. a slider value rounded to
+        // the increment or an `XList` label swapped for its underlying value
+        let written = match pending.clone() {
+            Value::String(new_value) => self.write(new_value).await?,
+            Value::Bool(new_value) => self.write(new_value).await?,
+            Value::Number(new_value) => match new_value.as_i64() {
+                Some(new_value) => self.write(new_value).await?,
+                None => self.write(new_value.as_f64().unwrap_or_default()).await?,
+            },
+            _ => {
+                return Err(Error::setting_type_bad_match(
+                    self.value.clone().unwrap_or_default(),
+                    pending,
+                ))
+            }
         };
-        root.expand().await
+        self.value = Some(written);
+        self.pending = None;
+        Ok(())
+    }
+
+    /// Check and write `new_value`, returning the value which was sent
+    async fn write<T>(&self, new_value: T) -> Result<Value>
+    where
+        SubSetting: Write<T>,
+        T: Serialize + for<'de> Deserialize<'de> + Debug,
+    {
+        self.check_type(&new_value)?;
+        let new_value = self.check(new_value).await?;
+        self.send_write(new_value.clone()).await?;
+        Ok(new_value)
+    }
+
+    /// Check the setting can be written and `new_value` is the same type as the current value
+    fn check_type<T>(&self, new_value: &T) -> Result<()>
+    where
+        T: Serialize + for<'de> Deserialize<'de>,
+    {
+        // Check object is not read only and is not Menu
+        if !self.is_writable() {
+            Err(ClientError::WriteSettingsReadOnly.into())
+        }
+        // Check new value type matches current type
+        else if serde_json::from_value::<T>(self.value.clone().unwrap_or_default()).is_err() {
+            Err(self.bad_type(serde_json::json!(new_value)))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Error for a new value whose type the setting can not hold
+    fn bad_type(&self, new_value: Value) -> Error {
+        Error::setting_type_bad_match(self.value.clone().unwrap_or_default(), new_value)
+    }
+
+    /// Send a checked value to the device
+    ///
+    /// Settings prefetched by [`warm_up()`](Device::warm_up) are dropped before sending, so they
+    /// can not be handed out with the old value even if this is cancelled after the device took
+    /// the write, and again after, in case they were prefetched while the write was in flight.
+    ///
+    /// Some firmware answers a write with the setting's own `HASHVAL` with success but ignores
+    /// it, and only takes writes with the hash of the setting's menu. The first write which
+    /// changes a setting whose hashes differ is read back to learn whether the device does this.
+    /// On such a device every write is read back, and written again with the menu's hash if it
+    /// did not change.
+    async fn send_write(&self, new_value: Value) -> Result<()> {
+        let device = self.device.clone().unwrap();
+        device.discard_prefetched_settings().await;
+        let result = self.write_value(&device, new_value).await;
+        device.discard_prefetched_settings().await;
+        result
+    }
+
+    async fn write_value(&self, device: &Device, new_value: Value) -> Result<()> {
+        device
+            .send_command(CommandDetail::WriteSettings(
+                self.endpoint.clone(),
+                self.write_hashval().unwrap(),
+                new_value.clone(),
+            ))
+            .await?;
+
+        let menu_hashval = match (self.hashval, self.menu_hashval) {
+            (Some(hashval), Some(menu_hashval)) if hashval != menu_hashval => menu_hashval,
+            _ => return Ok(()),
+        };
+        match device.menu_hash_writes().await {
+            Some(false) => return Ok(()),
+            // Writing the current value again shows nothing
+            None if self.value.as_ref() == Some(&new_value) => return Ok(()),
+            _ => {}
+        }
+
+        let written = match self.dynamic_response().await.and_then(Response::setting) {
+            Ok(current) => current.value.as_ref() == Some(&new_value),
+            Err(e) => {
+                log::warn!(
+                    "Could not read '{}' back after writing: {}",
+                    self.endpoint,
+                    e
+                );
+                return Ok(());
+            }
+        };
+        if device.menu_hash_writes().await.is_none() {
+            device.learn_menu_hash_writes(!written).await;
+        }
+        if written {
+            return Ok(());
+        }
+
+        log::warn!(
+            "Write to '{}' had no effect, writing with the menu's hash",
+            self.endpoint
+        );
+        // The device may have stored the value differently, so the first write may have been
+        // taken after all
+        if let Err(e) = device
+            .send_command(CommandDetail::WriteSettings(
+                self.endpoint.clone(),
+                menu_hashval,
+                new_value,
+            ))
+            .await
+        {
+            log::warn!(
+                "Write to '{}' with the menu's hash failed: {}",
+                self.endpoint,
+                e
+            );
+        }
+        Ok(())
+    }
+
+    /// Some value types are actually sliders so try to update accordingly
+    pub(super) async fn resolve_slider(&mut self) -> Result<()> {
+        if self.object_type == SettingType::Value {
+            self.object_type = SettingType::Slider;
+            if self.probe_slider_info().await?.is_none() {
+                self.object_type = SettingType::Value;
+            }
+        }
+        Ok(())
+    }
+
+    /// Slider info of a `Value` typed as a slider to check whether it is one. Plain values often
+    /// have no static endpoint, so the device refusing the read means it is not a slider.
+    async fn probe_slider_info(&self) -> Result<Option<SliderInfo>> {
+        match self.slider_info().await {
+            Err(Error::Api(_)) => Ok(None),
+            other => other,
+        }
     }
 
     fn add_parent_data(&mut self, parent: &SubSetting) {
@@ -585,38 +1217,37 @@ impl SubSetting {
 
 #[async_trait]
 impl Write<String> for SubSetting {
-    async fn write(&self, new_value: String) -> Result<()> {
+    async fn check(&self, mut new_value: String) -> Result<Value> {
         match self.setting_type() {
             SettingType::List | SettingType::XList => {
-                if !self.elements().await?.contains(&new_value) {
-                    return Err(Error::setting_non_element());
+                // Elements are picked by label, but the device stores the underlying value
+                let pairs = self.element_pairs().await?;
+                match pairs
+                    .iter()
+                    .find(|(label, _)| *label == new_value)
+                    .or_else(|| pairs.iter().find(|(_, value)| *value == new_value))
+                {
+                    Some((_, value)) => new_value = value.clone(),
+                    None => return Err(Error::setting_non_element()),
                 }
             }
-            SettingType::Value => {}
-            _ => {
-                // Should have already been caught
-                panic!("Bad Type")
-            }
+            SettingType::Value | SettingType::ValueSync => {}
+            _ => return Err(self.bad_type(serde_json::json!(new_value))),
         }
-        let device = self.device.clone().unwrap();
-        device
-            .send_command(CommandDetail::WriteSettings(
-                self.endpoint.clone(),
-                self.hashval.unwrap(),
-                serde_json::json!(new_value),
-            ))
-            .await
-            .map(drop)
+        Ok(serde_json::json!(new_value))
     }
 }
 
 #[async_trait]
 impl Write<i32> for SubSetting {
-    async fn write(&self, new_value: i32) -> Result<()> {
+    async fn check(&self, mut new_value: i32) -> Result<Value> {
         match self.setting_type() {
-            SettingType::Value => {}
+            SettingType::Value | SettingType::ValueSync => {}
             SettingType::Slider => {
-                let slider_info = self.slider_info().await?.unwrap();
+                let slider_info = self
+                    .slider_info()
+                    .await?
+                    .ok_or_else(|| Error::slider_no_bounds(&self.name))?;
 
                 if new_value > slider_info.max || new_value < slider_info.min {
                     return Err(Error::setting_outside_bounds(
@@ -625,40 +1256,24 @@ impl Write<i32> for SubSetting {
                         new_value,
                     ));
                 }
+                new_value = slider_info.snap(new_value);
             }
-            _ => {
-                // Should have already been caught
-                panic!("Bad Type")
-            }
+            _ => return Err(self.bad_type(serde_json::json!(new_value))),
         }
-        let device = self.device.clone().unwrap();
-        device
-            .send_command(CommandDetail::WriteSettings(
-                self.endpoint.clone(),
-                self.hashval.unwrap(),
-                serde_json::json!(new_value),
-            ))
-            .await
-            .map(drop)
+        Ok(serde_json::json!(new_value))
     }
 }
 
 #[async_trait]
 impl Write<bool> for SubSetting {
-    async fn write(&self, new_value: bool) -> Result<()> {
-        if matches!(self.setting_type(), SettingType::Value) {
-            let device = self.device.clone().unwrap();
-            device
-                .send_command(CommandDetail::WriteSettings(
-                    self.endpoint.clone(),
-                    self.hashval.unwrap(),
-                    serde_json::json!(new_value),
-                ))
-                .await
-                .map(drop)
+    async fn check(&self, new_value: bool) -> Result<Value> {
+        if matches!(
+            self.setting_type(),
+            SettingType::Value | SettingType::ValueSync
+        ) {
+            Ok(serde_json::json!(new_value))
         } else {
-            // Should have already been caught
-            panic!("Bad Type")
+            Err(self.bad_type(serde_json::json!(new_value)))
         }
     }
 }
@@ -668,78 +1283,78 @@ impl Write<f64> for SubSetting {
     // Num types with max/min values larger than i32 should cast to f64 first because casting from float
     // to int converts the value instead of simply truncating bits. We want to preserve MIN/MAX so that we can
     // return errors instead of possibly returning successful because of bad conversions.
-    async fn write(&self, new_value: f64) -> Result<()> {
-        self.write(new_value as i32).await
+    async fn check(&self, new_value: f64) -> Result<Value> {
+        self.check(new_value as i32).await
     }
 }
 
 #[async_trait]
 impl Write<f32> for SubSetting {
-    async fn write(&self, new_value: f32) -> Result<()> {
-        self.write(new_value as i32).await
+    async fn check(&self, new_value: f32) -> Result<Value> {
+        self.check(new_value as i32).await
     }
 }
 
 #[async_trait]
 impl Write<u128> for SubSetting {
-    async fn write(&self, new_value: u128) -> Result<()> {
-        self.write(new_value as f64).await
+    async fn check(&self, new_value: u128) -> Result<Value> {
+        self.check(new_value as f64).await
     }
 }
 
 #[async_trait]
 impl Write<u64> for SubSetting {
-    async fn write(&self, new_value: u64) -> Result<()> {
-        self.write(new_value as f64).await
+    async fn check(&self, new_value: u64) -> Result<Value> {
+        self.check(new_value as f64).await
     }
 }
 
 #[async_trait]
 impl Write<u32> for SubSetting {
-    async fn write(&self, new_value: u32) -> Result<()> {
-        self.write(new_value as f64).await
+    async fn check(&self, new_value: u32) -> Result<Value> {
+        self.check(new_value as f64).await
     }
 }
 
 #[async_trait]
 impl Write<u16> for SubSetting {
-    async fn write(&self, new_value: u16) -> Result<()> {
-        self.write(new_value as i32).await
+    async fn check(&self, new_value: u16) -> Result<Value> {
+        self.check(new_value as i32).await
     }
 }
 
 #[async_trait]
 impl Write<u8> for SubSetting {
-    async fn write(&self, new_value: u8) -> Result<()> {
-        self.write(new_value as i32).await
+    async fn check(&self, new_value: u8) -> Result<Value> {
+        self.check(new_value as i32).await
     }
 }
 
 #[async_trait]
 impl Write<i128> for SubSetting {
-    async fn write(&self, new_value: i128) -> Result<()> {
-        self.write(new_value as f64).await
+    async fn check(&self, new_value: i128) -> Result<Value> {
+        self.check(new_value as f64).await
     }
 }
 
 #[async_trait]
 impl Write<i64> for SubSetting {
-    async fn write(&self, new_value: i64) -> Result<()> {
-        self.write(new_value as f64).await
+    async fn check(&self, new_value: i64) -> Result<Value> {
+        self.check(new_value as f64).await
     }
 }
 
 #[async_trait]
 impl Write<i16> for SubSetting {
-    async fn write(&self, new_value: i16) -> Result<()> {
-        self.write(new_value as i32).await
+    async fn check(&self, new_value: i16) -> Result<Value> {
+        self.check(new_value as i32).await
     }
 }
 
 #[async_trait]
 impl Write<i8> for SubSetting {
-    async fn write(&self, new_value: i8) -> Result<()> {
-        self.write(new_value as i32).await
+    async fn check(&self, new_value: i8) -> Result<Value> {
+        self.check(new_value as i32).await
     }
 }
 
@@ -747,13 +1362,319 @@ pub async fn root(device: Device) -> Result<Vec<SubSetting>> {
     SubSetting::root(device).await
 }
 
+/// Get every setting in the tree, depth first
+pub async fn all(device: Device) -> Result<Vec<SubSetting>> {
+    let mut settings = Vec::new();
+    let mut stack = SubSetting::root(device).await?;
+    stack.reverse();
+
+    while let Some(setting) = stack.pop() {
+        if setting.object_type == SettingType::Menu {
+            stack.extend(setting.expand().await?.into_iter().rev());
+        }
+        settings.push(setting);
+    }
+    Ok(settings)
+}
+
+/// Walk the tree depth first and get every setting whose name contains `query`, ignoring case,
+/// along with its path of names from the settings root, e.g. "Picture > Backlight"
+pub async fn search(device: Device, query: &str) -> Result<Vec<(String, SubSetting)>> {
+    let query = query.to_lowercase();
+    let mut found = Vec::new();
+    let mut stack: Vec<(String, SubSetting)> = SubSetting::root(device)
+        .await?
+        .into_iter()
+        .rev()
+        .map(|setting| (setting.name(), setting))
+        .collect();
+
+    while let Some((path, setting)) = stack.pop() {
+        if setting.object_type == SettingType::Menu {
+            stack.extend(
+                setting
+                    .expand()
+                    .await?
+                    .into_iter()
+                    .rev()
+                    .map(|child| (format!("{} > {}", path, child.name()), child)),
+            );
+        }
+        if setting.name().to_lowercase().contains(&query) {
+            found.push((path, setting));
+        }
+    }
+    Ok(found)
+}
+
+/// Walk the tree depth first and get the first setting with the given `CNAME`
+pub async fn by_cname(device: Device, cname: &str) -> Result<SubSetting> {
+    let mut stack = SubSetting::root(device).await?;
+    stack.reverse();
+
+    while let Some(setting) = stack.pop() {
+        if setting.cname() == cname {
+            return Ok(setting);
+        }
+        if setting.object_type == SettingType::Menu {
+            stack.extend(setting.expand().await?.into_iter().rev());
+        }
+    }
+    Err(Error::setting_path_not_found(cname.into()))
+}
+
+/// Write every staged setting. Menus are written in the order they first appear, with lists
+/// written before the rest of their menu since a list such as the picture mode can change the
+/// other settings in it.
+pub async fn flush(settings: &mut [SubSetting]) -> Vec<Result<()>> {
+    let mut results: Vec<Result<()>> = settings.iter().map(|_| Ok(())).collect();
+
+    let mut menus: Vec<&str> = Vec::new();
+    for setting in settings.iter() {
+        if !menus.contains(&setting.menu()) {
+            menus.push(setting.menu());
+        }
+    }
+    let mut order: Vec<(usize, bool, usize)> = settings
+        .iter()
+        .enumerate()
+        .filter(|(_, setting)| setting.is_dirty())
+        .map(|(i, setting)| {
+            let menu = menus.iter().position(|menu| *menu == setting.menu());
+            let is_list = matches!(setting.object_type, SettingType::List | SettingType::XList);
+            (menu.unwrap_or_default(), !is_list, i)
+        })
+        .collect();
+    order.sort_unstable();
+
+    let mut written_menus = HashSet::new();
+    for (_, _, i) in order {
+        let setting = &mut settings[i];
+        // A write changes the hashvals of everything else in the menu
+        if !written_menus.insert(setting.menu().to_string()) {
+            if let Err(e) = setting.refresh().await {
+                results[i] = Err(e);
+                continue;
+            }
+        }
+        results[i] = setting.write_pending().await;
+    }
+    results
+}
+
+/// Get every writable setting in the first menu of `paths` which exists on the device, including
+/// those in its sub-menus, along with their bounds. There is one request per menu and slider.
+pub async fn editable_in_menu(device: Device, paths: &[&str]) -> Result<Vec<EditableSetting>> {
+    let mut menu = None;
+    for path in paths {
+        match SubSetting::menu_at(device.clone(), path)
+            .await
+            .children()
+            .await
+        {
+            Err(Error::Api(ApiError::UriNotFound)) => continue,
+            children => {
+                menu = Some(children?);
+                break;
+            }
+        }
+    }
+    let mut stack = match menu {
+        Some(children) => children,
+        None => {
+            log::warn!("Menu not found at any of {:?}", paths);
+            return Err(Error::setting_path_not_found(paths.join(", ")));
+        }
+    };
+    stack.reverse();
+
+    let mut editable = Vec::new();
+    while let Some(setting) = stack.pop() {
+        if setting.object_type == SettingType::Menu {
+            stack.extend(setting.children().await?.into_iter().rev());
+        } else if setting.is_writable() {
+            editable.push(EditableSetting::resolve(setting).await?);
+        }
+    }
+    Ok(editable)
+}
+
+/// Get the setting at the first of `paths` which exists on the device. Paths are relative to
+/// the settings root since the same setting can live in different places depending on firmware.
+pub async fn at_path(device: Device, paths: &[&str]) -> Result<SubSetting> {
+    for path in paths {
+        match SubSetting::at_path(device.clone(), path).await {
+            Err(Error::Api(ApiError::UriNotFound)) => continue,
+            other => return other,
+        }
+    }
+    log::warn!("Setting not found at any of {:?}", paths);
+    Err(Error::setting_path_not_found(paths.join(", ")))
+}
+
+/// Device flags such as `HIDDEN` and `READONLY` come as strings whose spelling depends on the
+/// firmware -- e.g. "TRUE", "false", "YES", "Off" -- and are occasionally real json booleans.
 fn string_to_bool<'de, D>(deserializer: D) -> StdResult<bool, D::Error>
 where
     D: de::Deserializer<'de>,
 {
-    let string = String::deserialize(deserializer)?;
-    string
-        .to_lowercase()
-        .parse::<bool>()
-        .map_err(|_| de::Error::invalid_type(de::Unexpected::Str(&string), &"a boolean"))
+    match Value::deserialize(deserializer)? {
+        Value::Bool(b) => Ok(b),
+        Value::String(string) => match string.trim().to_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => Ok(true),
+            "false" | "no" | "off" | "0" => Ok(false),
+            _ => Err(de::Error::invalid_value(
+                de::Unexpected::Str(&string),
+                &"a boolean",
+            )),
+        },
+        other => Err(de::Error::invalid_type(
+            de::Unexpected::Other(&other.to_string()),
+            &"a boolean",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SettingType, SliderInfo, SubSetting, Write};
+    use crate::{ClientError, Error};
+
+    use serde_json::json;
+
+    fn with_flags(hidden: &str, readonly: &str) -> SubSetting {
+        serde_json::from_value(json!({
+            "CNAME": "setting",
+            "HASHVAL": 1,
+            "HIDDEN": hidden,
+            "NAME": "Setting",
+            "READONLY": readonly,
+            "TYPE": "T_VALUE_V1",
+            "VALUE": 0
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn flags_uppercase() {
+        let setting = with_flags("TRUE", "FALSE");
+        assert!(setting.hidden());
+        assert!(!setting.read_only());
+    }
+
+    #[test]
+    fn flags_on_off() {
+        let setting = with_flags("Off", "On");
+        assert!(!setting.hidden());
+        assert!(setting.read_only());
+    }
+
+    #[test]
+    fn flags_yes_no() {
+        let setting = with_flags("YES", "no");
+        assert!(setting.hidden());
+        assert!(!setting.read_only());
+    }
+
+    #[test]
+    fn flags_json_bool() {
+        let setting: SubSetting = serde_json::from_value(json!({
+            "CNAME": "setting",
+            "HIDDEN": true,
+            "NAME": "Setting",
+            "READONLY": false,
+            "TYPE": "T_VALUE_V1"
+        }))
+        .unwrap();
+        assert!(setting.hidden());
+        assert!(!setting.read_only());
+    }
+
+    #[test]
+    fn flags_bad_value() {
+        let setting = serde_json::from_value::<SubSetting>(json!({
+            "CNAME": "setting",
+            "HIDDEN": "maybe",
+            "NAME": "Setting",
+            "TYPE": "T_VALUE_V1"
+        }));
+        assert!(setting.is_err());
+    }
+
+    #[test]
+    fn value_sync_type() {
+        let setting: SubSetting = serde_json::from_value(json!({
+            "CNAME": "setting",
+            "HASHVAL": 1,
+            "NAME": "Setting",
+            "TYPE": "T_VALUE_SYNC_V1",
+            "VALUE": 0
+        }))
+        .unwrap();
+        assert_eq!(setting.setting_type(), SettingType::ValueSync);
+        assert!(setting.is_writable());
+    }
+
+    #[tokio::test]
+    async fn value_sync_write_i32() {
+        let setting: SubSetting = serde_json::from_value(json!({
+            "CNAME": "setting",
+            "HASHVAL": 1,
+            "NAME": "Setting",
+            "TYPE": "T_VALUE_SYNC_V1",
+            "VALUE": 0
+        }))
+        .unwrap();
+        assert_eq!(Write::<i32>::check(&setting, 5).await.unwrap(), json!(5));
+        assert!(setting.validate(5).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn validate_mismatched_type() {
+        // Values which pass for the current value's type, but not for the setting type
+        let list: SubSetting = serde_json::from_value(json!({
+            "CNAME": "setting",
+            "HASHVAL": 1,
+            "NAME": "Setting",
+            "TYPE": "T_LIST_V1",
+            "VALUE": 1
+        }))
+        .unwrap();
+        assert!(matches!(
+            list.validate(5).await,
+            Err(Error::Client(ClientError::WriteSettingsBadType(_, _)))
+        ));
+
+        let slider: SubSetting = serde_json::from_value(json!({
+            "CNAME": "setting",
+            "HASHVAL": 1,
+            "NAME": "Setting",
+            "TYPE": "T_VALUE_ABS_V1",
+            "VALUE": true
+        }))
+        .unwrap();
+        assert!(matches!(
+            slider.validate(false).await,
+            Err(Error::Client(ClientError::WriteSettingsBadType(_, _)))
+        ));
+    }
+
+    #[test]
+    fn slider_snap() {
+        let slider = |min, max, increment| SliderInfo {
+            dec_marker: "".into(),
+            inc_marker: "".into(),
+            increment,
+            max,
+            min,
+            center: None,
+        };
+        assert_eq!(slider(0, 100, 1).snap(7), 7);
+        assert_eq!(slider(0, 100, 5).snap(7), 5);
+        assert_eq!(slider(0, 100, 5).snap(8), 10);
+        assert_eq!(slider(-50, 50, 2).snap(-3), -2);
+        // Stays within bounds when max is not on the increment
+        assert_eq!(slider(0, 99, 5).snap(98), 95);
+        assert_eq!(slider(3, 33, 5).snap(4), 3);
+    }
 }
@@ -0,0 +1,113 @@
+use super::{Device, Result, SettingType, SubSetting};
+use crate::error::{ApiError, Error};
+
+use serde_json::Value;
+use tokio::time::sleep;
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Duration;
+
+/// `CNAME` and hashval of each setting in a menu
+type MenuHashes = Vec<(String, Option<u32>)>;
+
+/// Polls a device's settings for changes, started with [`watch_settings()`](Device::watch_settings)
+#[derive(Debug)]
+pub struct SettingsWatch {
+    interval: Duration,
+    /// Every menu in the tree, with the `CNAME` and hashval of its settings from the last read
+    menus: Vec<(SubSetting, MenuHashes)>,
+    /// Value of every setting from the last read, by endpoint
+    snapshot: HashMap<String, Option<Value>>,
+    changed: VecDeque<SubSetting>,
+}
+
+impl SettingsWatch {
+    pub(super) async fn start(device: Device, interval: Duration) -> Result<Self> {
+        let mut watch = Self {
+            interval,
+            menus: Vec::new(),
+            snapshot: HashMap::new(),
+            changed: VecDeque::new(),
+        };
+        let root = SubSetting::root_menu(device).await;
+        watch.read_menus(vec![root].into()).await?;
+        watch.changed.clear();
+        Ok(watch)
+    }
+
+    /// Wait for the next setting to change
+    ///
+    /// If several settings changed between polls, they are returned one at a time, menu by menu.
+    /// Settings which are added to the tree, such as when a menu appears, are also returned.
+    pub async fn next_change(&mut self) -> Result<SubSetting> {
+        loop {
+            if let Some(mut setting) = self.changed.pop_front() {
+                setting.resolve_slider().await?;
+                return Ok(setting);
+            }
+            sleep(self.interval).await;
+            self.poll().await?;
+        }
+    }
+
+    /// Read the hashvals of every menu and queue anything which is different from the last read
+    async fn poll(&mut self) -> Result<()> {
+        log::trace!("Poll settings");
+        let mut stale = VecDeque::new();
+        for (menu, hashes) in std::mem::take(&mut self.menus) {
+            // Settings written with their menu's hash have no hashval to compare, so their menu
+            // is always read
+            if hashes.iter().all(|(_, hashval)| hashval.is_some()) {
+                match menu.hashes().await {
+                    Ok(current) if current == hashes => {
+                        self.menus.push((menu, hashes));
+                        continue;
+                    }
+                    Err(Error::Api(ApiError::UriNotFound)) => {
+                        log::debug!("Menu removed: '{}'", menu.endpoint());
+                        continue;
+                    }
+                    Err(e) => return Err(e),
+                    Ok(_) => {}
+                }
+            }
+            stale.push_back(menu);
+        }
+        self.read_menus(stale).await
+    }
+
+    /// Read the settings in each menu, queue anything which is different from the last read and
+    /// read any menus which were not in the tree before
+    async fn read_menus(&mut self, mut queue: VecDeque<SubSetting>) -> Result<()> {
+        let mut known: HashSet<String> = self
+            .menus
+            .iter()
+            .map(|(menu, _)| menu.endpoint())
+            .chain(queue.iter().map(SubSetting::endpoint))
+            .collect();
+
+        while let Some(menu) = queue.pop_front() {
+            let mut hashes = Vec::new();
+            for setting in menu.children().await? {
+                hashes.push((setting.cname(), setting.hashval()));
+                let endpoint = setting.endpoint();
+                if setting.setting_type() == SettingType::Menu {
+                    if known.insert(endpoint.clone()) {
+                        queue.push_back(setting.clone());
+                    } else {
+                        continue;
+                    }
+                }
+
+                let value = setting.value::<Value>();
+                if self.snapshot.get(&endpoint) != Some(&value) {
+                    log::debug!("Setting changed: '{}'", endpoint);
+                    self.changed.push_back(setting);
+                }
+                self.snapshot.insert(endpoint, value);
+            }
+            self.menus.push((menu, hashes));
+        }
+        Ok(())
+    }
+}
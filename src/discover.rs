@@ -1,120 +1,541 @@
-use super::{Device, Result};
+use super::device::PortProbe;
+use super::{ClientError, Device, Result};
 
 use regex::Regex;
+use reqwest::{Client, Url};
 use serde_json::Value;
 use tokio::{
     net::UdpSocket,
     time::{timeout, Duration},
 };
 
-use std::net::SocketAddr;
-use std::str;
+use std::net::{IpAddr, SocketAddr};
 
 pub const SSDP_IP: &str = "239.255.255.250:1900";
 pub const SSDP_URN: &str = "urn:dial-multiscreen-org:device:dial:1";
 pub const DEFAULT_SSDP_MAXTIME: usize = 3;
 
-pub(super) async fn uaudp_followup(location: &str) -> Result<Option<Device>> {
+/// Get the device description xml
+async fn description(location: &str, client: &Client) -> Result<String> {
+    Ok(client
+        .get(location)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?)
+}
+
+/// Get the device description xml along with the DIAL `Application-URL` header, if any
+async fn description_with_app_url(
+    location: &str,
+    client: &Client,
+) -> Result<(String, Option<String>)> {
+    let res = client.get(location).send().await?.error_for_status()?;
+    let app_url = res
+        .headers()
+        .get("Application-URL")
+        .and_then(|url| url.to_str().ok())
+        .map(String::from);
+    Ok((res.text().await?, app_url))
+}
+
+pub(super) async fn uaudp_followup(
+    location: &str,
+    client: &Client,
+    probe: PortProbe,
+) -> Result<Option<Device>> {
     log::trace!("Device description followup");
     // Get device description xml
-    let res = reqwest::get(location).await?.text().await?;
-
-    // Parse xml for device info
-    let mut items: Value = serde_xml_rs::from_str(&res).unwrap();
-
-    let friendly_name =
-        serde_json::from_value::<String>(items["device"]["friendlyName"]["$value"].take());
-    let manufacturer =
-        serde_json::from_value::<String>(items["device"]["manufacturer"]["$value"].take());
-    let model_name =
-        serde_json::from_value::<String>(items["device"]["modelName"]["$value"].take());
-    let uuid = serde_json::from_value::<String>(items["device"]["UDN"]["$value"].take());
-
-    match (friendly_name, manufacturer, model_name, uuid) {
-        (Ok(friendly_name), Ok(manufacturer), Ok(model_name), Ok(uuid))
-            if manufacturer == "Vizio" =>
-        {
-            // Strip http and port
-            let ip_addr = Regex::new(r"(?:http:////)?(\d+\.\d+\.\d+\.\d+)(?::\d+)?")
-                .unwrap()
-                .captures(location)
-                .unwrap()[1]
-                .into();
-            // Strip uuid
-            let uuid = Regex::new(r"^(?:(?:\s*\w+)\s*:\s*)?(.*)")
-                .unwrap()
-                .captures(&uuid)
-                .unwrap()[1]
-                .into();
-
-            Ok(Some(
-                Device::new(friendly_name, manufacturer, model_name, ip_addr, uuid).await?,
-            ))
+    let (res, app_url) = description_with_app_url(location, client).await?;
+
+    match Candidate::from_description(location, &res, client) {
+        Some(candidate) => {
+            let device = Device::with_client(
+                candidate.name,
+                "Vizio".into(),
+                candidate.model,
+                candidate.ip,
+                candidate.uuid,
+                client.clone(),
+                probe,
+            )
+            .await?;
+            device.set_application_url(app_url).await;
+            Ok(Some(device))
         }
-        _ => {
+        None => {
             log::warn!("Device is not compatible");
             Ok(None)
         }
     }
 }
 
-// Returns a vector of Vizio Devices
-pub(super) async fn ssdp(host: &str, st: &str, mx: usize) -> Result<Vec<Device>> {
-    log::info!("Starting SSDP query");
-    let body: &str = &[
-        "M-SEARCH * HTTP/1.1",
-        &format!("HOST: {}", host),
-        "MAN: \"ssdp:discover\"",
-        &format!("ST: {}", st),
-        &format!("MX: {}", mx),
-        "",
-        "",
-    ]
-    .join("\r\n");
-
-    // Open UDP Socket
-    let socket = UdpSocket::bind(SocketAddr::from(([0, 0, 0, 0], 0))).await?;
-
-    // Send ssdp request
-    socket.send_to(body.as_bytes(), host).await?;
-    let mut rbuf = [0; 1024];
-
-    // Get responses from devices
-    log::trace!("Wait for SSDP replies");
-    let mut devices: Vec<Device> = Vec::new();
-    while let Ok(Ok(len)) = timeout(Duration::from_secs(mx as u64), socket.recv(&mut rbuf)).await {
-        // Parse headers for xml url
-        let mut headers = [httparse::EMPTY_HEADER; 16];
-        let mut res = httparse::Response::new(&mut headers);
+/// A SmartCast device which replied to an SSDP search, found by
+/// [`discover_candidates()`](crate::discover_candidates())
+///
+/// Only the device description is read, so no connection is made to the device's API. Use
+/// [`connect()`](Self::connect) to get a [`Device`] for the one you want.
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    /// Device's 'friendly' name
+    pub name: String,
+    /// Device's model name
+    pub model: String,
+    /// Device's UUID
+    pub uuid: String,
+    /// Device's IP address
+    pub ip: String,
+    /// Url of the device description, from the `LOCATION` header
+    pub location: String,
+    /// Client the candidate was discovered with, used again to connect
+    client: Client,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.model == other.model
+            && self.uuid == other.uuid
+            && self.ip == other.ip
+            && self.location == other.location
+    }
+}
+
+impl Eq for Candidate {}
+
+impl Candidate {
+    /// Read a device description xml, returning `None` if it is not of a SmartCast device
+    fn from_description(location: &str, xml: &str, client: &Client) -> Option<Self> {
+        // Parse xml for device info
+        let mut items: Value = match serde_xml_rs::from_str(xml) {
+            Ok(items) => items,
+            Err(e) => {
+                log::warn!(
+                    "Could not parse device description at '{}': {}",
+                    location,
+                    e
+                );
+                return None;
+            }
+        };
+
+        let friendly_name =
+            serde_json::from_value::<String>(items["device"]["friendlyName"]["$value"].take());
+        let manufacturer =
+            serde_json::from_value::<String>(items["device"]["manufacturer"]["$value"].take());
+        let model_name =
+            serde_json::from_value::<String>(items["device"]["modelName"]["$value"].take());
+        let uuid = serde_json::from_value::<String>(items["device"]["UDN"]["$value"].take());
+
+        match (friendly_name, manufacturer, model_name, uuid) {
+            (Ok(friendly_name), Ok(manufacturer), Ok(model_name), Ok(uuid))
+                if manufacturer == "Vizio" =>
+            {
+                // Strip scheme, port, and path. The location was just fetched so it has a host.
+                let ip_addr = Url::parse(location)
+                    .ok()
+                    .and_then(|url| url.host_str().map(String::from))?;
+                // Strip uuid
+                let uuid = Regex::new(r"^(?:(?:\s*\w+)\s*:\s*)?(.*)")
+                    .unwrap()
+                    .captures(&uuid)
+                    .unwrap()[1]
+                    .into();
+
+                Some(Self {
+                    name: friendly_name,
+                    model: model_name,
+                    uuid,
+                    ip: ip_addr,
+                    location: location.into(),
+                    client: client.clone(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Connect to the device
+    ///
+    /// Same as [`Device::from_url()`] with the candidate's [`location`](Self::location), except
+    /// the client given to [`Discovery::with_client()`] is used, if any.
+    pub async fn connect(&self) -> Result<Device> {
+        log::info!("Attempt API connection to candidate at '{}'", self.location);
+        match uaudp_followup(&self.location, &self.client, PortProbe::default()).await? {
+            Some(device) => Ok(device),
+            None => {
+                log::error!("Device not found at '{}'", self.location);
+                Err(ClientError::DeviceNotFoundUrl(self.location.clone()).into())
+            }
+        }
+    }
+}
+
+/// Address family to prefer when a device advertises its description at both an IPv4 and an
+/// IPv6 address, see [`Discovery::prefer_family()`]
+///
+/// Defaults to [`Ipv4`](Self::Ipv4), which every SmartCast firmware serves its API on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AddressFamily {
+    /// Prefer IPv4 addresses
+    #[default]
+    Ipv4,
+    /// Prefer IPv6 addresses
+    Ipv6,
+}
 
-        res.parse(&rbuf).unwrap();
+impl AddressFamily {
+    /// Whether the host of `location` is an address of this family
+    fn matches(&self, location: &str) -> bool {
+        let ip = Url::parse(location).ok().and_then(|url| {
+            url.host_str()?
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .parse::<IpAddr>()
+                .ok()
+        });
+        matches!(
+            (self, ip),
+            (Self::Ipv4, Some(IpAddr::V4(_))) | (Self::Ipv6, Some(IpAddr::V6(_)))
+        )
+    }
+}
 
-        let location = str::from_utf8(
-            match headers.iter().find(|x| x.name.to_lowercase() == "location") {
-                Some(header) => header.value,
+/// A reply to an SSDP search, from any kind of device. See [`discover_raw()`](crate::discover_raw()).
+#[derive(Debug)]
+pub struct SsdpResponse {
+    /// Address the reply was sent from
+    pub addr: SocketAddr,
+    /// Headers of the reply in the order they were received
+    pub headers: Vec<(String, String)>,
+    /// Url of the device description, from the `LOCATION` or `AL` header, see
+    /// [`Discovery::prefer_family()`]
+    pub location: Option<String>,
+    /// Device description xml fetched from [`location`](Self::location), or `None` if the reply
+    /// had no location
+    pub description: Option<Result<String>>,
+}
+
+/// An in-progress SSDP scan for SmartCast devices, started with [`discover()`](crate::discover())
+///
+/// Devices are returned one at a time as they respond. The scan can be stopped early with
+/// [`abort()`](Self::abort) or by dropping the `Discovery`, which closes the socket.
+#[derive(Debug)]
+pub struct Discovery {
+    socket: Option<UdpSocket>,
+    mx: usize,
+    rbuf: Vec<u8>,
+    client: Client,
+    family: AddressFamily,
+}
+
+impl Discovery {
+    pub(super) async fn start(host: &str, st: &str, mx: usize) -> Result<Self> {
+        let socket = UdpSocket::bind(SocketAddr::from(([0, 0, 0, 0], 0))).await?;
+        Self::start_with_socket(socket, host, st, mx).await
+    }
+
+    /// Same as [`start()`](Self::start), but send the query from `socket` and receive replies on
+    /// it, rather than a new socket
+    pub(super) async fn start_with_socket(
+        socket: UdpSocket,
+        host: &str,
+        st: &str,
+        mx: usize,
+    ) -> Result<Self> {
+        log::info!("Starting SSDP query");
+        let body: &str = &[
+            "M-SEARCH * HTTP/1.1",
+            &format!("HOST: {}", host),
+            "MAN: \"ssdp:discover\"",
+            &format!("ST: {}", st),
+            &format!("MX: {}", mx),
+            "",
+            "",
+        ]
+        .join("\r\n");
+
+        // Send ssdp request
+        socket.send_to(body.as_bytes(), host).await?;
+
+        Ok(Self {
+            socket: Some(socket),
+            mx,
+            rbuf: vec![0; 1024],
+            client: Device::default_client()?,
+            family: AddressFamily::default(),
+        })
+    }
+
+    /// Use `client` to fetch device descriptions and for the API of every device found
+    ///
+    /// This lets proxy, TLS, and header settings apply to discovery as well, and connections are
+    /// reused. The client must accept the self-signed certificate SmartCast devices serve their
+    /// API with.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// let client = reqwest::Client::builder()
+    ///     .danger_accept_invalid_certs(true)
+    ///     .proxy(reqwest::Proxy::http("http://proxy.local:3128")?)
+    ///     .build()?;
+    ///
+    /// let mut discovery = smartcast::discover().await?.with_client(client);
+    /// while let Some(dev) = discovery.next_device().await {
+    ///     println!("{}", dev?.name());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Prefer addresses of `family` for devices which advertise both an IPv4 and an IPv6 address
+    ///
+    /// Devices list other addresses for their description in the `AL` header of their reply.
+    /// The first address of `family` is used, falling back to the `LOCATION` header if there is
+    /// none. The default is [`AddressFamily::Ipv4`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example() -> Result<(), smartcast::Error> {
+    /// use smartcast::AddressFamily;
+    ///
+    /// let mut discovery = smartcast::discover().await?.prefer_family(AddressFamily::Ipv6);
+    /// while let Some(dev) = discovery.next_device().await {
+    ///     println!("{}", dev?.ip());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn prefer_family(mut self, family: AddressFamily) -> Self {
+        self.family = family;
+        self
+    }
+
+    /// Wait for the next SmartCast device to respond
+    ///
+    /// Returns `None` once no device has responded within the scan window or the scan was aborted.
+    pub async fn next_device(&mut self) -> Option<Result<Device>> {
+        loop {
+            let location = match location(&self.next_reply().await?.1, self.family) {
+                Some(location) => location,
                 None => continue,
-            },
+            };
+            log::info!("Received reply for location '{}'", location);
+
+            match uaudp_followup(&location, &self.client, PortProbe::default()).await {
+                Ok(Some(device)) => return Some(Ok(device)),
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+
+    /// Wait for the next SmartCast device to respond, without connecting to it
+    ///
+    /// Returns `None` once no device has responded within the scan window or the scan was aborted.
+    pub async fn next_candidate(&mut self) -> Option<Result<Candidate>> {
+        loop {
+            let location = match location(&self.next_reply().await?.1, self.family) {
+                Some(location) => location,
+                None => continue,
+            };
+            log::info!("Received reply for location '{}'", location);
+
+            match description(&location, &self.client).await {
+                Ok(xml) => match Candidate::from_description(&location, &xml, &self.client) {
+                    Some(candidate) => return Some(Ok(candidate)),
+                    None => continue,
+                },
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+
+    /// Wait for the next reply from any device, without checking that it is a SmartCast device
+    ///
+    /// Returns `None` once nothing has responded within the scan window or the scan was aborted.
+    pub async fn next_response(&mut self) -> Option<SsdpResponse> {
+        let (addr, headers) = self.next_reply().await?;
+        let location = location(&headers, self.family);
+        log::info!("Received reply from '{}'", addr);
+
+        let description = match &location {
+            Some(location) => Some(description(location, &self.client).await),
+            None => None,
+        };
+        Some(SsdpResponse {
+            addr,
+            headers,
+            location,
+            description,
+        })
+    }
+
+    /// Receive the next reply and parse its headers
+    async fn next_reply(&mut self) -> Option<(SocketAddr, Vec<(String, String)>)> {
+        log::trace!("Wait for SSDP replies");
+        let socket = self.socket.as_ref()?;
+        let (len, addr) = match timeout(
+            Duration::from_secs(self.mx as u64),
+            socket.recv_from(&mut self.rbuf),
         )
-        .unwrap();
-        log::info!("Received reply for location '{}'", location);
+        .await
+        {
+            Ok(Ok(res)) => res,
+            _ => {
+                self.abort();
+                return None;
+            }
+        };
 
-        if let Some(device) = uaudp_followup(location).await? {
-            devices.push(device);
+        let mut headers = [httparse::EMPTY_HEADER; 32];
+        let mut res = httparse::Response::new(&mut headers);
+        if let Err(e) = res.parse(&self.rbuf[..len]) {
+            log::warn!("Malformed SSDP reply from '{}': {}", addr, e);
         }
-        // Clear rbuf
-        for b in rbuf[..len].iter_mut() {
-            *b = 0
+
+        let headers = res
+            .headers
+            .iter()
+            .take_while(|header| !header.name.is_empty())
+            .map(|header| {
+                (
+                    header.name.to_string(),
+                    String::from_utf8_lossy(header.value).into_owned(),
+                )
+            })
+            .collect();
+        Some((addr, headers))
+    }
+
+    /// Stop the scan and close the socket
+    pub fn abort(&mut self) {
+        if self.socket.take().is_some() {
+            log::trace!("SSDP scan ended");
         }
     }
+}
+
+/// Find the xml url in SSDP reply headers, preferring one at an address of `family`
+///
+/// Other urls for the xml are listed in the `AL` header as `<url><url>...`.
+fn location(headers: &[(String, String)], family: AddressFamily) -> Option<String> {
+    let location = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("location"))
+        .map(|(_, value)| value.trim().to_string());
+    let alternates = headers
+        .iter()
+        .filter(|(name, _)| name.eq_ignore_ascii_case("al"))
+        .flat_map(|(_, value)| {
+            value
+                .split(['<', '>'])
+                .map(str::trim)
+                .filter(|url| !url.is_empty())
+        });
+
+    location
+        .iter()
+        .map(String::as_str)
+        .chain(alternates)
+        .find(|url| family.matches(url))
+        .map(String::from)
+        .or(location)
+}
+
+// Returns every reply to the search
+pub(super) async fn ssdp_raw(host: &str, st: &str, mx: usize) -> Result<Vec<SsdpResponse>> {
+    let mut discovery = Discovery::start(host, st, mx).await?;
+
+    let mut responses: Vec<SsdpResponse> = Vec::new();
+    while let Some(response) = discovery.next_response().await {
+        responses.push(response);
+    }
+
+    log::info!("Received [{}] SSDP replies", responses.len());
+    Ok(responses)
+}
+
+// Returns a vector of Vizio Devices
+pub(super) async fn ssdp(host: &str, st: &str, mx: usize) -> Result<Vec<Device>> {
+    collect_devices(Discovery::start(host, st, mx).await?).await
+}
+
+// Returns a vector of Vizio Devices found by searching from `socket`
+pub(super) async fn ssdp_with_socket(
+    socket: UdpSocket,
+    host: &str,
+    st: &str,
+    mx: usize,
+) -> Result<Vec<Device>> {
+    collect_devices(Discovery::start_with_socket(socket, host, st, mx).await?).await
+}
+
+async fn collect_devices(mut discovery: Discovery) -> Result<Vec<Device>> {
+    let mut devices: Vec<Device> = Vec::new();
+    while let Some(device) = discovery.next_device().await {
+        devices.push(device?);
+    }
 
     log::info!("Found [{}] SmartCast Device(s)", devices.len());
     Ok(devices)
 }
 
+// Returns every Vizio Device which replied, without connecting to them
+pub(super) async fn ssdp_candidates(host: &str, st: &str, mx: usize) -> Result<Vec<Candidate>> {
+    let mut discovery = Discovery::start(host, st, mx).await?;
+
+    let mut candidates: Vec<Candidate> = Vec::new();
+    while let Some(candidate) = discovery.next_candidate().await {
+        candidates.push(candidate?);
+    }
+
+    log::info!("Found [{}] SmartCast Device candidate(s)", candidates.len());
+    Ok(candidates)
+}
+
+// Returns the first Vizio Device to respond within `wait`
+pub(super) async fn ssdp_one(host: &str, st: &str, wait: Duration) -> Result<Device> {
+    // Devices reply within MX seconds, so there is no need to ask for more than the wait
+    let mx = (wait.as_secs_f64().ceil() as usize).max(1);
+    let mut discovery = Discovery::start(host, st, mx).await?;
+
+    let first = timeout(wait, async {
+        while let Some(device) = discovery.next_device().await {
+            match device {
+                Ok(device) => return Some(device),
+                Err(e) => log::warn!("Skipping device which could not be read: {}", e),
+            }
+        }
+        None
+    })
+    .await;
+    discovery.abort();
+
+    match first {
+        Ok(Some(device)) => {
+            log::info!("Found SmartCast Device '{}'", device.name());
+            Ok(device)
+        }
+        _ => Err(ClientError::NoDeviceFound.into()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{ssdp, DEFAULT_SSDP_MAXTIME, SSDP_URN};
-    use crate::Device;
+    use super::{
+        location, ssdp, ssdp_candidates, ssdp_one, ssdp_raw, ssdp_with_socket, AddressFamily,
+        Candidate, Discovery, DEFAULT_SSDP_MAXTIME, SSDP_URN,
+    };
+    use crate::{ClientError, Device, Error};
 
     use chrono::prelude::*;
     use http::Response;
@@ -127,6 +548,7 @@ mod tests {
     use warp::{self, Filter};
 
     use std::net::SocketAddr;
+    use std::time::{Duration, Instant};
 
     macro_rules! device_desc {
         ($ip:expr, $port:expr, $name:expr, $manufacturer:expr, $model:expr, $uuid:expr) => {
@@ -181,9 +603,15 @@ mod tests {
     }
 
     // Emulate Device SSDP Response
-    async fn emulate_device(
+    async fn emulate_device(smartcast_device: bool, rx: Receiver<Option<SocketAddr>>) -> Device {
+        emulate_device_requiring_header(smartcast_device, rx, None).await
+    }
+
+    // Emulate a device whose description server rejects requests without the given header
+    async fn emulate_device_requiring_header(
         smartcast_device: bool,
         mut rx: Receiver<Option<SocketAddr>>,
+        required_header: Option<(&'static str, &'static str)>,
     ) -> Device {
         // Bind Socket
         let socket = UdpSocket::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
@@ -191,14 +619,6 @@ mod tests {
             .unwrap();
         let device_addr = socket.local_addr().unwrap();
 
-        // Device Desc Server
-        let desc_addr: SocketAddr = {
-            let socket = UdpSocket::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
-                .await
-                .unwrap();
-            socket.local_addr().unwrap()
-        };
-
         // Build Device
         let rand_string: String = rand::thread_rng()
             .sample_iter(&Alphanumeric)
@@ -236,6 +656,7 @@ mod tests {
             .and(warp::path("device-desc.xml"))
             .and(warp::path::end())
             .and(warp::get())
+            .and(warp::header::headers_cloned())
             .map({
                 let ip = device.ip();
                 let port = device.port();
@@ -243,10 +664,15 @@ mod tests {
                 let manufacturer = device.manufacturer();
                 let model_name = device.model_name();
                 let uuid = device.uuid();
-                move || {
+                move |headers: http::HeaderMap| {
+                    if let Some((name, value)) = required_header {
+                        if headers.get(name).and_then(|h| h.to_str().ok()) != Some(value) {
+                            return Response::builder().status(403).body(String::new()).unwrap();
+                        }
+                    }
                     let desc_xml = device_desc!(ip, port, name, manufacturer, model_name, uuid);
                     Response::builder()
-                        .header("Application-URL", "http//127.0.0.1:8008/apps/")
+                        .header("Application-URL", "http://127.0.0.1:8008/apps/")
                         .header("Content-Length", desc_xml.len())
                         .header("Content-Type", "application/xml")
                         .body(desc_xml)
@@ -254,7 +680,11 @@ mod tests {
                 }
             });
 
-        tokio::spawn(warp::serve(descriptions).run(desc_addr));
+        // Let the server pick its own port, a port found by binding another socket first may be
+        // taken again before the server binds it
+        let (desc_addr, server) =
+            warp::serve(descriptions).bind_ephemeral(SocketAddr::from(([127, 0, 0, 1], 0)));
+        tokio::spawn(server);
 
         // SSDP Response
         tokio::spawn({
@@ -344,6 +774,59 @@ mod tests {
         assert_eq!(found_devices, expected_devices);
     }
 
+    #[tokio::test]
+    async fn ssdp_candidates_only_read_description() {
+        // Start SSDP
+        let (ssdp_addr, ssdp_rx) = emulate_ssdp().await;
+
+        // Devices. Only the description server is emulated, there is no API to connect to.
+        let expected_device = emulate_device(true, ssdp_rx.clone()).await;
+        emulate_device(false, ssdp_rx).await;
+
+        let candidates = ssdp_candidates(
+            &format!("{}:{}", ssdp_addr.ip(), ssdp_addr.port()),
+            SSDP_URN,
+            DEFAULT_SSDP_MAXTIME,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(candidates.len(), 1);
+        let candidate = &candidates[0];
+        assert_eq!(candidate.name, expected_device.name());
+        assert_eq!(candidate.model, expected_device.model_name());
+        assert_eq!(candidate.uuid, expected_device.uuid());
+        assert_eq!(candidate.ip, "127.0.0.1");
+        assert!(candidate.location.ends_with("/ssdp/device-desc.xml"));
+    }
+
+    #[tokio::test]
+    async fn ssdp_injected_socket() {
+        // Start SSDP
+        let (ssdp_addr, ssdp_rx) = emulate_ssdp().await;
+
+        // Devices
+        let expected_device = emulate_device(true, ssdp_rx.clone()).await;
+
+        let socket = UdpSocket::bind(SocketAddr::from(([127, 0, 0, 1], 0)))
+            .await
+            .unwrap();
+        let socket_addr = socket.local_addr().unwrap();
+
+        let found_devices = ssdp_with_socket(
+            socket,
+            &format!("{}:{}", ssdp_addr.ip(), ssdp_addr.port()),
+            SSDP_URN,
+            DEFAULT_SSDP_MAXTIME,
+        )
+        .await
+        .unwrap();
+
+        // The query was sent from the given socket, and the reply received on it
+        assert_eq!(*ssdp_rx.borrow(), Some(socket_addr));
+        assert_eq!(found_devices, vec![expected_device]);
+    }
+
     #[tokio::test]
     async fn ssdp_no_device() {
         // Start SSDP
@@ -364,4 +847,284 @@ mod tests {
 
         assert_eq!(found_devices.len(), 0);
     }
+
+    #[tokio::test]
+    async fn ssdp_abort() {
+        // Start SSDP
+        let (ssdp_addr, ssdp_rx) = emulate_ssdp().await;
+
+        // Devices
+        for _ in 0..5 {
+            emulate_device(true, ssdp_rx.clone()).await;
+        }
+
+        let start = Instant::now();
+        let mut discovery = Discovery::start(
+            &format!("{}:{}", ssdp_addr.ip(), ssdp_addr.port()),
+            SSDP_URN,
+            DEFAULT_SSDP_MAXTIME,
+        )
+        .await
+        .unwrap();
+
+        assert!(discovery.next_device().await.unwrap().is_ok());
+        discovery.abort();
+        assert!(discovery.next_device().await.is_none());
+        assert!(Instant::now() - start < Duration::from_secs(DEFAULT_SSDP_MAXTIME as u64));
+    }
+
+    #[tokio::test]
+    async fn ssdp_first_device() {
+        // Start SSDP
+        let (ssdp_addr, ssdp_rx) = emulate_ssdp().await;
+
+        // Devices
+        emulate_device(false, ssdp_rx.clone()).await;
+        let expected_device = emulate_device(true, ssdp_rx).await;
+
+        let wait = Duration::from_secs(DEFAULT_SSDP_MAXTIME as u64);
+        let start = Instant::now();
+        let found_device = ssdp_one(
+            &format!("{}:{}", ssdp_addr.ip(), ssdp_addr.port()),
+            SSDP_URN,
+            wait,
+        )
+        .await
+        .unwrap();
+
+        // Returned without waiting out the scan window
+        assert!(Instant::now() - start < wait);
+        assert_eq!(found_device, expected_device);
+    }
+
+    #[tokio::test]
+    async fn ssdp_first_device_none_found() {
+        // Start SSDP
+        let (ssdp_addr, ssdp_rx) = emulate_ssdp().await;
+
+        // Devices
+        emulate_device(false, ssdp_rx).await;
+
+        let res = ssdp_one(
+            &format!("{}:{}", ssdp_addr.ip(), ssdp_addr.port()),
+            SSDP_URN,
+            Duration::from_millis(500),
+        )
+        .await;
+        assert!(matches!(
+            res,
+            Err(Error::Client(ClientError::NoDeviceFound))
+        ));
+    }
+
+    #[tokio::test]
+    async fn ssdp_raw_includes_other_devices() {
+        // Start SSDP
+        let (ssdp_addr, ssdp_rx) = emulate_ssdp().await;
+
+        // Devices
+        let smartcast = emulate_device(true, ssdp_rx.clone()).await;
+        let other = emulate_device(false, ssdp_rx).await;
+
+        let responses = ssdp_raw(
+            &format!("{}:{}", ssdp_addr.ip(), ssdp_addr.port()),
+            SSDP_URN,
+            DEFAULT_SSDP_MAXTIME,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(responses.len(), 2);
+        for device in [smartcast, other] {
+            let response = responses
+                .iter()
+                .find(|res| {
+                    res.headers
+                        .iter()
+                        .any(|(name, value)| name == "USN" && value.contains(&device.uuid()))
+                })
+                .unwrap();
+            assert!(response.addr.ip().is_loopback());
+            assert!(response.location.is_some());
+
+            let description = response.description.as_ref().unwrap().as_ref().unwrap();
+            assert!(description.contains(&format!(
+                "<manufacturer>{}</manufacturer>",
+                device.manufacturer()
+            )));
+        }
+    }
+
+    #[tokio::test]
+    async fn connect_best_stale_ip() {
+        // Start SSDP
+        let (ssdp_addr, ssdp_rx) = emulate_ssdp().await;
+
+        // Devices
+        let expected_device = emulate_device(true, ssdp_rx).await;
+
+        // Nothing is listening at the stale address
+        let device = Device::connect_best_via(
+            &format!("{}:{}", ssdp_addr.ip(), ssdp_addr.port()),
+            "127.0.0.2",
+            &expected_device.uuid(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(device, expected_device);
+        assert_eq!(device.ip(), expected_device.ip());
+    }
+
+    #[tokio::test]
+    async fn connect_best_ip_reused() {
+        // Start SSDP
+        let (ssdp_addr, ssdp_rx) = emulate_ssdp().await;
+
+        // Devices
+        let expected_device = emulate_device(true, ssdp_rx).await;
+
+        // Another device was given the old IP
+        let other = warp::path!("ssdp" / "device-desc.xml").map(|| {
+            device_desc!(
+                "127.0.0.3",
+                8008,
+                "Other TV",
+                "Vizio",
+                "other_model",
+                "00000000-0000-0000-0000-000000000000"
+            )
+        });
+        tokio::spawn(warp::serve(other).bind(SocketAddr::from(([127, 0, 0, 3], 8008))));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let device = Device::connect_best_via(
+            &format!("{}:{}", ssdp_addr.ip(), ssdp_addr.port()),
+            "127.0.0.3",
+            &expected_device.uuid(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(device.uuid(), expected_device.uuid());
+        assert_eq!(device.ip(), expected_device.ip());
+    }
+
+    #[tokio::test]
+    async fn reconnect_other_device() {
+        // Start SSDP
+        let (ssdp_addr, ssdp_rx) = emulate_ssdp().await;
+
+        // Only a different device answers the search by UUID
+        let other_device = emulate_device(true, ssdp_rx).await;
+        let device = Device::new(
+            "Gone TV",
+            "Vizio",
+            "gone_model",
+            "127.0.0.2",
+            "00000000-0000-0000-0000-000000000000",
+        )
+        .await
+        .unwrap();
+
+        let res = device
+            .reconnect_via(&format!("{}:{}", ssdp_addr.ip(), ssdp_addr.port()))
+            .await;
+        assert_ne!(other_device.uuid(), device.uuid());
+        assert!(matches!(
+            res,
+            Err(Error::Client(ClientError::DeviceNotFoundUUID(uuid))) if uuid == device.uuid()
+        ));
+    }
+
+    #[tokio::test]
+    async fn discovery_with_client() {
+        // Start SSDP
+        let (ssdp_addr, ssdp_rx) = emulate_ssdp().await;
+        let ssdp_host = format!("{}:{}", ssdp_addr.ip(), ssdp_addr.port());
+
+        // Device behind something which needs a header, like a proxy
+        let expected_device =
+            emulate_device_requiring_header(true, ssdp_rx, Some(("x-proxy-token", "letmein")))
+                .await;
+
+        // Rejected with the default client
+        let mut discovery = Discovery::start(&ssdp_host, SSDP_URN, DEFAULT_SSDP_MAXTIME)
+            .await
+            .unwrap();
+        assert!(discovery.next_device().await.unwrap().is_err());
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert("x-proxy-token", "letmein".parse().unwrap());
+        let client = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .unwrap();
+
+        let mut discovery = Discovery::start(&ssdp_host, SSDP_URN, DEFAULT_SSDP_MAXTIME)
+            .await
+            .unwrap()
+            .with_client(client);
+        let device = discovery.next_device().await.unwrap().unwrap();
+        assert_eq!(device, expected_device);
+    }
+
+    #[tokio::test]
+    async fn candidate_connect_with_client() {
+        // Start SSDP
+        let (ssdp_addr, ssdp_rx) = emulate_ssdp().await;
+        let ssdp_host = format!("{}:{}", ssdp_addr.ip(), ssdp_addr.port());
+
+        let expected_device =
+            emulate_device_requiring_header(true, ssdp_rx, Some(("x-proxy-token", "letmein")))
+                .await;
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert("x-proxy-token", "letmein".parse().unwrap());
+        let client = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .unwrap();
+
+        let mut discovery = Discovery::start(&ssdp_host, SSDP_URN, DEFAULT_SSDP_MAXTIME)
+            .await
+            .unwrap()
+            .with_client(client);
+        let candidate = discovery.next_candidate().await.unwrap().unwrap();
+        discovery.abort();
+
+        // The description is read again with the discovery's client
+        let device = candidate.connect().await.unwrap();
+        assert_eq!(device, expected_device);
+    }
+
+    #[test]
+    fn candidate_invalid_description() {
+        let client = reqwest::Client::new();
+        let location = "http://127.0.0.1:8008/ssdp/device-desc.xml";
+        assert!(Candidate::from_description(location, "not xml <", &client).is_none());
+        assert!(Candidate::from_description(location, "", &client).is_none());
+    }
+
+    #[test]
+    fn location_prefers_family() {
+        let v4 = "http://192.168.0.14:8008/ssdp/device-desc.xml";
+        let v6 = "http://[fe80::1]:8008/ssdp/device-desc.xml";
+        let headers = |al: &str| {
+            vec![
+                ("LOCATION".to_string(), v4.to_string()),
+                ("AL".to_string(), al.to_string()),
+            ]
+        };
+
+        // Advertised at both
+        let both = headers(&format!("<{}><{}>", v6, v4));
+        assert_eq!(location(&both, AddressFamily::default()).unwrap(), v4);
+        assert_eq!(location(&both, AddressFamily::Ipv6).unwrap(), v6);
+
+        // The location is used when there is nothing of the preferred family
+        let v4_only = headers("");
+        assert_eq!(location(&v4_only, AddressFamily::Ipv6).unwrap(), v4);
+        assert!(location(&[], AddressFamily::Ipv4).is_none());
+    }
 }
@@ -48,6 +48,21 @@ impl Error {
         matches!(self, Error::IO(_))
     }
 
+    /// Returns true if a request to the device timed out
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, Error::Reqwest(e) if e.is_timeout())
+    }
+
+    /// Returns true if the device could not be connected to
+    pub fn is_connect(&self) -> bool {
+        matches!(self, Error::Reqwest(e) if e.is_connect())
+    }
+
+    /// Returns true if a response body could not be decoded
+    pub fn is_decode(&self) -> bool {
+        matches!(self, Error::Reqwest(e) if e.is_decode())
+    }
+
     pub(super) fn device_not_found_ip(ip_addr: String) -> Error {
         ClientError::DeviceNotFoundIP(ip_addr).into()
     }
@@ -64,9 +79,21 @@ impl Error {
         ClientError::WriteSettingsOutsideBounds(min, max, new_value).into()
     }
 
+    pub(super) fn slider_no_bounds(name: &str) -> Error {
+        ClientError::SliderNoBounds(name.into()).into()
+    }
+
+    pub(super) fn setting_off_increment(increment: i32, new_value: i32) -> Error {
+        ClientError::WriteSettingsOffIncrement(increment, new_value).into()
+    }
+
     pub(super) fn setting_non_element() -> Error {
         ClientError::WriteSettingsNotAnElement.into()
     }
+
+    pub(super) fn setting_path_not_found(path: String) -> Error {
+        ClientError::SettingPathNotFound(path).into()
+    }
 }
 
 impl From<ApiError> for Error {
@@ -126,15 +153,15 @@ pub enum ApiError {
     /// URI not found - probably means this api needs to be modified to work with your firmware
     UriNotFound,
     /// Pairing: Too many failed pair attempts
-    MaxChallengesExceeded,
+    MaxChallengesExceeded(Option<String>),
     /// Pairing: Incorrect pin
-    PairingDenied,
+    PairingDenied(Option<String>),
     /// Pairing: Pin out of range
-    ValueOutOfRange,
+    ValueOutOfRange(Option<String>),
     /// Pairing: Incorrect challenge
-    ChallengeIncorrect,
+    ChallengeIncorrect(Option<String>),
     /// Pairing: is already in progress
-    Blocked,
+    Blocked(Option<String>),
     /// Unknown command failure
     Failure,
     /// Unknown abort
@@ -178,11 +205,11 @@ impl Display for ApiError {
         match self {
             Self::InvalidParameter => write!(f, "Invalid Parameter"),
             Self::UriNotFound => write!(f, "URI not found"),
-            Self::MaxChallengesExceeded => write!(f, "Too many failed pair attempts"),
-            Self::PairingDenied => write!(f, "Incorrect pin"),
-            Self::ValueOutOfRange => write!(f, "Pin out of range"),
-            Self::ChallengeIncorrect => write!(f, "Incorrect challenge"),
-            Self::Blocked => write!(f, "Command was blocked"),
+            Self::MaxChallengesExceeded(_) => write!(f, "Too many failed pair attempts"),
+            Self::PairingDenied(_) => write!(f, "Incorrect pin"),
+            Self::ValueOutOfRange(_) => write!(f, "Pin out of range"),
+            Self::ChallengeIncorrect(_) => write!(f, "Incorrect challenge"),
+            Self::Blocked(_) => write!(f, "Command was blocked"),
             Self::Failure => write!(f, "Unknown command failure"),
             Self::Aborted => write!(f, "Unknown abort"),
             Self::Busy => write!(f, "Device is busy"),
@@ -201,6 +228,28 @@ impl Display for ApiError {
             Self::NetIPDHCPFailed => write!(f, "DHCP failure"),
             Self::NetUnknown => write!(f, "Unknown network Error"),
             Self::Unknown(e) => write!(f, "Unknown error: '{}'", e),
+        }?;
+
+        match self.detail() {
+            Some(detail) => write!(f, " ({})", detail),
+            None => Ok(()),
+        }
+    }
+}
+
+impl ApiError {
+    /// The `DETAIL` the device gave for a pairing error, if any
+    ///
+    /// This is often more specific than the error itself, e.g. a [`Blocked`](Self::Blocked)
+    /// error may say that another client is already pairing.
+    pub fn detail(&self) -> Option<&str> {
+        match self {
+            Self::MaxChallengesExceeded(detail)
+            | Self::PairingDenied(detail)
+            | Self::ValueOutOfRange(detail)
+            | Self::ChallengeIncorrect(detail)
+            | Self::Blocked(detail) => detail.as_deref(),
+            _ => None,
         }
     }
 }
@@ -218,6 +267,8 @@ pub enum ClientError {
     DeviceNotFoundIP(String),
     /// Could not find device by UUID
     DeviceNotFoundUUID(String),
+    /// The description at the url is not of a SmartCast device
+    DeviceNotFoundUrl(String),
     /// New settings value type does not match current
     WriteSettingsBadType(Value, Value),
     /// New settings value is outside the bounds of the slider
@@ -226,6 +277,55 @@ pub enum ClientError {
     WriteSettingsReadOnly,
     /// Attempted to write a List or XList with a value not contained in the object's elements
     WriteSettingsNotAnElement,
+    /// New settings value is not on the slider's increment
+    WriteSettingsOffIncrement(i32, i32),
+    /// Neither settings endpoint gave the slider's bounds, with the slider's name
+    SliderNoBounds(String),
+    /// Could not find a setting at the given path
+    SettingPathNotFound(String),
+    /// Pairing client ID is empty or too long
+    InvalidClientId(String),
+    /// Pairing client name is empty or too long
+    InvalidClientName(String),
+    /// Pairing pin has too few digits
+    InvalidPin(String),
+    /// Channel is not a number or a number with a sub-channel, e.g. "4.1"
+    InvalidChannel(String),
+    /// The tuner is not the current input
+    TunerNotActive,
+    /// The device did not give a DIAL `Application-URL`
+    NoApplicationUrl,
+    /// The device refused to launch the DIAL app, with the HTTP status it replied with
+    DialLaunchFailed(String, u16),
+    /// The device info did not give a settings root, so settings can not be reached
+    MissingSettingsRoot,
+    /// A key combination was sent without any key events
+    EmptyKeyCombo,
+    /// The app catalog was disabled with [`disable_app_catalog()`](super::Device::disable_app_catalog)
+    AppCatalogDisabled,
+    /// No SmartCast device responded to discovery in time
+    NoDeviceFound,
+    /// No app in the catalog matches the name, with the names of apps which share a word with it
+    AppNotFound(String, Vec<String>),
+    /// Several apps in the catalog match the name equally well, with their names
+    AmbiguousAppName(String, Vec<String>),
+    /// An app was looked up by an empty name
+    EmptyAppName,
+    /// The device does not have the setting behind a convenience method, such as
+    /// [`set_eco_mode()`](super::Device::set_eco_mode)
+    Unsupported {
+        /// What the device can not do, e.g. "eco mode"
+        feature: &'static str,
+    },
+    /// The setting behind a convenience method, such as [`eco_mode()`](super::Device::eco_mode),
+    /// has no value
+    NoValue {
+        /// The setting without a value, e.g. "eco mode"
+        feature: &'static str,
+    },
+    /// An on/off setting, such as [`cec_enabled()`](super::Device::cec_enabled), has a value which
+    /// is neither on nor off, with the value
+    NotAToggle(String),
     #[doc(hidden)]
     Message(String),
 }
@@ -249,6 +349,12 @@ impl Display for ClientError {
                 uuid
             ),
 
+            Self::DeviceNotFoundUrl(url) => write!(
+                f,
+                "Could not connect to SmartCast device with description url: '{}'",
+                url
+            ),
+
             Self::WriteSettingsBadType(current, new) => write!(
                 f,
                 "New value type [{:?}] does not match current [{:?}]",
@@ -265,11 +371,154 @@ impl Display for ClientError {
                 write!(f, "Attempted to write a menu or read only setting")
             }
 
+            Self::WriteSettingsOffIncrement(increment, new_val) => write!(
+                f,
+                "New value is not on the slider's increment of {}. New value: {}",
+                increment, new_val
+            ),
+
+            Self::SliderNoBounds(name) => write!(f, "Slider '{}' has no bounds", name),
+
             Self::WriteSettingsNotAnElement => {
                 write!(f, "Attempted to write a List or XList with a value not contained in the object's elements")
             }
 
+            Self::SettingPathNotFound(path) => {
+                write!(f, "Could not find setting at path: '{}'", path)
+            }
+
+            Self::InvalidClientId(id) => write!(
+                f,
+                "Client ID must be non-empty and at most 64 characters: '{}'",
+                id
+            ),
+
+            Self::InvalidClientName(name) => write!(
+                f,
+                "Client name must be non-empty and at most 64 characters: '{}'",
+                name
+            ),
+
+            Self::InvalidPin(pin) => write!(
+                f,
+                "Pin must have at least {} digits: '{}'",
+                crate::device::PIN_LEN,
+                pin
+            ),
+
+            Self::InvalidChannel(channel) => write!(f, "Invalid channel: '{}'", channel),
+
+            Self::TunerNotActive => write!(f, "The tuner is not the current input"),
+
+            Self::NoApplicationUrl => write!(f, "The device did not give a DIAL Application-URL"),
+
+            Self::DialLaunchFailed(app, status) => {
+                write!(f, "Could not launch DIAL app '{}': HTTP {}", app, status)
+            }
+
+            Self::MissingSettingsRoot => {
+                write!(
+                    f,
+                    "Device info has no settings root, settings can not be reached"
+                )
+            }
+
+            Self::EmptyKeyCombo => write!(f, "Key combination has no key events"),
+
+            Self::AppCatalogDisabled => write!(f, "The app catalog is disabled"),
+
+            Self::NoDeviceFound => write!(f, "No SmartCast device was found"),
+
+            Self::AppNotFound(name, near) if near.is_empty() => {
+                write!(f, "No app matches '{}'", name)
+            }
+
+            Self::AppNotFound(name, near) => write!(
+                f,
+                "No app matches '{}', similar apps: {}",
+                name,
+                near.join(", ")
+            ),
+
+            Self::AmbiguousAppName(name, candidates) => write!(
+                f,
+                "Several apps match '{}': {}",
+                name,
+                candidates.join(", ")
+            ),
+
+            Self::EmptyAppName => write!(f, "App name is empty"),
+
+            Self::Unsupported { feature } => write!(f, "The device does not support {}", feature),
+
+            Self::NoValue { feature } => write!(f, "The device gave no value for {}", feature),
+
+            Self::NotAToggle(value) => write!(f, "'{}' is not on or off", value),
+
             Self::Message(msg) => write!(f, "{}", msg),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn reqwest_connect() {
+        // Bind then drop a listener so nothing is listening on the port
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let err: Error = reqwest::get(format!("http://{}", addr))
+            .await
+            .unwrap_err()
+            .into();
+        assert!(err.is_connect());
+        assert!(!err.is_timeout());
+        assert!(!err.is_decode());
+    }
+
+    #[tokio::test]
+    async fn reqwest_timeout() {
+        // Accepts connections but never responds
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(100))
+            .build()
+            .unwrap();
+        let err: Error = client
+            .get(format!("http://{}", listener.local_addr().unwrap()))
+            .send()
+            .await
+            .unwrap_err()
+            .into();
+        assert!(err.is_timeout());
+        assert!(!err.is_decode());
+    }
+
+    #[tokio::test]
+    async fn reqwest_decode() {
+        let response = reqwest::Response::from(http::Response::new("not json"));
+        let err: Error = response
+            .json::<serde_json::Value>()
+            .await
+            .unwrap_err()
+            .into();
+        assert!(err.is_decode());
+        assert!(!err.is_connect());
+        assert!(!err.is_timeout());
+    }
+
+    #[test]
+    fn not_reqwest() {
+        let err = Error::Other("other".into());
+        assert!(!err.is_timeout());
+        assert!(!err.is_connect());
+        assert!(!err.is_decode());
+    }
+}
@@ -46,10 +46,23 @@ mod device;
 mod discover;
 mod error;
 
-pub use device::{App, Button, Device, DeviceInfo, Input, SettingType, SliderInfo, SubSetting};
+#[cfg(feature = "last-status")]
+pub use device::Status;
+pub use device::{
+    App, AppPayload, Bounds, Button, CodeSet, Device, DeviceInfo, DeviceKind, DeviceTime,
+    EditableSetting, EndpointBase, Feature, HdrFormat, Input, InputKind, KeyEvent, KeyResult,
+    ModelQuirks, PoolStats, PowerState, RequestType, SettingType, SettingsWatch, SignalFormat,
+    SliderInfo, SubSetting, TaggedSetting,
+};
+pub use discover::{AddressFamily, Candidate, Discovery, SsdpResponse};
 pub use error::{ApiError, ClientError, Error, Result};
 
+use tokio::net::UdpSocket;
+use tokio::sync::Semaphore;
+
 use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
 
 /// Discover devices on network
 ///
@@ -62,3 +75,202 @@ pub fn discover_devices() -> impl Future<Output = Result<Vec<Device>>> {
         discover::DEFAULT_SSDP_MAXTIME,
     )
 }
+
+/// Discover devices on network by searching from `socket`
+///
+/// Same as [`discover_devices()`], but the search is sent from `socket` and replies are received
+/// on it. This allows discovery on a socket which was already set up, e.g. bound to the address
+/// of a specific network interface.
+///
+/// # Example
+///
+/// ```
+/// # async fn example() -> Result<(), smartcast::Error> {
+/// use tokio::net::UdpSocket;
+///
+/// // Search from the wired interface only
+/// let socket = UdpSocket::bind("192.168.0.2:0").await?;
+/// let devices = smartcast::discover_devices_with_socket(socket).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn discover_devices_with_socket(
+    socket: UdpSocket,
+) -> impl Future<Output = Result<Vec<Device>>> {
+    discover::ssdp_with_socket(
+        socket,
+        discover::SSDP_IP,
+        discover::SSDP_URN,
+        discover::DEFAULT_SSDP_MAXTIME,
+    )
+}
+
+/// Find the first device on the network
+///
+/// Returns as soon as one device responds rather than waiting out the rest of the scan, which
+/// is all that is needed when there is only one TV. Returns [`ClientError::NoDeviceFound`] if no
+/// device responds. See [`discover_one_within()`] to wait longer or shorter.
+///
+/// # Example
+///
+/// ```
+/// # async fn example() -> Result<(), smartcast::Error> {
+/// let dev = smartcast::discover_one().await?;
+/// println!("Found {}", dev.name());
+/// # Ok(())
+/// # }
+/// ```
+pub fn discover_one() -> impl Future<Output = Result<Device>> {
+    discover_one_within(Duration::from_secs(discover::DEFAULT_SSDP_MAXTIME as u64))
+}
+
+/// Find the first device on the network, waiting at most `timeout` for it to respond
+///
+/// Same as [`discover_one()`], but with a different time to wait.
+pub fn discover_one_within(timeout: Duration) -> impl Future<Output = Result<Device>> {
+    discover::ssdp_one(discover::SSDP_IP, discover::SSDP_URN, timeout)
+}
+
+/// Find devices on the network without connecting to them
+///
+/// Same as [`discover_devices()`], but only the description of each device is read, so no port
+/// is probed and no [`Device`] is created. This is much faster for listing the devices on the
+/// network, then connecting to the one which is picked. See [`Candidate`].
+///
+/// # Example
+///
+/// ```
+/// # async fn example() -> Result<(), smartcast::Error> {
+/// let candidates = smartcast::discover_candidates().await?;
+/// for candidate in &candidates {
+///     println!("{} ({})", candidate.name, candidate.ip);
+/// }
+///
+/// if let Some(candidate) = candidates.first() {
+///     let dev = candidate.connect().await?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn discover_candidates() -> impl Future<Output = Result<Vec<Candidate>>> {
+    discover::ssdp_candidates(
+        discover::SSDP_IP,
+        discover::SSDP_URN,
+        discover::DEFAULT_SSDP_MAXTIME,
+    )
+}
+
+/// Get every SSDP reply on the network along with its device description
+///
+/// This is meant for debugging when a device is not found by [`discover_devices()`]. Replies
+/// from all devices are returned, not just SmartCast devices, and no [`Device`] is created. See
+/// [`SsdpResponse`].
+///
+/// # Example
+///
+/// ```
+/// # async fn example() -> Result<(), smartcast::Error> {
+/// for res in smartcast::discover_raw().await? {
+///     println!("{}: {:?}", res.addr, res.location);
+///     if let Some(Ok(description)) = res.description {
+///         println!("{}", description);
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn discover_raw() -> impl Future<Output = Result<Vec<SsdpResponse>>> {
+    discover::ssdp_raw(
+        discover::SSDP_IP,
+        discover::SSDP_URN,
+        discover::DEFAULT_SSDP_MAXTIME,
+    )
+}
+
+/// Start discovering devices on network
+///
+/// Unlike [`discover_devices()`], devices are returned by the [`Discovery`] one at a time as they
+/// respond, so the scan can be stopped as soon as the device you are looking for is found.
+///
+/// # Example
+///
+/// ```
+/// # async fn example() -> Result<(), smartcast::Error> {
+/// let mut discovery = smartcast::discover().await?;
+///
+/// while let Some(dev) = discovery.next_device().await {
+///     let dev = dev?;
+///     if dev.name() == "Living Room TV" {
+///         discovery.abort();
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn discover() -> impl Future<Output = Result<Discovery>> {
+    discover::Discovery::start(
+        discover::SSDP_IP,
+        discover::SSDP_URN,
+        discover::DEFAULT_SSDP_MAXTIME,
+    )
+}
+
+/// Run `op` on many devices concurrently
+///
+/// At most `limit` devices are queried at once. Each device is returned with its own result, in
+/// the same order as `devices`, so one device failing does not affect the others.
+///
+/// # Example
+///
+/// ```
+/// # async fn example() -> Result<(), smartcast::Error> {
+/// let devices = smartcast::discover_devices().await?;
+///
+/// let states = smartcast::poll_all(&devices, 4, |dev| async move {
+///     dev.is_powered_on().await
+/// })
+/// .await;
+///
+/// for (dev, powered_on) in states {
+///     match powered_on {
+///         Ok(powered_on) => println!("{}: {}", dev.name(), powered_on),
+///         Err(e) => println!("{}: {}", dev.name(), e),
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub async fn poll_all<F, Fut, T>(
+    devices: &[Device],
+    limit: usize,
+    op: F,
+) -> Vec<(Device, Result<T>)>
+where
+    F: Fn(Device) -> Fut,
+    Fut: Future<Output = Result<T>> + Send + 'static,
+    T: Send + 'static,
+{
+    let permits = Arc::new(Semaphore::new(limit.max(1)));
+
+    let handles: Vec<_> = devices
+        .iter()
+        .map(|dev| {
+            let permits = permits.clone();
+            let fut = op(dev.clone());
+            tokio::spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                fut.await
+            })
+        })
+        .collect();
+
+    let mut results = Vec::with_capacity(devices.len());
+    for (dev, handle) in devices.iter().zip(handles) {
+        let res = match handle.await {
+            Ok(res) => res,
+            Err(e) => Err(Error::Other(format!("Task failed: {}", e))),
+        };
+        results.push((dev.clone(), res));
+    }
+    results
+}
@@ -0,0 +1,1079 @@
+mod support;
+use support::{CodeSet, DeviceType, PortOption, Test, EXPECTED_ESN, EXPECTED_TIME};
+
+use smartcast::{
+    Bounds, Button, ClientError, DeviceTime, Error, Feature, HdrFormat, SettingType, SignalFormat,
+};
+
+use tokio::time::timeout;
+
+use std::time::Duration;
+
+#[tokio::test]
+async fn sleep_timer() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            assert_eq!(dev.sleep_timer().await.unwrap(), None);
+
+            // Rounds to the nearest option
+            let set = dev
+                .set_sleep_timer(Some(Duration::from_secs(50 * 60)))
+                .await
+                .unwrap();
+            assert_eq!(set, Some(Duration::from_secs(60 * 60)));
+            assert_eq!(dev.sleep_timer().await.unwrap(), set);
+
+            let set = dev
+                .set_sleep_timer(Some(Duration::from_secs(10 * 60 * 60)))
+                .await
+                .unwrap();
+            assert_eq!(set, Some(Duration::from_secs(180 * 60)));
+
+            assert_eq!(dev.set_sleep_timer(None).await.unwrap(), None);
+            assert_eq!(dev.sleep_timer().await.unwrap(), None);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn sleep_timer_missing() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::SoundBar,
+        CodeSet::Random,
+        |dev| async move {
+            assert!(matches!(
+                dev.sleep_timer().await,
+                Err(Error::Client(ClientError::Unsupported {
+                    feature: "sleep timer"
+                }))
+            ));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn region() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            assert_eq!(
+                dev.region().await.unwrap(),
+                Some("United States".to_string())
+            );
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn region_missing() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::SoundBar,
+        CodeSet::Random,
+        |dev| async move {
+            assert_eq!(dev.region().await.unwrap(), None);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn backlight() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            assert_eq!(dev.backlight().await.unwrap(), 50);
+            assert_eq!(dev.set_backlight(20).await.unwrap(), 20);
+            assert_eq!(dev.backlight().await.unwrap(), 20);
+
+            assert!(matches!(
+                dev.set_backlight(101).await,
+                Err(Error::Client(ClientError::WriteSettingsOutsideBounds(
+                    0, 100, 101
+                )))
+            ));
+            assert_eq!(dev.backlight().await.unwrap(), 20);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn brightness() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            assert_eq!(dev.brightness().await.unwrap(), 50);
+            // Rounds to the slider's increment
+            assert_eq!(dev.set_brightness(33).await.unwrap(), 35);
+            assert_eq!(dev.brightness().await.unwrap(), 35);
+
+            let setting = dev.find_setting_by_cname("brightness").await.unwrap();
+            setting.update(42).await.unwrap();
+            assert_eq!(dev.brightness().await.unwrap(), 40);
+
+            assert!(matches!(
+                setting.update_strict(42).await,
+                Err(Error::Client(ClientError::WriteSettingsOffIncrement(5, 42)))
+            ));
+            setting.update_strict(45).await.unwrap();
+            assert_eq!(dev.brightness().await.unwrap(), 45);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn picture_settings() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            let settings = dev.picture_settings().await.unwrap();
+            let names: Vec<String> = settings.iter().map(|e| e.setting.name()).collect();
+            assert!(names.contains(&"Backlight".to_string()));
+            assert!(names.contains(&"HDR Mode".to_string()));
+            assert!(!names.contains(&"Volume".to_string()));
+
+            for editable in &settings {
+                match editable.setting.setting_type() {
+                    SettingType::Slider => {
+                        assert!(matches!(editable.bounds, Bounds::Slider(_)));
+                        assert!(editable.setting.value::<i32>().is_some());
+                    }
+                    SettingType::List | SettingType::XList => {
+                        assert!(matches!(&editable.bounds, Bounds::Elements(e) if !e.is_empty()));
+                    }
+                    other => panic!("Unexpected picture setting type: {:?}", other),
+                }
+            }
+
+            let backlight = settings
+                .iter()
+                .find(|e| e.setting.name() == "Backlight")
+                .unwrap();
+            assert_eq!(backlight.setting.value::<i32>(), Some(50));
+            match &backlight.bounds {
+                Bounds::Slider(info) => assert_eq!((info.min, info.max), (0, 100)),
+                _ => panic!("Backlight is not a slider"),
+            }
+
+            // Slider info which is only at the dynamic endpoint is found too
+            let sharpness = settings
+                .iter()
+                .find(|e| e.setting.name() == "Sharpness")
+                .unwrap();
+            assert!(matches!(&sharpness.bounds, Bounds::Slider(info) if info.max == 20));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn picture_settings_missing() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::SoundBar,
+        CodeSet::Random,
+        |dev| async move {
+            assert!(matches!(
+                dev.picture_settings().await,
+                Err(Error::Client(ClientError::Unsupported {
+                    feature: "picture settings"
+                }))
+            ));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn picture_missing() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::SoundBar,
+        CodeSet::Random,
+        |dev| async move {
+            assert!(matches!(
+                dev.set_backlight(20).await,
+                Err(Error::Client(ClientError::Unsupported { .. }))
+            ));
+            assert!(matches!(
+                dev.brightness().await,
+                Err(Error::Client(ClientError::Unsupported { .. }))
+            ));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn device_time() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            assert_eq!(
+                dev.device_time().await.unwrap(),
+                Some(DeviceTime {
+                    time: EXPECTED_TIME.into(),
+                    time_zone: Some("Pacific".into()),
+                })
+            );
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn device_time_missing() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::SoundBar,
+        CodeSet::Random,
+        |dev| async move {
+            assert_eq!(dev.device_time().await.unwrap(), None);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn usage_hours() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            // Reported as "2,418 Hours"
+            assert_eq!(dev.usage_hours().await.unwrap(), Some(2418));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn usage_hours_missing() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::SoundBar,
+        CodeSet::Random,
+        |dev| async move {
+            assert_eq!(dev.usage_hours().await.unwrap(), None);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn current_input_signal() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            // Reported as "3840x2160@60Hz HDR10"
+            assert_eq!(
+                dev.current_input_signal().await.unwrap(),
+                SignalFormat::Video {
+                    width: 3840,
+                    height: 2160,
+                    refresh_rate: Some(60),
+                    hdr: Some(HdrFormat::HDR10),
+                }
+            );
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn current_input_signal_missing() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::SoundBar,
+        CodeSet::Random,
+        |dev| async move {
+            assert_eq!(
+                dev.current_input_signal().await.unwrap(),
+                SignalFormat::Unknown
+            );
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn esn() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            assert_eq!(dev.esn().await.unwrap(), Some(EXPECTED_ESN.to_string()));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn esn_missing() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::SoundBar,
+        CodeSet::Random,
+        |dev| async move {
+            assert_eq!(dev.esn().await.unwrap(), None);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn watch_settings() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            let mut watch = dev
+                .watch_settings(Duration::from_millis(100))
+                .await
+                .unwrap();
+
+            dev.set_backlight(20).await.unwrap();
+            let setting = watch.next_change().await.unwrap();
+            assert_eq!(setting.cname(), "backlight");
+            assert_eq!(setting.setting_type(), SettingType::Slider);
+            assert_eq!(setting.value::<i32>(), Some(20));
+
+            // Nothing else changed, so polls only read hashvals. Closed captions is the only menu
+            // read in full, once per poll, as caption style has no hashval to compare.
+            let start = support::settings_read_count();
+            assert!(timeout(Duration::from_millis(500), watch.next_change())
+                .await
+                .is_err());
+            assert!(support::settings_read_count() - start <= 5);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn channels() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            assert_eq!(dev.current_channel().await.unwrap(), "4.1");
+            assert_eq!(
+                dev.channel_list().await.unwrap(),
+                vec!["2.1", "4.1", "4.2", "7.1"]
+            );
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn channels_missing() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::SoundBar,
+        CodeSet::Random,
+        |dev| async move {
+            assert!(matches!(
+                dev.current_channel().await,
+                Err(Error::Client(ClientError::Unsupported { .. }))
+            ));
+            assert!(matches!(
+                dev.channel_list().await,
+                Err(Error::Client(ClientError::Unsupported { .. }))
+            ));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn tune_to_channel() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            support::pair(&dev).await;
+            dev.change_input("HDMI-0").await.unwrap();
+            assert!(matches!(
+                dev.tune_to_channel("7.1").await,
+                Err(Error::Client(ClientError::TunerNotActive))
+            ));
+
+            dev.change_input("TV").await.unwrap();
+            let start = support::key_events().len();
+            dev.tune_to_channel("7-1").await.unwrap();
+            dev.tune_to_channel("12").await.unwrap();
+            let events: Vec<String> = support::key_events()[start..]
+                .iter()
+                .map(|(action, _)| action.clone())
+                .collect();
+            assert_eq!(events, ["KEYPRESS"; 5]);
+            assert_eq!(
+                support::key_codes()[start..],
+                [(0, 55), (0, 45), (0, 49), (0, 49), (0, 50)]
+            );
+
+            let start = support::key_events().len();
+            assert!(matches!(
+                dev.tune_to_channel("seven").await,
+                Err(Error::Client(ClientError::InvalidChannel(_)))
+            ));
+            assert_eq!(support::key_events().len(), start);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn audio_output() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            assert_eq!(dev.audio_output().await.unwrap(), "TV Speakers");
+            dev.set_audio_output("ARC").await.unwrap();
+            assert_eq!(dev.audio_output().await.unwrap(), "ARC");
+
+            assert!(matches!(
+                dev.set_audio_output("Bluetooth").await,
+                Err(Error::Client(ClientError::WriteSettingsNotAnElement))
+            ));
+            assert_eq!(dev.audio_output().await.unwrap(), "ARC");
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn audio_output_missing() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::SoundBar,
+        CodeSet::Random,
+        |dev| async move {
+            assert!(matches!(
+                dev.audio_output().await,
+                Err(Error::Client(ClientError::Unsupported { .. }))
+            ));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn audio_track() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            assert_eq!(
+                dev.audio_tracks().await.unwrap(),
+                vec!["English", "Spanish", "French"]
+            );
+            assert_eq!(dev.audio_track().await.unwrap(), "English");
+            dev.set_audio_track("Spanish").await.unwrap();
+            assert_eq!(dev.audio_track().await.unwrap(), "Spanish");
+
+            assert!(matches!(
+                dev.set_audio_track("German").await,
+                Err(Error::Client(ClientError::WriteSettingsNotAnElement))
+            ));
+            assert_eq!(dev.audio_track().await.unwrap(), "Spanish");
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn audio_track_missing() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::SoundBar,
+        CodeSet::Random,
+        |dev| async move {
+            assert!(matches!(
+                dev.audio_track().await,
+                Err(Error::Client(ClientError::Unsupported { .. }))
+            ));
+            assert!(matches!(
+                dev.audio_tracks().await,
+                Err(Error::Client(ClientError::Unsupported { .. }))
+            ));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn eco_mode() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            assert_eq!(dev.eco_mode().await.unwrap(), "Standard");
+            dev.set_eco_mode("Off").await.unwrap();
+            assert_eq!(dev.eco_mode().await.unwrap(), "Off");
+
+            assert!(matches!(
+                dev.set_eco_mode("Turbo").await,
+                Err(Error::Client(ClientError::WriteSettingsNotAnElement))
+            ));
+            assert_eq!(dev.eco_mode().await.unwrap(), "Off");
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn eco_mode_missing() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::SoundBar,
+        CodeSet::Random,
+        |dev| async move {
+            assert!(matches!(
+                dev.eco_mode().await,
+                Err(Error::Client(ClientError::Unsupported {
+                    feature: "eco mode"
+                }))
+            ));
+            assert_eq!(
+                dev.set_eco_mode("Off").await.unwrap_err().to_string(),
+                "The device does not support eco mode"
+            );
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn local_dimming() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            assert_eq!(dev.local_dimming().await.unwrap(), "Off");
+            dev.set_local_dimming("High").await.unwrap();
+            assert_eq!(dev.local_dimming().await.unwrap(), "High");
+
+            assert!(matches!(
+                dev.set_local_dimming("Maximum").await,
+                Err(Error::Client(ClientError::WriteSettingsNotAnElement))
+            ));
+            assert_eq!(dev.local_dimming().await.unwrap(), "High");
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn motion_smoothing() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            assert_eq!(dev.motion_smoothing().await.unwrap(), "Off");
+            dev.set_motion_smoothing("Medium").await.unwrap();
+            assert_eq!(dev.motion_smoothing().await.unwrap(), "Medium");
+            dev.set_motion_smoothing("Off").await.unwrap();
+            assert_eq!(dev.motion_smoothing().await.unwrap(), "Off");
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn resolution() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            assert_eq!(
+                dev.resolutions().await.unwrap(),
+                vec!["Auto", "720p", "1080p", "2160p"]
+            );
+            assert_eq!(dev.resolution().await.unwrap(), "Auto");
+            dev.set_resolution("1080p").await.unwrap();
+            assert_eq!(dev.resolution().await.unwrap(), "1080p");
+
+            assert!(matches!(
+                dev.set_resolution("480i").await,
+                Err(Error::Client(ClientError::WriteSettingsNotAnElement))
+            ));
+            assert_eq!(dev.resolution().await.unwrap(), "1080p");
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn resolution_missing() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::SoundBar,
+        CodeSet::Random,
+        |dev| async move {
+            assert!(matches!(
+                dev.resolutions().await,
+                Err(Error::Client(ClientError::Unsupported {
+                    feature: "resolution"
+                }))
+            ));
+            assert!(matches!(
+                dev.set_resolution("1080p").await,
+                Err(Error::Client(ClientError::Unsupported {
+                    feature: "resolution"
+                }))
+            ));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn screensaver() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            assert_eq!(dev.screensaver().await.unwrap(), "Off");
+            dev.set_screensaver("Art").await.unwrap();
+            assert_eq!(dev.screensaver().await.unwrap(), "Art");
+
+            assert!(matches!(
+                dev.set_screensaver("Aquarium").await,
+                Err(Error::Client(ClientError::WriteSettingsNotAnElement))
+            ));
+            assert_eq!(dev.screensaver().await.unwrap(), "Art");
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn screensaver_missing() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::SoundBar,
+        CodeSet::Random,
+        |dev| async move {
+            assert!(matches!(
+                dev.screensaver().await,
+                Err(Error::Client(ClientError::Unsupported {
+                    feature: "screensaver"
+                }))
+            ));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn advanced_picture_missing() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::SoundBar,
+        CodeSet::Random,
+        |dev| async move {
+            assert!(matches!(
+                dev.local_dimming().await,
+                Err(Error::Client(ClientError::Unsupported {
+                    feature: "local dimming"
+                }))
+            ));
+            assert!(matches!(
+                dev.set_motion_smoothing("Off").await,
+                Err(Error::Client(ClientError::Unsupported {
+                    feature: "motion smoothing"
+                }))
+            ));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn store_mode() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            assert!(!dev.store_mode().await.unwrap());
+            dev.set_store_mode(true).await.unwrap();
+            assert!(dev.store_mode().await.unwrap());
+            dev.set_store_mode(false).await.unwrap();
+            assert!(!dev.store_mode().await.unwrap());
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn store_mode_missing() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::SoundBar,
+        CodeSet::Random,
+        |dev| async move {
+            assert!(matches!(
+                dev.store_mode().await,
+                Err(Error::Client(ClientError::Unsupported {
+                    feature: "store mode"
+                }))
+            ));
+            assert!(matches!(
+                dev.set_store_mode(false).await,
+                Err(Error::Client(ClientError::Unsupported { .. }))
+            ));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn aspect_ratio() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            assert_eq!(dev.aspect_ratio().await.unwrap(), "Normal");
+            dev.set_aspect_ratio("Wide").await.unwrap();
+            assert_eq!(dev.aspect_ratio().await.unwrap(), "Wide");
+
+            assert!(matches!(
+                dev.set_aspect_ratio("Cinema").await,
+                Err(Error::Client(ClientError::WriteSettingsNotAnElement))
+            ));
+            assert_eq!(dev.aspect_ratio().await.unwrap(), "Wide");
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn aspect_ratio_missing() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::SoundBar,
+        CodeSet::Random,
+        |dev| async move {
+            assert!(matches!(
+                dev.aspect_ratio().await,
+                Err(Error::Client(ClientError::Unsupported { .. }))
+            ));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn volume_percent() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            // 0-100 scale
+            assert_eq!(dev.volume_percent().await.unwrap(), 0.0);
+            assert_eq!(dev.set_volume_percent(42.0).await.unwrap(), 42.0);
+            assert_eq!(dev.volume_percent().await.unwrap(), 42.0);
+            assert_eq!(dev.set_volume_percent(150.0).await.unwrap(), 100.0);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn volume_cached_endpoint() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            assert_eq!(dev.volume().await.unwrap(), 0);
+            dev.set_volume_percent(42.0).await.unwrap();
+
+            // Only the volume endpoint is read once it is known
+            let start = support::request_count();
+            assert_eq!(dev.volume().await.unwrap(), 42);
+            assert_eq!(support::request_count() - start, 1);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn volume_coarse_scale() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::SoundBar,
+        CodeSet::Random,
+        |dev| async move {
+            // Read on the device's own 0-31 scale rather than as a percent
+            let (min, max, _) = dev.volume_range().await.unwrap();
+            assert_eq!(max, 31);
+            assert_eq!(dev.volume().await.unwrap(), min);
+            dev.set_volume_percent(100.0).await.unwrap();
+            assert_eq!(dev.volume().await.unwrap(), max);
+            dev.set_volume_percent(50.0).await.unwrap();
+            assert_eq!(dev.volume().await.unwrap(), 16);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn held_keys_released_after_volume() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            // The cached volume setting must not keep the device alive
+            dev.volume().await.unwrap();
+
+            let start = support::key_events().len();
+            dev.key_down(Button::Menu, None).await.unwrap();
+            drop(dev);
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            let events: Vec<String> = support::key_events()[start..]
+                .iter()
+                .map(|(action, _)| action.clone())
+                .collect();
+            assert_eq!(events, ["KEYDOWN", "KEYUP"]);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn volume_range() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            assert_eq!(dev.volume_range().await.unwrap(), (0, 100, 1));
+            // Cached after the first read
+            let start = support::request_count();
+            assert_eq!(dev.volume_range().await.unwrap(), (0, 100, 1));
+            assert_eq!(support::request_count(), start);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn volume_range_coarse_scale() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::SoundBar,
+        CodeSet::Random,
+        |dev| async move {
+            assert_eq!(dev.volume_range().await.unwrap(), (0, 31, 1));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn volume_percent_coarse_scale() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::SoundBar,
+        CodeSet::Random,
+        |dev| async move {
+            // 0-31 scale, 50% rounds to 16
+            let percent = dev.set_volume_percent(50.0).await.unwrap();
+            assert!((percent - 16.0 * 100.0 / 31.0).abs() < 0.001);
+            assert_eq!(dev.volume_percent().await.unwrap(), percent);
+
+            assert_eq!(dev.set_volume_percent(100.0).await.unwrap(), 100.0);
+            assert_eq!(dev.set_volume_percent(-5.0).await.unwrap(), 0.0);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn mute() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        |dev| async move {
+            assert!(!dev.is_muted().await.unwrap());
+            assert!(dev.toggle_mute().await.unwrap());
+            assert!(dev.is_muted().await.unwrap());
+            assert!(!dev.toggle_mute().await.unwrap());
+            assert!(!dev.is_muted().await.unwrap());
+
+            dev.set_muted(true).await.unwrap();
+            assert!(dev.is_muted().await.unwrap());
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn cec_arc() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            assert!(!dev.cec_enabled().await.unwrap());
+            dev.set_cec(true).await.unwrap();
+            assert!(dev.cec_enabled().await.unwrap());
+            dev.set_cec(false).await.unwrap();
+            assert!(!dev.cec_enabled().await.unwrap());
+
+            assert!(!dev.arc_enabled().await.unwrap());
+            dev.set_arc(true).await.unwrap();
+            assert!(dev.arc_enabled().await.unwrap());
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn cec_arc_soundbar() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::SoundBar,
+        CodeSet::Random,
+        |dev| async move {
+            dev.set_cec(true).await.unwrap();
+            assert!(dev.cec_enabled().await.unwrap());
+
+            assert!(matches!(
+                dev.arc_enabled().await,
+                Err(Error::Client(ClientError::Unsupported { .. }))
+            ));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn hdr_formats() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            assert_eq!(
+                dev.hdr_formats().await.unwrap(),
+                [
+                    HdrFormat::HDR10,
+                    HdrFormat::HDR10Plus,
+                    HdrFormat::DolbyVision,
+                    HdrFormat::HLG
+                ]
+            );
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn hdr_formats_missing() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::SoundBar,
+        CodeSet::Random,
+        |dev| async move {
+            assert!(dev.hdr_formats().await.unwrap().is_empty());
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn supports_tv() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            for feature in [
+                Feature::Volume,
+                Feature::Mute,
+                Feature::Cec,
+                Feature::Arc,
+                Feature::Backlight,
+                Feature::LocalDimming,
+                Feature::Screensaver,
+                Feature::Tuner,
+                Feature::AppLaunch,
+            ] {
+                assert!(dev.supports(feature).await.unwrap(), "{:?}", feature);
+            }
+
+            // Cached after the first check
+            let start = support::request_count();
+            assert!(dev.supports(Feature::Tuner).await.unwrap());
+            assert_eq!(support::request_count(), start);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn supports_soundbar() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::SoundBar,
+        CodeSet::Random,
+        |dev| async move {
+            for feature in [Feature::Volume, Feature::Mute, Feature::Cec] {
+                assert!(dev.supports(feature).await.unwrap(), "{:?}", feature);
+            }
+            for feature in [
+                Feature::Backlight,
+                Feature::LocalDimming,
+                Feature::Screensaver,
+                Feature::Tuner,
+                Feature::AppLaunch,
+            ] {
+                assert!(!dev.supports(feature).await.unwrap(), "{:?}", feature);
+            }
+        },
+    )
+    .await;
+}
@@ -1,6 +1,10 @@
 mod support;
 use support::{CodeSet, DeviceType, PortOption, Test};
 
+use smartcast::{ClientError, Device, Error};
+
+use std::time::Duration;
+
 #[tokio::test]
 async fn port7345() {
     Test::simulate(
@@ -22,3 +26,82 @@ async fn port9000() {
     )
     .await;
 }
+
+#[tokio::test]
+async fn api_not_ready() {
+    // The first round of port probes fails since the API server is not up yet
+    support::simulate_with_api_delay(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        Duration::from_millis(300),
+    )
+    .await;
+
+    let dev = Device::from_ip("127.0.0.1").await.unwrap();
+    assert_eq!(dev.ip(), "127.0.0.1");
+}
+
+#[tokio::test]
+async fn missing_settings_root() {
+    support::simulate_without_settings_root(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+    )
+    .await;
+
+    assert!(matches!(
+        Device::from_ip("127.0.0.1").await,
+        Err(Error::Client(ClientError::MissingSettingsRoot))
+    ));
+}
+
+#[tokio::test]
+async fn late_settings_root() {
+    // Device info is missing the settings root until the second read
+    support::simulate_with_late_settings_root(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        1,
+    )
+    .await;
+
+    let dev = Device::from_ip("127.0.0.1").await.unwrap();
+    assert!(!dev.settings().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn description_url() {
+    support::simulate(PortOption::Random, DeviceType::Random, CodeSet::Random).await;
+    support::connect_device().await;
+
+    let dev = Device::from_url("http://127.0.0.1:8008/forwarded/desc.xml")
+        .await
+        .unwrap();
+    // The address is used as given
+    assert_eq!(dev.ip(), "127.0.0.1");
+    dev.settings().await.unwrap();
+
+    assert!(Device::from_url("http://127.0.0.1:8008/missing.xml")
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn api_slow_to_start() {
+    // Longer than every port is tried for by default
+    support::simulate_with_api_delay(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        Duration::from_millis(1500),
+    )
+    .await;
+
+    let dev = Device::from_ip_with_probe("127.0.0.1", 10, Duration::from_millis(250))
+        .await
+        .unwrap();
+    assert_eq!(dev.ip(), "127.0.0.1");
+}
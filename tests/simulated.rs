@@ -1,9 +1,17 @@
 mod support;
 use support::{helpers, CodeSet, DeviceType, PortOption, Test};
 
-use smartcast::SettingType;
+use smartcast::{
+    ApiError, Bounds, Button, ClientError, CodeSet as RemoteCodeSet, DeviceKind, EndpointBase,
+    Error, InputKind, KeyEvent, PowerState, RequestType, SettingType,
+};
 
 use rand::Rng;
+use serde::Deserialize;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 #[tokio::test]
 async fn pair_start() {
@@ -53,25 +61,77 @@ async fn pair_cancel() {
 }
 
 #[tokio::test]
-async fn powerstate() {
+async fn ensure_paired_not_paired() {
     Test::simulate(
         PortOption::Random,
-        DeviceType::Random,
+        DeviceType::TV,
         CodeSet::Random,
         |dev| async move {
-            dev.is_powered_on().await.unwrap();
+            assert!(!dev.verify_auth_token().await.unwrap());
+
+            let asked = Arc::new(AtomicUsize::new(0));
+            let auth_token = dev
+                .ensure_paired("name", "id", || {
+                    asked.fetch_add(1, Ordering::SeqCst);
+                    async { Ok("0000".to_string()) }
+                })
+                .await
+                .unwrap();
+            assert_eq!(asked.load(Ordering::SeqCst), 1);
+            assert!(auth_token.is_some());
+            assert_eq!(dev.auth_token().await, auth_token);
+            assert!(dev.verify_auth_token().await.unwrap());
         },
     )
     .await;
 }
 
 #[tokio::test]
-async fn current_input() {
+async fn ensure_paired_already_paired() {
     Test::simulate(
         PortOption::Random,
-        DeviceType::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            support::pair(&dev).await;
+            let token = dev.auth_token().await;
+
+            let start = support::request_count();
+            let auth_token = dev
+                .ensure_paired("name", "id", || async {
+                    panic!("Pin requested for a paired client")
+                })
+                .await
+                .unwrap();
+            assert_eq!(auth_token, token);
+
+            // Only the token was checked
+            assert_eq!(support::request_count() - start, 1);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn clear_auth_token() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
         CodeSet::Random,
         |dev| async move {
+            support::pair(&dev).await;
+            dev.current_input().await.unwrap();
+
+            dev.clear_auth_token().await;
+            assert_eq!(dev.auth_token().await, None);
+            assert!(!dev.verify_auth_token().await.unwrap());
+            assert!(matches!(
+                dev.current_input().await,
+                Err(Error::Api(ApiError::RequiresPairing))
+            ));
+
+            // Pairing again works as usual
+            support::pair(&dev).await;
             dev.current_input().await.unwrap();
         },
     )
@@ -79,261 +139,1593 @@ async fn current_input() {
 }
 
 #[tokio::test]
-async fn list_inputs() {
+async fn ensure_paired_canceled() {
     Test::simulate(
         PortOption::Random,
-        DeviceType::Random,
+        DeviceType::TV,
         CodeSet::Random,
         |dev| async move {
-            dev.list_inputs().await.unwrap();
+            let result = dev
+                .ensure_paired("name", "id", || async {
+                    Err(ClientError::from("No pin").into())
+                })
+                .await;
+            assert!(matches!(
+                result,
+                Err(Error::Client(ClientError::Message(_)))
+            ));
+            assert!(dev.auth_token().await.is_none());
         },
     )
     .await;
 }
 
 #[tokio::test]
-async fn change_input() {
+async fn power_on_repeated() {
     Test::simulate(
         PortOption::Random,
         DeviceType::Random,
         CodeSet::Random,
         |dev| async move {
-            let inputs = dev.list_inputs().await.unwrap();
-            for input in inputs {
-                dev.change_input(input.name()).await.unwrap();
-            }
-            assert!(dev.change_input("not_an_input").await.is_err())
+            assert!(!dev.is_powered_on().await.unwrap());
+
+            // Already off
+            let start = support::key_events().len();
+            dev.power_off().await.unwrap();
+            assert_eq!(support::key_events().len() - start, 0);
+
+            dev.power_on().await.unwrap();
+            dev.power_on().await.unwrap();
+            assert!(dev.is_powered_on().await.unwrap());
+            assert_eq!(support::key_events().len() - start, 1);
+
+            dev.power_off().await.unwrap();
+            dev.power_off().await.unwrap();
+            assert!(!dev.is_powered_on().await.unwrap());
+            assert_eq!(support::key_events().len() - start, 2);
         },
     )
     .await;
 }
 
 #[tokio::test]
-async fn settings_read() {
+async fn powerstate() {
     Test::simulate(
         PortOption::Random,
         DeviceType::Random,
         CodeSet::Random,
         |dev| async move {
-            let settings = dev.settings().await.unwrap();
-            assert!(!settings.is_empty());
-            for s in settings {
-                match s.setting_type() {
-                    SettingType::Slider => {
-                        let found_slider_info = s.slider_info().await;
-                        assert!(found_slider_info.is_ok());
-                        assert!(&found_slider_info.unwrap().is_some());
+            let powered_on = dev.is_powered_on().await.unwrap();
+            let expected = if powered_on {
+                PowerState::On
+            } else {
+                PowerState::Off
+            };
+            assert_eq!(dev.power_state().await.unwrap(), expected);
+        },
+    )
+    .await;
+}
 
-                        let found_slider_info = s.slider_info().await.unwrap().unwrap();
-                        let exp_slider = support::expected_slider_info();
+#[tokio::test]
+async fn device_kind_tv() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            assert_eq!(dev.device_kind().await, DeviceKind::TV);
+        },
+    )
+    .await;
+}
 
-                        if !found_slider_info.dec_marker.is_empty()
-                            || !found_slider_info.inc_marker.is_empty()
-                            || found_slider_info.center.is_some()
-                        {
-                            assert_eq!(exp_slider.dec_marker, found_slider_info.dec_marker);
-                            assert_eq!(exp_slider.inc_marker, found_slider_info.inc_marker);
-                            assert_eq!(exp_slider.center, found_slider_info.center);
-                        }
-                        assert_eq!(exp_slider.increment, found_slider_info.increment);
-                        assert_eq!(exp_slider.max, found_slider_info.max);
-                        assert_eq!(exp_slider.min, found_slider_info.min);
-                    }
-                    SettingType::Value => {
-                        assert!(s.value::<serde_json::Value>().is_some())
-                    }
-                    SettingType::List | SettingType::XList => {
-                        let elements = s.elements().await.unwrap();
-                        assert!(elements.len() == support::LIST_LEN);
-                    }
-                    _ => {}
-                }
-            }
+#[tokio::test]
+async fn device_kind_soundbar() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::SoundBar,
+        CodeSet::Random,
+        |dev| async move {
+            assert_eq!(dev.device_kind().await, DeviceKind::SoundBar);
         },
     )
     .await;
 }
 
 #[tokio::test]
-async fn settings_write() {
+async fn custom_request() {
+    #[derive(Debug, Deserialize)]
+    struct PowerMode {
+        #[serde(rename = "CNAME")]
+        cname: String,
+        #[serde(rename = "VALUE")]
+        value: u8,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct Pairing {
+        pairing_req_token: u32,
+        challenge_type: u32,
+    }
+
     Test::simulate(
         PortOption::Random,
         DeviceType::Random,
         CodeSet::Random,
         |dev| async move {
-            let settings = dev.settings().await.unwrap();
-            assert!(!settings.is_empty());
+            // ITEMS
+            let res = dev
+                .custom_request(RequestType::Get, "/state/device/power_mode", None, None)
+                .await
+                .unwrap();
+            assert_eq!(res["ITEMS"][0]["CNAME"], "power_mode");
 
-            let mut rng = rand::thread_rng();
+            let modes: Vec<PowerMode> = dev
+                .custom_request_as(RequestType::Get, "/state/device/power_mode", None, None)
+                .await
+                .unwrap();
+            assert_eq!(modes[0].cname, "power_mode");
+            assert_eq!(modes[0].value == 1, dev.is_powered_on().await.unwrap());
 
-            for s in settings {
-                match s.setting_type() {
-                    SettingType::Slider => {
-                        log::debug!(target: "test::simulated", "Type Slider");
-                        let slider_info = s.slider_info().await.unwrap().unwrap();
+            // ITEM
+            let pairing: Pairing = dev
+                .custom_request_as(
+                    RequestType::Put,
+                    "/pairing/start",
+                    Some(serde_json::json!({
+                        "DEVICE_NAME": "name",
+                        "DEVICE_ID": "id",
+                    })),
+                    None,
+                )
+                .await
+                .unwrap();
+            assert_eq!(pairing.challenge_type, 1);
+            dev.cancel_pair((
+                pairing.pairing_req_token,
+                pairing.challenge_type,
+                "id".into(),
+            ))
+            .await
+            .unwrap();
+        },
+    )
+    .await;
+}
 
-                        // Good Values
-                        for _ in 0..50 {
-                            assert!(s
-                                .update(rng.gen_range(slider_info.min..=slider_info.max))
-                                .await
-                                .is_ok());
-                        }
-                        assert!(s.update(slider_info.min).await.is_ok());
-                        assert!(s.update(slider_info.max).await.is_ok());
+#[tokio::test]
+async fn custom_request_auth_override() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |mut dev| async move {
+            let endpoint = "/menu_native/dynamic/tv_settings/devices/current_input";
 
-                        // Bad values - these should be handled by the library
-                        assert!(s.update(slider_info.max + 1).await.is_err());
-                        assert!(s.update(slider_info.max + 100).await.is_err());
-                        assert!(s.update(slider_info.min - 1).await.is_err());
-                        assert!(s.update(slider_info.min - 100).await.is_err());
-                        assert!(s.update("bad value".to_string()).await.is_err());
-                        assert!(s.update(true).await.is_err());
-                    }
-                    SettingType::Value => {
-                        log::debug!(target: "test::simulated", "Type Value");
-                        // Good Values
-                        if s.is_boolean() {
-                            log::debug!(target: "test::simulated", "Type Value - Boolean");
-                            assert!(s.update(true).await.is_ok());
-                            assert!(s.update(false).await.is_ok());
-                        } else if s.is_number() {
-                            log::debug!(target: "test::simulated", "Type Value - Number");
-                            for _ in 0..50 {
-                                assert!(s.update(rng.gen::<i32>()).await.is_ok())
-                            }
-                        }
-                        if s.is_string() {
-                            log::debug!(target: "test::simulated", "Type Value - String");
-                            for _ in 0..50 {
-                                assert!(s
-                                    .update(support::rand_data::string(rng.gen_range(5..25)))
-                                    .await
-                                    .is_ok())
-                            }
-                        }
+            let pairing_data = dev.begin_pair("name", "id").await.unwrap();
+            let token = dev.finish_pair(pairing_data, "0000").await.unwrap();
 
-                        // Bad values - these should be handled by the library
-                        if s.is_boolean() {
-                            assert!(s.update(rng.gen::<f32>()).await.is_err());
-                            assert!(s.update(rng.gen::<u32>()).await.is_err());
-                            assert!(s.update(rng.gen::<i32>()).await.is_err());
-                            assert!(s
-                                .update(support::rand_data::string(rng.gen_range(5..25)))
-                                .await
-                                .is_err());
-                        } else if s.is_number() {
-                            for _ in 0..50 {
-                                assert!(s
-                                    .update(rng.gen_range(i32::MAX as f64..f64::MAX))
-                                    .await
-                                    .is_err());
-                                assert!(s
-                                    .update(rng.gen_range(i32::MAX as u64..u64::MAX))
-                                    .await
-                                    .is_err());
-                                assert!(s
-                                    .update(rng.gen_range(i32::MIN as i64..i64::MIN))
-                                    .await
-                                    .is_err());
-                                assert!(s.update(true).await.is_err());
-                                assert!(s.update(false).await.is_err());
-                                assert!(s
-                                    .update(support::rand_data::string(rng.gen_range(5..25)))
-                                    .await
-                                    .is_err());
-                            }
-                        } else if s.is_string() {
-                            for _ in 0..50 {
-                                assert!(s.update(rng.gen::<f32>()).await.is_err());
-                                assert!(s.update(rng.gen::<u32>()).await.is_err());
-                                assert!(s.update(rng.gen::<i32>()).await.is_err());
-                                assert!(s.update(true).await.is_err());
-                                assert!(s.update(false).await.is_err());
-                                assert!(s
-                                    .update(support::rand_data::string(rng.gen_range(100..250)))
-                                    .await
-                                    .is_err());
-                            }
-                        }
-                    }
-                    SettingType::List | SettingType::XList => {
-                        log::debug!(target: "test::simulated", "Type List");
-                        let elements = s.elements().await.unwrap();
-                        assert!(elements.len() == support::LIST_LEN);
+            // Device has no token so the override is the only one sent
+            dev.custom_request(RequestType::Get, endpoint, None, Some(&token))
+                .await
+                .unwrap();
+            assert_eq!(dev.auth_token().await, None);
 
-                        // Good Values
-                        for element in elements {
-                            assert!(s.update(element).await.is_ok());
-                        }
+            dev.set_auth_token(token.clone()).await.unwrap();
+            assert!(matches!(
+                dev.custom_request(RequestType::Get, endpoint, None, Some("bad_token"))
+                    .await,
+                Err(Error::Api(ApiError::RequiresPairing))
+            ));
 
-                        // Bad values - these should be handled by the library
-                        for _ in 0..50 {
-                            assert!(s
-                                .update(support::rand_data::string(rng.gen_range(10..25)))
-                                .await
-                                .is_err());
-                        }
-                    }
-                    _ => {
-                        // Bad values - these should be handled by the library
-                        for _ in 0..50 {
-                            assert!(s.update(rng.gen::<f32>()).await.is_err());
-                            assert!(s.update(rng.gen::<u32>()).await.is_err());
-                            assert!(s.update(rng.gen::<i32>()).await.is_err());
-                            assert!(s.update(true).await.is_err());
-                            assert!(s.update(false).await.is_err());
-                            assert!(s
-                                .update(support::rand_data::string(rng.gen_range(100..250)))
-                                .await
-                                .is_err());
-                        }
-                    }
-                }
-            }
+            // Stored token is still used by default
+            dev.custom_request(RequestType::Get, endpoint, None, None)
+                .await
+                .unwrap();
+            assert_eq!(dev.auth_token().await, Some(token));
         },
     )
     .await;
 }
 
 #[tokio::test]
-async fn virtual_remote_default() {
+async fn app_catalog_disabled() {
     Test::simulate(
         PortOption::Random,
         DeviceType::Random,
-        CodeSet::Default,
+        CodeSet::Random,
         |dev| async move {
-            let buttons = helpers::button_vec();
+            dev.disable_app_catalog().await;
 
-            for i in 0..3 {
-                for button in &buttons {
-                    let res = match i {
-                        0 => dev.key_down(*button, None).await,
-                        1 => dev.key_up(*button).await,
-                        2 => dev.key_press(*button).await,
-                        _ => panic!(),
-                    };
-                    assert!(res.is_ok());
-                }
-            }
+            let start = support::request_count();
+            assert!(matches!(
+                dev.current_app().await,
+                Err(Error::Client(ClientError::AppCatalogDisabled))
+            ));
+            assert_eq!(support::request_count(), start);
         },
     )
     .await;
 }
 
 #[tokio::test]
-async fn virtual_remote_secondary() {
+async fn current_app_payload() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            // Does not need the catalog
+            dev.disable_app_catalog().await;
+
+            let payload = dev.current_app_payload().await.unwrap();
+            assert_eq!(payload.name_space(), 4);
+            assert_eq!(payload.app_id(), "999");
+            assert_eq!(payload.message(), "http://unknown.app/launch");
+            assert_eq!(
+                payload.to_string(),
+                r#"{"NAME_SPACE":4,"APP_ID":"999","MESSAGE":"http://unknown.app/launch"}"#
+            );
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn poll_all() {
     Test::simulate(
         PortOption::Random,
         DeviceType::Random,
-        CodeSet::Secondary,
+        CodeSet::Random,
         |dev| async move {
-            let buttons = helpers::button_vec();
+            // The simulator only serves one device, so connect several clients to it
+            let mut devices = vec![dev];
+            for _ in 0..3 {
+                devices.push(support::connect_device().await);
+            }
 
-            for i in 0..3 {
-                for button in &buttons {
-                    let res = match i {
-                        0 => dev.key_down(*button, None).await,
+            // Each client has its own request limit, so only poll_all bounds the requests
+            support::track_concurrency(Duration::from_millis(50));
+            let calls = Arc::new(AtomicUsize::new(0));
+            let results = smartcast::poll_all(&devices, 2, |dev| {
+                let call = calls.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    if call == 1 {
+                        return Err(ClientError::from("Failed").into());
+                    }
+                    dev.is_powered_on().await
+                }
+            })
+            .await;
+            assert_eq!(support::max_concurrent_requests(), 2);
+            support::track_concurrency(Duration::ZERO);
+
+            assert_eq!(results.len(), devices.len());
+            for (i, (_, res)) in results.iter().enumerate() {
+                if i == 1 {
+                    assert!(res.is_err());
+                } else {
+                    assert!(res.is_ok());
+                }
+            }
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn requires_auth_tv() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            assert!(dev.requires_auth().await.unwrap());
+
+            // Cached, and not affected by pairing
+            support::pair(&dev).await;
+            let start = support::request_count();
+            assert!(dev.requires_auth().await.unwrap());
+            assert_eq!(support::request_count(), start);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn requires_auth_soundbar() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::SoundBar,
+        CodeSet::Random,
+        |dev| async move {
+            assert!(!dev.requires_auth().await.unwrap());
+            dev.current_input().await.unwrap();
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn launch_dial_app() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        |dev| async move {
+            assert_eq!(
+                dev.application_url().await.as_deref(),
+                Some("http://127.0.0.1:8008/apps/")
+            );
+            dev.launch_dial_app("YouTube").await.unwrap();
+            assert!(matches!(
+                dev.launch_dial_app("NotAnApp").await,
+                Err(Error::Client(ClientError::DialLaunchFailed(_, 404)))
+            ));
+        },
+    )
+    .await;
+}
+
+#[cfg(feature = "last-status")]
+#[tokio::test]
+async fn last_status() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        |dev| async move {
+            dev.is_powered_on().await.unwrap();
+            assert_eq!(dev.last_status().await.unwrap().result, "SUCCESS");
+
+            dev.custom_request(RequestType::Get, "/not/an/endpoint", None, None)
+                .await
+                .unwrap_err();
+            assert_eq!(dev.last_status().await.unwrap().result, "URI_NOT_FOUND");
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn current_input() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        |dev| async move {
+            support::pair(&dev).await;
+            dev.current_input().await.unwrap();
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn list_inputs() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        |dev| async move {
+            dev.list_inputs().await.unwrap();
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn physical_inputs() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        |dev| async move {
+            let device_info = dev.device_info().await.unwrap();
+            let physical = device_info.physical_inputs();
+            assert!(!physical.is_empty());
+
+            let names: Vec<String> = dev
+                .list_inputs()
+                .await
+                .unwrap()
+                .iter()
+                .map(|input| input.name())
+                .collect();
+            assert!(physical.iter().all(|name| names.contains(name)));
+            assert!(physical.len() < names.len());
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn hdmi_inputs() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        |dev| async move {
+            let names: Vec<String> = dev
+                .hdmi_inputs()
+                .await
+                .unwrap()
+                .iter()
+                .map(|input| input.name())
+                .collect();
+            assert_eq!(names, vec!["HDMI-0", "HDMI-1", "HDMI-2", "HDMI-3"]);
+
+            // HDMI, the composite input, the tuner, and SmartCast
+            assert_eq!(dev.input_count().await.unwrap(), 7);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn input_kinds() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        |dev| async move {
+            let mut kinds: Vec<(String, InputKind)> = dev
+                .list_inputs()
+                .await
+                .unwrap()
+                .iter()
+                .map(|input| (input.name(), input.kind()))
+                .collect();
+            kinds.sort_by(|a, b| a.0.cmp(&b.0));
+
+            assert_eq!(
+                kinds,
+                vec![
+                    ("CAST".to_string(), InputKind::Cast),
+                    ("COMP".to_string(), InputKind::Composite),
+                    ("HDMI-0".to_string(), InputKind::Hdmi(0)),
+                    ("HDMI-1".to_string(), InputKind::Hdmi(1)),
+                    ("HDMI-2".to_string(), InputKind::Hdmi(2)),
+                    ("HDMI-3".to_string(), InputKind::Hdmi(3)),
+                    ("TV".to_string(), InputKind::Tuner),
+                ]
+            );
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn inputs_with_current() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        |dev| async move {
+            support::pair(&dev).await;
+            dev.change_input("HDMI-2").await.unwrap();
+
+            let inputs = dev.inputs_with_current().await.unwrap();
+            assert_eq!(inputs.len(), dev.list_inputs().await.unwrap().len());
+
+            let current: Vec<_> = inputs
+                .iter()
+                .filter(|(_, is_current)| *is_current)
+                .collect();
+            assert_eq!(current.len(), 1);
+            assert_eq!(current[0].0.name(), "HDMI-2");
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn input_eq() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        |dev| async move {
+            let first = dev.list_inputs().await.unwrap();
+            let second = dev.list_inputs().await.unwrap();
+            assert_eq!(first, second);
+
+            let hdmi = first.iter().find(|input| input.name() == "HDMI-1").unwrap();
+            assert!(second
+                .iter()
+                .any(|input| input.hashval() == hdmi.hashval() && input == hdmi));
+            assert!(first.iter().filter(|input| *input == hdmi).count() == 1);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn change_input() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        |dev| async move {
+            support::pair(&dev).await;
+            let inputs = dev.list_inputs().await.unwrap();
+            for input in inputs {
+                dev.change_input(input.name()).await.unwrap();
+            }
+            assert!(dev.change_input("not_an_input").await.is_err())
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn settings_read() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        |dev| async move {
+            let settings = dev.settings().await.unwrap();
+            assert!(!settings.is_empty());
+            for s in settings {
+                match s.setting_type() {
+                    SettingType::Slider => {
+                        let found_slider_info = s.slider_info().await;
+                        assert!(found_slider_info.is_ok());
+                        assert!(&found_slider_info.unwrap().is_some());
+
+                        let found_slider_info = s.slider_info().await.unwrap().unwrap();
+                        let exp_slider = support::expected_slider_info();
+
+                        if !found_slider_info.dec_marker.is_empty()
+                            || !found_slider_info.inc_marker.is_empty()
+                            || found_slider_info.center.is_some()
+                        {
+                            assert_eq!(exp_slider.dec_marker, found_slider_info.dec_marker);
+                            assert_eq!(exp_slider.inc_marker, found_slider_info.inc_marker);
+                            assert_eq!(exp_slider.center, found_slider_info.center);
+                        }
+                        assert_eq!(exp_slider.increment, found_slider_info.increment);
+                        assert_eq!(exp_slider.max, found_slider_info.max);
+                        assert_eq!(exp_slider.min, found_slider_info.min);
+                    }
+                    SettingType::Value => {
+                        assert!(s.value::<serde_json::Value>().is_some())
+                    }
+                    SettingType::List | SettingType::XList => {
+                        let elements = s.elements().await.unwrap();
+                        assert!(elements.len() == support::LIST_LEN);
+                    }
+                    _ => {}
+                }
+            }
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn cast_name() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        |dev| async move {
+            let cast_name = dev.cast_name().await.unwrap();
+            assert_eq!(cast_name, dev.device_info().await.unwrap().cast_name);
+            assert_ne!(cast_name, dev.name());
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn device_info_raw() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        |dev| async move {
+            let info = dev.device_info_raw().await.unwrap();
+            // Not a field of DeviceInfo
+            assert_eq!(info["BOARD_REVISION"], "B2");
+            assert_eq!(info["MODEL_NAME"], dev.model_name());
+            assert_eq!(
+                info["CAST_NAME"],
+                dev.device_info().await.unwrap().cast_name
+            );
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn warm_up() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        |dev| async move {
+            dev.warm_up(true).await.unwrap();
+            dev.warm_up(true).await.unwrap();
+            assert!(dev.cached_device_info().await.is_some());
+
+            let start = support::request_count();
+            let warm = dev.settings().await.unwrap();
+            let warm_requests = support::request_count() - start;
+
+            let start = support::request_count();
+            let cold = dev.settings().await.unwrap();
+            let cold_requests = support::request_count() - start;
+
+            assert!(warm_requests < cold_requests);
+            assert_eq!(
+                warm.iter().map(|s| s.name()).collect::<Vec<_>>(),
+                cold.iter().map(|s| s.name()).collect::<Vec<_>>()
+            );
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn warm_up_then_write() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        |dev| async move {
+            dev.warm_up(true).await.unwrap();
+
+            // The first list is at the root, so it is among the prefetched settings
+            let list = dev
+                .all_settings()
+                .await
+                .unwrap()
+                .into_iter()
+                .find(|s| s.setting_type() == SettingType::List)
+                .unwrap();
+            let new_value = list.elements().await.unwrap()[1].clone();
+            list.update(new_value.clone()).await.unwrap();
+
+            let root = dev.settings().await.unwrap();
+            let read_back = root.iter().find(|s| s.cname() == list.cname()).unwrap();
+            assert_eq!(read_back.value::<String>(), Some(new_value));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn settings_write() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        |dev| async move {
+            let settings = dev.settings().await.unwrap();
+            assert!(!settings.is_empty());
+
+            let mut rng = rand::thread_rng();
+
+            for s in settings {
+                match s.setting_type() {
+                    SettingType::Slider => {
+                        log::debug!(target: "test::simulated", "Type Slider");
+                        let slider_info = s.slider_info().await.unwrap().unwrap();
+
+                        // Good Values
+                        for _ in 0..50 {
+                            assert!(s
+                                .update(rng.gen_range(slider_info.min..=slider_info.max))
+                                .await
+                                .is_ok());
+                        }
+                        assert!(s.update(slider_info.min).await.is_ok());
+                        assert!(s.update(slider_info.max).await.is_ok());
+
+                        // Bad values - these should be handled by the library
+                        assert!(s.update(slider_info.max + 1).await.is_err());
+                        assert!(s.update(slider_info.max + 100).await.is_err());
+                        assert!(s.update(slider_info.min - 1).await.is_err());
+                        assert!(s.update(slider_info.min - 100).await.is_err());
+                        assert!(s.update("bad value".to_string()).await.is_err());
+                        assert!(s.update(true).await.is_err());
+                    }
+                    SettingType::Value => {
+                        log::debug!(target: "test::simulated", "Type Value");
+                        // Good Values
+                        if s.is_boolean() {
+                            log::debug!(target: "test::simulated", "Type Value - Boolean");
+                            assert!(s.update(true).await.is_ok());
+                            assert!(s.update(false).await.is_ok());
+                        } else if s.is_number() {
+                            log::debug!(target: "test::simulated", "Type Value - Number");
+                            for _ in 0..50 {
+                                assert!(s.update(rng.gen::<i32>()).await.is_ok())
+                            }
+                        }
+                        if s.is_string() {
+                            log::debug!(target: "test::simulated", "Type Value - String");
+                            for _ in 0..50 {
+                                assert!(s
+                                    .update(support::rand_data::string(rng.gen_range(5..25)))
+                                    .await
+                                    .is_ok())
+                            }
+                        }
+
+                        // Bad values - these should be handled by the library
+                        if s.is_boolean() {
+                            assert!(s.update(rng.gen::<f32>()).await.is_err());
+                            assert!(s.update(rng.gen::<u32>()).await.is_err());
+                            assert!(s.update(rng.gen::<i32>()).await.is_err());
+                            assert!(s
+                                .update(support::rand_data::string(rng.gen_range(5..25)))
+                                .await
+                                .is_err());
+                        } else if s.is_number() {
+                            for _ in 0..50 {
+                                assert!(s
+                                    .update(rng.gen_range(i32::MAX as f64..f64::MAX))
+                                    .await
+                                    .is_err());
+                                assert!(s
+                                    .update(rng.gen_range(i32::MAX as u64..u64::MAX))
+                                    .await
+                                    .is_err());
+                                assert!(s
+                                    .update(rng.gen_range(i32::MIN as i64..i64::MIN))
+                                    .await
+                                    .is_err());
+                                assert!(s.update(true).await.is_err());
+                                assert!(s.update(false).await.is_err());
+                                assert!(s
+                                    .update(support::rand_data::string(rng.gen_range(5..25)))
+                                    .await
+                                    .is_err());
+                            }
+                        } else if s.is_string() {
+                            for _ in 0..50 {
+                                assert!(s.update(rng.gen::<f32>()).await.is_err());
+                                assert!(s.update(rng.gen::<u32>()).await.is_err());
+                                assert!(s.update(rng.gen::<i32>()).await.is_err());
+                                assert!(s.update(true).await.is_err());
+                                assert!(s.update(false).await.is_err());
+                                assert!(s
+                                    .update(support::rand_data::string(rng.gen_range(100..250)))
+                                    .await
+                                    .is_err());
+                            }
+                        }
+                    }
+                    SettingType::List | SettingType::XList => {
+                        log::debug!(target: "test::simulated", "Type List");
+                        let elements = s.elements().await.unwrap();
+                        assert!(elements.len() == support::LIST_LEN);
+
+                        // Good Values
+                        for element in elements {
+                            assert!(s.update(element).await.is_ok());
+                        }
+
+                        // Bad values - these should be handled by the library
+                        for _ in 0..50 {
+                            assert!(s
+                                .update(support::rand_data::string(rng.gen_range(10..25)))
+                                .await
+                                .is_err());
+                        }
+                    }
+                    _ => {
+                        // Bad values - these should be handled by the library
+                        for _ in 0..50 {
+                            assert!(s.update(rng.gen::<f32>()).await.is_err());
+                            assert!(s.update(rng.gen::<u32>()).await.is_err());
+                            assert!(s.update(rng.gen::<i32>()).await.is_err());
+                            assert!(s.update(true).await.is_err());
+                            assert!(s.update(false).await.is_err());
+                            assert!(s
+                                .update(support::rand_data::string(rng.gen_range(100..250)))
+                                .await
+                                .is_err());
+                        }
+                    }
+                }
+            }
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn settings_by_cname() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            let setting = dev.find_setting_by_cname("sleep_timer").await.unwrap();
+            assert_eq!(setting.name(), "Sleep Timer");
+            assert_eq!(setting.cname(), "sleep_timer");
+            assert_eq!(setting.value::<String>(), Some("Off".into()));
+
+            // Matches the CNAME, not the name
+            assert!(matches!(
+                dev.find_setting_by_cname("Sleep Timer").await,
+                Err(Error::Client(ClientError::SettingPathNotFound(_)))
+            ));
+            assert!(matches!(
+                dev.find_setting_by_cname("not_a_setting").await,
+                Err(Error::Client(ClientError::SettingPathNotFound(_)))
+            ));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn settings_element_pairs() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            let setting = dev
+                .find_setting_by_cname("color_temperature")
+                .await
+                .unwrap();
+            assert_eq!(setting.setting_type(), SettingType::XList);
+            assert_eq!(
+                setting.elements().await.unwrap(),
+                ["Cool", "Normal", "Warm"]
+            );
+            assert_eq!(
+                setting.element_pairs().await.unwrap(),
+                [
+                    ("Cool".to_string(), "COOL".to_string()),
+                    ("Normal".to_string(), "NORMAL".to_string()),
+                    ("Warm".to_string(), "WARM".to_string()),
+                ]
+            );
+
+            // Picking by label writes the underlying value
+            setting.update("Warm".to_string()).await.unwrap();
+            let setting = dev
+                .find_setting_by_cname("color_temperature")
+                .await
+                .unwrap();
+            assert_eq!(setting.value::<String>(), Some("WARM".into()));
+
+            // The underlying value is accepted as well
+            setting.update("NORMAL".to_string()).await.unwrap();
+            let setting = dev
+                .find_setting_by_cname("color_temperature")
+                .await
+                .unwrap();
+            assert_eq!(setting.value::<String>(), Some("NORMAL".into()));
+
+            assert!(matches!(
+                setting.update("Hot".to_string()).await,
+                Err(Error::Client(ClientError::WriteSettingsNotAnElement))
+            ));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn settings_search() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            let found = dev.search_settings("BACKLIGHT").await.unwrap();
+            assert_eq!(found.len(), 1);
+            assert_eq!(found[0].0, "Picture > Backlight");
+            assert_eq!(found[0].1.cname(), "backlight");
+
+            let found = dev.search_settings("esn").await.unwrap();
+            let paths: Vec<&str> = found.iter().map(|(path, _)| path.as_str()).collect();
+            assert_eq!(
+                paths,
+                ["System > System Information > ULI Information > ESN"]
+            );
+
+            assert!(dev
+                .search_settings("not a setting")
+                .await
+                .unwrap()
+                .is_empty());
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn settings_editable() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        |dev| async move {
+            let editable = dev.editable_settings().await.unwrap();
+            assert!(!editable.is_empty());
+
+            let exp_slider = support::expected_slider_info();
+            let mut found_exp_slider = false;
+            let mut types = Vec::new();
+            for e in editable {
+                assert!(e.setting.is_writable());
+                match e.setting.setting_type() {
+                    SettingType::Slider => match e.bounds {
+                        Bounds::Slider(info) => {
+                            assert!(info.min < info.max && info.increment > 0);
+                            found_exp_slider |= info.min == exp_slider.min
+                                && info.max == exp_slider.max
+                                && info.increment == exp_slider.increment;
+                        }
+                        _ => panic!("Slider without slider bounds"),
+                    },
+                    SettingType::List | SettingType::XList => {
+                        assert!(
+                            matches!(e.bounds, Bounds::Elements(elements) if !elements.is_empty())
+                        );
+                    }
+                    SettingType::Menu => panic!("Menus are not editable"),
+                    _ => {}
+                }
+                types.push(e.setting.setting_type());
+            }
+            assert!(found_exp_slider);
+            assert!(types.contains(&SettingType::Slider));
+            assert!(types.contains(&SettingType::List));
+            assert!(types.contains(&SettingType::XList));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn dump_settings_json() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            let dump = dev.dump_settings_json().await.unwrap();
+            let settings: Vec<serde_json::Value> = serde_json::from_str(&dump).unwrap();
+            let setting = |cname: &str| {
+                settings
+                    .iter()
+                    .find(|s| s["cname"] == cname)
+                    .cloned()
+                    .unwrap()
+            };
+
+            let backlight = setting("backlight");
+            assert_eq!(backlight["name"], "Backlight");
+            assert_eq!(backlight["type"], "Slider");
+            assert_eq!(backlight["value"], 50);
+            assert_eq!(backlight["editable"], true);
+            assert_eq!(backlight["bounds"]["max"], 100);
+            assert!(backlight["endpoint"]
+                .as_str()
+                .unwrap()
+                .ends_with("/picture/backlight"));
+
+            let time_zone = setting("time_zone");
+            assert_eq!(time_zone["bounds"].as_array().unwrap().len(), 4);
+
+            let esn = setting("esn");
+            assert_eq!(esn["read_only"], true);
+            assert!(esn["bounds"].is_null());
+
+            assert_eq!(setting("wifi_password")["value"], "<redacted>");
+            assert!(!dump.contains("hunter22"));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn settings_tagged() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            let tagged = dev.all_settings_tagged().await.unwrap();
+            let editable = tagged.iter().filter(|t| t.editable).count();
+            assert_eq!(editable, dev.editable_settings().await.unwrap().len());
+
+            for t in &tagged {
+                assert_ne!(t.setting.setting_type(), SettingType::Menu);
+                assert_eq!(t.editable, t.setting.is_writable());
+                if !t.editable {
+                    assert!(matches!(t.bounds, Bounds::Unbounded));
+                }
+            }
+
+            // System information is shown but cannot be changed
+            let tag = |cname: &str| {
+                tagged
+                    .iter()
+                    .find(|t| t.setting.cname() == cname)
+                    .map(|t| (t.editable, t.setting.read_only()))
+            };
+            assert_eq!(tag("esn"), Some((false, true)));
+            assert_eq!(tag("panel_hours"), Some((false, true)));
+            assert_eq!(tag("current_time"), Some((false, true)));
+            assert_eq!(tag("time_zone"), Some((true, false)));
+            assert_eq!(tag("backlight"), Some((true, false)));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn slider_info_static_only() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            let setting = dev.find_setting_by_cname("backlight").await.unwrap();
+            let info = setting.slider_info().await.unwrap().unwrap();
+            assert_eq!((info.min, info.max, info.increment), (0, 100, 1));
+            assert_eq!(info.center, Some(50));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn slider_info_dynamic_only() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            let setting = dev.find_setting_by_cname("sharpness").await.unwrap();
+            let info = setting.slider_info().await.unwrap().unwrap();
+            assert_eq!((info.min, info.max, info.increment), (0, 20, 2));
+            assert_eq!(info.center, Some(10));
+
+            // Bounds from the dynamic endpoint are still enforced
+            setting.update(14).await.unwrap();
+            assert!(setting.update(22).await.is_err());
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn slider_info_merged() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            let setting = dev.find_setting_by_cname("tint").await.unwrap();
+            let info = setting.slider_info().await.unwrap().unwrap();
+            assert_eq!((info.min, info.max, info.increment), (-50, 50, 1));
+            assert_eq!(info.center, Some(0));
+            assert_eq!(info.dec_marker, "Red");
+            assert_eq!(info.inc_marker, "Green");
+            assert_eq!(setting.value::<i32>(), Some(0));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn settings_base_learned() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            let setting = dev.find_setting_by_cname("sharpness").await.unwrap();
+            dev.set_settings_base(None).await;
+            assert_eq!(dev.settings_base(&SettingType::Slider).await, None);
+
+            // Sharpness is only described at the dynamic base, which is tried after the static
+            let start = support::request_count();
+            assert!(setting.slider_info().await.unwrap().is_some());
+            assert_eq!(support::request_count() - start, 2);
+            assert_eq!(
+                dev.settings_base(&SettingType::Slider).await,
+                Some(EndpointBase::Dynamic)
+            );
+
+            // The static base is skipped once the dynamic base is known to have it
+            let start = support::request_count();
+            assert!(setting.slider_info().await.unwrap().is_some());
+            assert_eq!(support::request_count() - start, 1);
+
+            // Learned again when a slider is only described at the other base
+            let backlight = dev.find_setting_by_cname("backlight").await.unwrap();
+            assert!(backlight.slider_info().await.unwrap().is_some());
+            assert_eq!(
+                dev.settings_base(&SettingType::Slider).await,
+                Some(EndpointBase::Static)
+            );
+
+            // A base which was set is not replaced by a learned one
+            dev.set_settings_base(Some(EndpointBase::Static)).await;
+            assert!(setting.slider_info().await.unwrap().is_some());
+            assert_eq!(
+                dev.settings_base(&SettingType::Slider).await,
+                Some(EndpointBase::Static)
+            );
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn settings_base_learned_per_type() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            let backlight = dev.find_setting_by_cname("backlight").await.unwrap();
+            let hdr_mode = dev.find_setting_by_cname("hdr_mode").await.unwrap();
+            dev.set_settings_base(None).await;
+            support::dynamic_elements(true);
+
+            // Sliders are learned to be at the static base
+            assert!(backlight.slider_info().await.unwrap().is_some());
+            assert_eq!(
+                dev.settings_base(&SettingType::Slider).await,
+                Some(EndpointBase::Static)
+            );
+
+            // Which does not make lists, whose elements are only at the dynamic base, read the
+            // static base first
+            for _ in 0..2 {
+                let start = support::request_count();
+                assert_eq!(hdr_mode.elements().await.unwrap().len(), 6);
+                assert_eq!(support::request_count() - start, 1);
+            }
+            support::dynamic_elements(false);
+
+            assert_eq!(
+                dev.settings_base(&SettingType::List).await,
+                Some(EndpointBase::Dynamic)
+            );
+            assert_eq!(
+                dev.settings_base(&SettingType::Slider).await,
+                Some(EndpointBase::Static)
+            );
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn read_setting_bases() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            // The static base has the bounds of tint but none of its markers or value
+            let res = dev.read_setting_static("picture/tint").await.unwrap();
+            let item = &res["ITEMS"][0];
+            assert_eq!(item["MINIMUM"], -50);
+            assert_eq!(item["MAXIMUM"], 50);
+            assert!(item["CENTER"].is_null());
+            assert_eq!(item["DECMARKER"], "");
+            assert!(item.get("VALUE").is_none());
+
+            let res = dev.read_setting_dynamic("/picture/tint/").await.unwrap();
+            let item = &res["ITEMS"][0];
+            assert_eq!(item["CENTER"], 0);
+            assert_eq!(item["DECMARKER"], "Red");
+            assert_eq!(item["INCMARKER"], "Green");
+            assert_eq!(item["VALUE"], 0);
+
+            // Sharpness is only described at the dynamic base
+            assert!(matches!(
+                dev.read_setting_static("picture/sharpness").await,
+                Err(Error::Api(ApiError::UriNotFound))
+            ));
+            let res = dev.read_setting_dynamic("picture/sharpness").await.unwrap();
+            assert_eq!(res["ITEMS"][0]["MAXIMUM"], 20);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn settings_flush_changes() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            support::change_hashvals_on_write(true);
+
+            let mut settings = Vec::new();
+            for cname in [
+                "backlight",
+                "brightness",
+                "hdr_mode",
+                "sleep_timer",
+                "picture_size",
+            ] {
+                settings.push(dev.find_setting_by_cname(cname).await.unwrap());
+            }
+            settings[0].stage(20);
+            // Rounded to the slider's increment of 5 when written
+            settings[1].stage(37);
+            settings[2].stage("HDR10".to_string());
+            settings[4].stage("Cinema".to_string());
+            assert!(settings[0].is_dirty());
+            assert!(!settings[3].is_dirty());
+
+            let results = dev.flush_changes(&mut settings).await;
+            support::change_hashvals_on_write(false);
+
+            // Every setting in the menu was written even though each write changed the hashvals
+            assert!(results[0].is_ok());
+            assert!(results[1].is_ok());
+            assert!(results[2].is_ok());
+            assert!(results[3].is_ok());
+            assert!(matches!(
+                results[4],
+                Err(Error::Client(ClientError::WriteSettingsNotAnElement))
+            ));
+            assert!(!settings[0].is_dirty());
+            assert!(settings[4].is_dirty());
+            assert_eq!(settings[1].value::<i32>(), Some(35));
+
+            let backlight = dev.find_setting_by_cname("backlight").await.unwrap();
+            assert_eq!(backlight.value::<i32>(), Some(20));
+            let brightness = dev.find_setting_by_cname("brightness").await.unwrap();
+            assert_eq!(brightness.value::<i32>(), Some(35));
+            let hdr_mode = dev.find_setting_by_cname("hdr_mode").await.unwrap();
+            assert_eq!(hdr_mode.value::<String>().unwrap(), "HDR10");
+            let picture_size = dev.find_setting_by_cname("picture_size").await.unwrap();
+            assert_eq!(picture_size.value::<String>().unwrap(), "Normal");
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn virtual_remote_default() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Default,
+        |dev| async move {
+            let buttons = helpers::button_vec();
+
+            for i in 0..3 {
+                for button in &buttons {
+                    let res = match i {
+                        0 => dev.key_down(*button, None).await,
+                        1 => dev.key_up(*button).await,
+                        2 => dev.key_press(*button).await,
+                        _ => panic!(),
+                    };
+                    assert!(res.is_ok());
+                }
+            }
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn request_limit() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        |dev| async move {
+            // One request at a time by default
+            support::track_concurrency(Duration::from_millis(50));
+            let results = tokio::join!(
+                dev.power_state(),
+                dev.power_state(),
+                dev.power_state(),
+                dev.power_state()
+            );
+            assert!(
+                results.0.is_ok() && results.1.is_ok() && results.2.is_ok() && results.3.is_ok()
+            );
+            assert_eq!(support::max_concurrent_requests(), 1);
+
+            support::track_concurrency(Duration::from_millis(50));
+            dev.set_max_concurrent_requests(4).await;
+            let results = tokio::join!(
+                dev.power_state(),
+                dev.power_state(),
+                dev.power_state(),
+                dev.power_state()
+            );
+            assert!(
+                results.0.is_ok() && results.1.is_ok() && results.2.is_ok() && results.3.is_ok()
+            );
+            assert!(support::max_concurrent_requests() > 1);
+
+            support::track_concurrency(Duration::ZERO);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn remote_codeset_pinned() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Secondary,
+        |dev| async move {
+            dev.set_remote_codeset(RemoteCodeSet::Secondary).await;
+
+            let start = support::request_count();
+            dev.key_press(Button::Left).await.unwrap();
+            dev.key_press(Button::Up).await.unwrap();
+            dev.key_press(Button::Right).await.unwrap();
+            assert_eq!(support::request_count() - start, 3);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn remote_codeset_detected() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Secondary,
+        |dev| async move {
+            assert_eq!(dev.remote_codeset().await, None);
+
+            // Other buttons do not tell the code sets apart
+            dev.key_press(Button::Ok).await.unwrap();
+            assert_eq!(dev.remote_codeset().await, None);
+
+            // The default code is rejected first
+            let start = support::request_count();
+            dev.key_press(Button::Left).await.unwrap();
+            assert_eq!(support::request_count() - start, 2);
+            assert_eq!(dev.remote_codeset().await, Some(RemoteCodeSet::Secondary));
+
+            let start = support::request_count();
+            dev.key_press(Button::Right).await.unwrap();
+            assert_eq!(support::request_count() - start, 1);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn key_press_detailed() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Secondary,
+        |dev| async move {
+            let result = dev.key_press_detailed(Button::Ok).await.unwrap();
+            assert_eq!(result.codeset, RemoteCodeSet::Default);
+            assert!(!result.fallback);
+
+            // The default code is rejected, so the alternate one is reported
+            let result = dev.key_press_detailed(Button::Left).await.unwrap();
+            assert_eq!(result.codeset, RemoteCodeSet::Secondary);
+            assert!(result.fallback);
+            assert_eq!(result.codes, vec![(3, 4)]);
+
+            // Once the code set is known there is nothing to fall back from
+            let result = dev.key_press_detailed(Button::Left).await.unwrap();
+            assert_eq!(result.codeset, RemoteCodeSet::Secondary);
+            assert!(!result.fallback);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn key_combo() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        |dev| async move {
+            let start_requests = support::request_count();
+            let start_events = support::key_events().len();
+            dev.key_combo(&[
+                (KeyEvent::Down, Button::Menu),
+                (KeyEvent::Press, Button::VolumeUp),
+                (KeyEvent::Up, Button::Menu),
+            ])
+            .await
+            .unwrap();
+
+            // One request with the whole KEYLIST
+            assert_eq!(support::request_count() - start_requests, 1);
+            let events: Vec<String> = support::key_events()[start_events..]
+                .iter()
+                .map(|(action, _)| action.clone())
+                .collect();
+            assert_eq!(events, ["KEYDOWN", "KEYPRESS", "KEYUP"]);
+
+            assert!(matches!(
+                dev.key_combo(&[]).await,
+                Err(Error::Client(ClientError::EmptyKeyCombo))
+            ));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn key_down_hold() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        |dev| async move {
+            // Shorter than the minimum hold
+            let start = support::key_events().len();
+            dev.key_down(Button::VolumeUp, Some(Duration::from_millis(1)))
+                .await
+                .unwrap();
+
+            let events = &support::key_events()[start..];
+            assert_eq!(events.len(), 2);
+            assert_eq!(events[0].0, "KEYDOWN");
+            assert_eq!(events[1].0, "KEYUP");
+            assert!(events[1].1 - events[0].1 >= Duration::from_millis(50));
+
+            // Longer than the minimum hold
+            dev.set_min_key_hold(Duration::from_millis(10)).await;
+            let start = support::key_events().len();
+            dev.key_down(Button::VolumeUp, Some(Duration::from_millis(200)))
+                .await
+                .unwrap();
+
+            let events = &support::key_events()[start..];
+            assert_eq!(events.len(), 2);
+            assert!(events[1].1 - events[0].1 >= Duration::from_millis(200));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn long_press() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        |dev| async move {
+            let start = support::key_events().len();
+            dev.long_press(Button::InputNext, Duration::from_millis(300))
+                .await
+                .unwrap();
+
+            let events = &support::key_events()[start..];
+            assert_eq!(events.len(), 2);
+            assert_eq!(events[0].0, "KEYDOWN");
+            assert_eq!(events[1].0, "KEYUP");
+            assert!(events[1].1 - events[0].1 >= Duration::from_millis(300));
+
+            // Not lengthened to the minimum hold
+            dev.set_min_key_hold(Duration::from_secs(1)).await;
+            let start = support::key_events().len();
+            dev.long_press(Button::InputNext, Duration::from_millis(100))
+                .await
+                .unwrap();
+
+            let events = &support::key_events()[start..];
+            assert_eq!(events.len(), 2);
+            assert!(events[1].1 - events[0].1 < Duration::from_secs(1));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn key_down_released_when_dropped() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        |dev| async move {
+            let start = support::key_events().len();
+            let _ = tokio::time::timeout(
+                Duration::from_millis(100),
+                dev.key_down(Button::VolumeUp, Some(Duration::from_millis(300))),
+            )
+            .await;
+
+            // Wait for the hold to finish after the future was dropped
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            let events = &support::key_events()[start..];
+            assert_eq!(events.len(), 2);
+            assert_eq!(events[1].0, "KEYUP");
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn held_keys_released_when_device_dropped() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        |dev| async move {
+            let start = support::key_events().len();
+            dev.key_down(Button::Menu, None).await.unwrap();
+            dev.key_down(Button::VolumeUp, None).await.unwrap();
+            dev.key_up(Button::VolumeUp).await.unwrap();
+
+            // A clone being dropped does not release anything
+            drop(dev.clone());
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            assert_eq!(support::key_events().len() - start, 3);
+
+            drop(dev);
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            let events: Vec<String> = support::key_events()[start..]
+                .iter()
+                .map(|(action, _)| action.clone())
+                .collect();
+            assert_eq!(events, ["KEYDOWN", "KEYDOWN", "KEYUP", "KEYUP"]);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn held_keys_released_after_warm_up() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Random,
+        |dev| async move {
+            // Prefetched settings must not keep the device alive
+            dev.warm_up(true).await.unwrap();
+
+            let start = support::key_events().len();
+            dev.key_down(Button::Menu, None).await.unwrap();
+            drop(dev);
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            let events: Vec<String> = support::key_events()[start..]
+                .iter()
+                .map(|(action, _)| action.clone())
+                .collect();
+            assert_eq!(events, ["KEYDOWN", "KEYUP"]);
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn virtual_remote_secondary() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::Random,
+        CodeSet::Secondary,
+        |dev| async move {
+            let buttons = helpers::button_vec();
+
+            for i in 0..3 {
+                for button in &buttons {
+                    let res = match i {
+                        0 => dev.key_down(*button, None).await,
                         1 => dev.key_up(*button).await,
                         2 => dev.key_press(*button).await,
                         _ => panic!(),
@@ -345,3 +1737,207 @@ async fn virtual_remote_secondary() {
     )
     .await;
 }
+
+#[tokio::test]
+async fn settings_validate() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            let backlight = dev.find_setting_by_cname("backlight").await.unwrap();
+            backlight.validate(75).await.unwrap();
+            assert!(matches!(
+                backlight.validate(150).await,
+                Err(Error::Client(ClientError::WriteSettingsOutsideBounds(
+                    0, 100, 150
+                )))
+            ));
+            assert!(matches!(
+                backlight.validate("Bright".to_string()).await,
+                Err(Error::Client(ClientError::WriteSettingsBadType(_, _)))
+            ));
+
+            // Nothing is written
+            let backlight = dev.find_setting_by_cname("backlight").await.unwrap();
+            assert_eq!(backlight.value::<i32>(), Some(50));
+
+            let color_temp = dev
+                .find_setting_by_cname("color_temperature")
+                .await
+                .unwrap();
+            color_temp.validate("Warm".to_string()).await.unwrap();
+            assert!(matches!(
+                color_temp.validate("Hot".to_string()).await,
+                Err(Error::Client(ClientError::WriteSettingsNotAnElement))
+            ));
+
+            let esn = dev.find_setting_by_cname("esn").await.unwrap();
+            assert!(matches!(
+                esn.validate("ABC".to_string()).await,
+                Err(Error::Client(ClientError::WriteSettingsReadOnly))
+            ));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn reconnect_keeps_session() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            support::pair(&dev).await;
+            dev.warm_up(true).await.unwrap();
+            let token = dev.auth_token().await;
+
+            let moved = dev.reconnect().await.unwrap();
+            assert_eq!(moved.uuid(), dev.uuid());
+            assert_eq!(moved.auth_token().await, token);
+            assert!(moved.verify_auth_token().await.unwrap());
+            moved.current_input().await.unwrap();
+
+            // Prefetched settings are kept and write through the new client
+            let start = support::request_count();
+            let settings = moved.settings().await.unwrap();
+            assert_eq!(support::request_count(), start);
+
+            let list = settings
+                .into_iter()
+                .find(|s| s.setting_type() == SettingType::List)
+                .unwrap();
+            let new_value = list.elements().await.unwrap()[1].clone();
+            list.update(new_value).await.unwrap();
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn remote_recorder() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            let recorded = Arc::new(std::sync::Mutex::new(Vec::new()));
+            dev.set_remote_recorder({
+                let recorded = recorded.clone();
+                move |event, button, at| recorded.lock().unwrap().push((event, button, at))
+            })
+            .await;
+
+            dev.key_press(Button::Menu).await.unwrap();
+            dev.key_down(Button::VolumeUp, None).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            dev.key_up(Button::VolumeUp).await.unwrap();
+
+            dev.clear_remote_recorder().await;
+            dev.key_press(Button::Back).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(50)).await;
+
+            let recorded = recorded.lock().unwrap().clone();
+            assert_eq!(
+                recorded
+                    .iter()
+                    .map(|(event, button, _)| (*event, *button))
+                    .collect::<Vec<_>>(),
+                vec![
+                    (KeyEvent::Press, Button::Menu),
+                    (KeyEvent::Down, Button::VolumeUp),
+                    (KeyEvent::Up, Button::VolumeUp),
+                ]
+            );
+
+            // The hold is kept in the timing
+            assert!(recorded[0].2 <= recorded[1].2);
+            assert!(recorded[2].2.duration_since(recorded[1].2) >= Duration::from_millis(100));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn settings_write_menu_hashed() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            // Nested setting without a hashval of its own, written with its menu's hash
+            let setting = dev.find_setting_by_cname("caption_style").await.unwrap();
+            assert!(setting.is_writable());
+            setting.update("Large".to_string()).await.unwrap();
+
+            let setting = dev.find_setting_by_cname("caption_style").await.unwrap();
+            assert_eq!(setting.value::<String>(), Some("Large".into()));
+
+            // Also when found by walking the tree
+            let setting = dev
+                .all_settings()
+                .await
+                .unwrap()
+                .into_iter()
+                .find(|s| s.cname() == "caption_style")
+                .unwrap();
+            setting.update("High Contrast".to_string()).await.unwrap();
+
+            let setting = dev.find_setting_by_cname("caption_style").await.unwrap();
+            assert_eq!(setting.value::<String>(), Some("High Contrast".into()));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn settings_write_menu_hash_required() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            // Nested setting whose own hashval is taken but ignored, written again with its
+            // menu's hash
+            let setting = dev.find_setting_by_cname("caption_color").await.unwrap();
+            setting.update("Yellow".to_string()).await.unwrap();
+
+            let setting = dev.find_setting_by_cname("caption_color").await.unwrap();
+            assert_eq!(setting.value::<String>(), Some("Yellow".into()));
+
+            // Later writes go through the same way once the device is known to ignore them
+            setting.update("Cyan".to_string()).await.unwrap();
+            let setting = dev.find_setting_by_cname("caption_color").await.unwrap();
+            assert_eq!(setting.value::<String>(), Some("Cyan".into()));
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn settings_write_read_back_once() {
+    Test::simulate(
+        PortOption::Random,
+        DeviceType::TV,
+        CodeSet::Random,
+        |dev| async move {
+            let setting = dev.find_setting_by_cname("hdr_mode").await.unwrap();
+            let elements = setting.elements().await.unwrap();
+
+            // The first write is read back to learn the device takes writes with the setting's
+            // own hash, so later writes are not
+            let start = support::settings_read_count();
+            setting.update(elements[1].clone()).await.unwrap();
+            let first = support::settings_read_count() - start;
+
+            let start = support::settings_read_count();
+            setting.update(elements[2].clone()).await.unwrap();
+            assert_eq!(support::settings_read_count() - start, first - 1);
+
+            let setting = dev.find_setting_by_cname("hdr_mode").await.unwrap();
+            assert_eq!(setting.value::<String>(), Some(elements[2].clone()));
+        },
+    )
+    .await;
+}
@@ -5,7 +5,12 @@ mod macros;
 mod simulated_device;
 
 use simulated_device::SimulatedDevice;
-pub use simulated_device::{expected_slider_info, CodeSet, DeviceType, PortOption, LIST_LEN};
+#[allow(unused_imports)]
+pub use simulated_device::{
+    change_hashvals_on_write, dynamic_elements, expected_slider_info, key_codes, key_events,
+    max_concurrent_requests, request_count, settings_read_count, track_concurrency, CodeSet,
+    DeviceType, PortOption, EXPECTED_ESN, EXPECTED_TIME, LIST_LEN,
+};
 
 use smartcast::{Device, Error};
 
@@ -23,14 +28,14 @@ pub struct Test {
 }
 
 impl Test {
-    pub async fn simulate<F, Fut: 'static>(
+    pub async fn simulate<F, Fut>(
         port_option: PortOption,
         device_type: DeviceType,
         code_set: CodeSet,
         func: F,
     ) where
         F: FnOnce(Device) -> Fut,
-        Fut: Future<Output = ()>,
+        Fut: Future<Output = ()> + 'static,
     {
         let start = Instant::now();
         let timeout = Duration::from_secs(5);
@@ -115,6 +120,63 @@ pub async fn simulate(port: PortOption, device_type: DeviceType, command_set: Co
     device.serve();
 }
 
+/// Pair the client with the simulated device and set its auth token
+pub async fn pair(dev: &Device) {
+    let mut dev = dev.clone();
+    let pairing_data = dev.begin_pair("name", "id").await.unwrap();
+    let token = dev.finish_pair(pairing_data, "0000").await.unwrap();
+    dev.set_auth_token(token).await.unwrap();
+}
+
+/// Begin emulation of a device whose API server starts `delay` after its description server.
+/// Returns once the description server is ready.
+pub async fn simulate_with_api_delay(
+    port: PortOption,
+    device_type: DeviceType,
+    command_set: CodeSet,
+    delay: Duration,
+) {
+    let device = SimulatedDevice::new(port, device_type, command_set);
+    device.serve_with_api_delay(delay);
+    wait_for_description().await;
+}
+
+/// Begin emulation of a device whose device info has an empty settings root. Returns once the
+/// description server is ready.
+pub async fn simulate_without_settings_root(
+    port: PortOption,
+    device_type: DeviceType,
+    command_set: CodeSet,
+) {
+    let device = SimulatedDevice::new(port, device_type, command_set).without_settings_root();
+    device.serve();
+    wait_for_description().await;
+}
+
+/// Begin emulation of a device whose device info has an empty settings root for the first `reads`
+/// reads. Returns once the description server is ready.
+pub async fn simulate_with_late_settings_root(
+    port: PortOption,
+    device_type: DeviceType,
+    command_set: CodeSet,
+    reads: usize,
+) {
+    let device =
+        SimulatedDevice::new(port, device_type, command_set).without_settings_root_for(reads);
+    device.serve();
+    wait_for_description().await;
+}
+
+/// Wait until the simulated device's description server is ready
+async fn wait_for_description() {
+    while reqwest::get("http://127.0.0.1:8008/ssdp/device-desc.xml")
+        .await
+        .is_err()
+    {
+        sleep(Duration::from_millis(10)).await;
+    }
+}
+
 /// This function will return a `Device`. It will continuously try to connect by ip until the simulated servers are ready.
 /// Unexpected errors will panic.
 pub async fn connect_device() -> Device {
@@ -194,6 +256,17 @@ pub mod helpers {
             smartcast::Button::PowerOff,
             smartcast::Button::PowerOn,
             smartcast::Button::PowerToggle,
+            smartcast::Button::Num0,
+            smartcast::Button::Num1,
+            smartcast::Button::Num2,
+            smartcast::Button::Num3,
+            smartcast::Button::Num4,
+            smartcast::Button::Num5,
+            smartcast::Button::Num6,
+            smartcast::Button::Num7,
+            smartcast::Button::Num8,
+            smartcast::Button::Num9,
+            smartcast::Button::Dash,
         ]
     }
 }
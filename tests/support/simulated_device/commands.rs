@@ -1,8 +1,10 @@
-use super::{settings::Setting, Input, Result, SimulatedDevice, State};
+use super::{rand_data, settings::Setting, Input, Result, SimulatedDevice, State};
 
 use rand::Rng;
 use serde_json::Value;
 
+use std::sync::atomic::Ordering;
+
 /// Start pairing command
 pub fn pair_start(mut val: Value, device: SimulatedDevice) -> warp::reply::Json {
     log::info!(target: "test::simulated_device::commands", "PAIR START");
@@ -73,6 +75,13 @@ pub fn pair_finish(mut val: Value, device: SimulatedDevice) -> warp::reply::Json
                     status!(Result::InvalidParameter)
                 } else {
                     *state = State::Ready;
+                    let auth_token = rand_data::string(10);
+                    device
+                        .inner
+                        .auth_tokens
+                        .write()
+                        .unwrap()
+                        .push(auth_token.clone());
                     format!(
                         r#"
                             "ITEM": {{
@@ -80,7 +89,7 @@ pub fn pair_finish(mut val: Value, device: SimulatedDevice) -> warp::reply::Json
                             }},
                             {}
                         "#,
-                        0,
+                        auth_token,
                         status!(Result::Success)
                     )
                 }
@@ -183,8 +192,21 @@ pub fn power_state(device: SimulatedDevice) -> warp::reply::Json {
 }
 
 /// Get current input command
-pub fn current_input(device: SimulatedDevice) -> warp::reply::Json {
+///
+/// A soundbar allows requests without an auth token, a TV requires pairing. An auth token which
+/// was not issued by pairing is rejected.
+pub fn current_input(auth: Option<String>, device: SimulatedDevice) -> warp::reply::Json {
     log::info!(target: "test::simulated_device::commands", "CURRENT INPUT");
+    let authorized = match auth {
+        Some(auth) => device.inner.auth_tokens.read().unwrap().contains(&auth),
+        None => device.inner.settings_root != "tv_settings",
+    };
+    if !authorized {
+        return warp::reply::json::<Value>(
+            &serde_json::from_str(&format!("{{{}}}", status!(Result::RequiresPairing))).unwrap(),
+        );
+    }
+
     let input: &Input = device
         .inner
         .input_list
@@ -296,7 +318,7 @@ pub fn change_input(mut val: Value, device: SimulatedDevice) -> warp::reply::Jso
         device.inner.current_input.write(),
     ) {
         ("MODIFY", Ok(name), Ok(hashval), Ok(mut current_input)) => {
-            if device.inner.input_list.get(&name).is_none() {
+            if !device.inner.input_list.contains_key(&name) {
                 status!(Result::InvalidParameter)
             } else if device
                 .inner
@@ -326,10 +348,12 @@ pub fn change_input(mut val: Value, device: SimulatedDevice) -> warp::reply::Jso
 /// Get device info command
 pub fn device_info(device: SimulatedDevice) -> warp::reply::Json {
     log::info!(target: "test::simulated_device::commands", "DEVICE INFO");
+    // SmartCast is not a hardware input
     let inputs: Vec<String> = device
         .inner
         .input_list
         .keys()
+        .filter(|x| *x != "CAST")
         .map(|x| format!("\"{}\"", x))
         .collect();
 
@@ -339,7 +363,8 @@ pub fn device_info(device: SimulatedDevice) -> warp::reply::Json {
             "ITEMS": [
                 {{
                     "VALUE": {{
-                        "CAST_NAME": "{}",
+                        "BOARD_REVISION": "B2",
+                        "CAST_NAME": "{} Cast",
                         "INPUTS": [{}],
                         "MODEL_NAME": "{}",
                         "SETTINGS_ROOT": "{}",
@@ -353,10 +378,21 @@ pub fn device_info(device: SimulatedDevice) -> warp::reply::Json {
             ],
             {}
         }}"#,
+        // Not the same as the friendly name, like a device renamed in Google Cast
         device.inner.name,
         inputs.join(","),
         device.inner.model,
-        device.inner.settings_root,
+        if device
+            .inner
+            .blank_settings_root_reads
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |reads| reads
+                .checked_sub(1))
+            .is_ok()
+        {
+            ""
+        } else {
+            &device.inner.settings_root
+        },
         status!(Result::Success),
     );
     let res: Value = serde_json::from_str(&res).unwrap();
@@ -364,8 +400,12 @@ pub fn device_info(device: SimulatedDevice) -> warp::reply::Json {
 }
 
 /// Read dynamic settings command
-pub fn read_setting_dynamic(setting: Setting) -> warp::reply::Json {
+pub fn read_setting_dynamic(setting: Setting, hash_only: bool) -> warp::reply::Json {
     log::info!(target: "test::simulated_device::commands", "READ DYNAMIC SETTINGS");
+    if hash_only {
+        return warp::reply::json(&setting.hash_only_value());
+    }
+    super::SETTINGS_READS.fetch_add(1, Ordering::SeqCst);
     warp::reply::json(&setting.dynamic_value())
 }
 
@@ -385,7 +425,15 @@ pub fn write_setting(mut val: Value, setting: Setting) -> warp::reply::Json {
     // Command should not return error for bad input because the api library should handle that.
     // Instead just test command formatting
     let mut res = match (request, hashval, value) {
-        (Ok(request), Ok(hashval), Ok(_)) if request == "MODIFY" && hashval == setting.hashval => {
+        (Ok(request), Ok(hashval), Ok(value))
+            if request == "MODIFY" && setting.accepts_hashval(hashval) =>
+        {
+            if !setting.ignores_hashval(hashval) {
+                setting.set_value(value);
+            }
+            if super::HASHVALS_CHANGE_ON_WRITE.load(Ordering::SeqCst) {
+                setting.menu_written();
+            }
             status!(Result::Success)
         }
         _ => status!(Result::InvalidParameter),
@@ -411,7 +459,19 @@ pub fn virtual_remote(mut val: Value, device: SimulatedDevice) -> warp::reply::J
         match (codeset_num, code, action) {
             (Ok(codeset_num), Ok(code), Ok(action))
                 if ["KEYDOWN", "KEYUP", "KEYPRESS"].contains(&action.as_str())
-                    && codeset.get(&codeset_num).unwrap().contains(&code) => {}
+                    && codeset.get(&codeset_num).unwrap().contains(&code) =>
+            {
+                // Power buttons
+                if codeset_num == 11 && action == "KEYPRESS" {
+                    let mut powered_on = device.inner.powered_on.write().unwrap();
+                    *powered_on = match code {
+                        0 => false,
+                        1 => true,
+                        _ => !*powered_on,
+                    };
+                }
+                super::record_key_event(action, codeset_num, code);
+            }
             _ => {
                 status = status!(Result::InvalidParameter);
                 break;
@@ -423,6 +483,31 @@ pub fn virtual_remote(mut val: Value, device: SimulatedDevice) -> warp::reply::J
     warp::reply::json(&res)
 }
 
+/// Get current app command
+///
+/// Always an app which is not in the catalog
+pub fn current_app() -> warp::reply::Json {
+    log::trace!(target: "test::simulated_device::commands", "CURRENT APP");
+    let res = format!(
+        r#"
+        {{
+            "ITEMS": [{{
+                "NAME": "Current App",
+                "TYPE": "T_APP_CURRENT_V1",
+                "VALUE": {{
+                    "NAME_SPACE": 4,
+                    "APP_ID": "999",
+                    "MESSAGE": "http://unknown.app/launch"
+                }}
+            }}],
+            {}
+        }}"#,
+        status!(Result::Success)
+    );
+    let res: Value = serde_json::from_str(&res).unwrap();
+    warp::reply::json(&res)
+}
+
 // TODO:
 // Get app list command
 // Launch app command
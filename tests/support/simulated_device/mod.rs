@@ -5,7 +5,7 @@ mod settings;
 use super::rand_data;
 
 use inputs::Input;
-pub use settings::{expected_slider_info, LIST_LEN};
+pub use settings::{expected_slider_info, EXPECTED_ESN, EXPECTED_TIME, LIST_LEN};
 
 use http::Response;
 use rand::{
@@ -16,7 +16,92 @@ use serde_json::Value;
 use warp::{filters::BoxedFilter, Filter, Reply};
 
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use std::time::Instant;
+
+/// Number of requests the API server has handled
+static REQUEST_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Dynamic settings reads which were not `HASHONLY`
+static SETTINGS_READS: AtomicUsize = AtomicUsize::new(0);
+
+/// Key actions handled by the virtual remote, with when they arrived
+static KEY_EVENTS: Mutex<Vec<(String, Instant)>> = Mutex::new(Vec::new());
+static KEY_CODES: Mutex<Vec<(u32, u32)>> = Mutex::new(Vec::new());
+
+/// Requests the API server is handling at once, and the most seen at once
+static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+static MAX_IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+
+/// Milliseconds each request is held before it is handled, so overlapping requests can be seen
+static REQUEST_DELAY: AtomicU64 = AtomicU64::new(0);
+
+/// Whether a write changes the hashvals of every setting in its menu, like some firmware does
+static HASHVALS_CHANGE_ON_WRITE: AtomicBool = AtomicBool::new(false);
+
+/// Whether lists have their elements at the dynamic endpoint instead of the static endpoint
+static DYNAMIC_ELEMENTS: AtomicBool = AtomicBool::new(false);
+
+async fn track_in_flight() -> std::result::Result<(), warp::Rejection> {
+    let in_flight = IN_FLIGHT.fetch_add(1, Ordering::SeqCst) + 1;
+    MAX_IN_FLIGHT.fetch_max(in_flight, Ordering::SeqCst);
+    let delay = REQUEST_DELAY.load(Ordering::SeqCst);
+    if delay > 0 {
+        tokio::time::sleep(Duration::from_millis(delay)).await;
+    }
+    IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Hold every request to the API server for `delay` and reset the most requests seen at once
+pub fn track_concurrency(delay: Duration) {
+    REQUEST_DELAY.store(delay.as_millis() as u64, Ordering::SeqCst);
+    MAX_IN_FLIGHT.store(0, Ordering::SeqCst);
+}
+
+/// Get the most requests the API server handled at once since [`track_concurrency()`]
+pub fn max_concurrent_requests() -> usize {
+    MAX_IN_FLIGHT.load(Ordering::SeqCst)
+}
+
+/// Change the hashvals of every setting in a menu when one of them is written, so settings read
+/// before the write can no longer be written without reading them again
+pub fn change_hashvals_on_write(enabled: bool) {
+    HASHVALS_CHANGE_ON_WRITE.store(enabled, Ordering::SeqCst);
+}
+
+/// Give list elements at the dynamic endpoint and leave them out of the static endpoint, like
+/// firmware which only has them at the dynamic endpoint
+pub fn dynamic_elements(enabled: bool) {
+    DYNAMIC_ELEMENTS.store(enabled, Ordering::SeqCst);
+}
+
+fn record_key_event(action: String, codeset: u32, code: u32) {
+    KEY_EVENTS.lock().unwrap().push((action, Instant::now()));
+    KEY_CODES.lock().unwrap().push((codeset, code));
+}
+
+/// Get the key actions handled by the virtual remote since the test binary started
+pub fn key_events() -> Vec<(String, Instant)> {
+    KEY_EVENTS.lock().unwrap().clone()
+}
+
+/// Get the `CODESET` and `CODE` of each key event in [`key_events()`]
+pub fn key_codes() -> Vec<(u32, u32)> {
+    KEY_CODES.lock().unwrap().clone()
+}
+
+/// Get the number of requests handled by the API server since the test binary started
+pub fn request_count() -> usize {
+    REQUEST_COUNT.load(Ordering::SeqCst)
+}
+
+/// Get the number of dynamic settings reads with values since the test binary started
+pub fn settings_read_count() -> usize {
+    SETTINGS_READS.load(Ordering::SeqCst)
+}
 
 /// Result for command response
 enum Result {
@@ -24,17 +109,20 @@ enum Result {
     InvalidParameter,
     ChallengeIncorrect,
     Blocked,
+    RequiresPairing,
+    UriNotFound,
 }
 
-impl ToString for Result {
-    fn to_string(&self) -> String {
+impl std::fmt::Display for Result {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Success => "SUCCESS",
-            Self::InvalidParameter => "INVALID_PARAMETER",
-            Self::Blocked => "BLOCKED",
-            Self::ChallengeIncorrect => "CHALLENGE_INCORRECT",
+            Self::Success => write!(f, "SUCCESS"),
+            Self::InvalidParameter => write!(f, "INVALID_PARAMETER"),
+            Self::Blocked => write!(f, "BLOCKED"),
+            Self::ChallengeIncorrect => write!(f, "CHALLENGE_INCORRECT"),
+            Self::RequiresPairing => write!(f, "REQUIRES_PAIRING"),
+            Self::UriNotFound => write!(f, "URI_NOT_FOUND"),
         }
-        .to_string()
     }
 }
 
@@ -112,6 +200,7 @@ impl CodeSet {
         }
 
         let mut hash = HashMap::new();
+        hash.insert(0, vec![45, 48, 49, 50, 51, 52, 53, 54, 55, 56, 57]);
         hash.insert(2, vec![0, 1, 2, 3]);
         match self {
             Self::Default => {
@@ -185,10 +274,12 @@ impl SimulatedDevice {
                 name,
                 model,
                 settings_root,
+                blank_settings_root_reads: AtomicUsize::new(0),
                 port,
                 uuid,
                 code_set: code_set.hashmap(),
                 state: RwLock::new(State::Ready),
+                auth_tokens: RwLock::new(Vec::new()),
                 powered_on: RwLock::new(false),
                 input_list,
                 current_input: RwLock::new(current_input),
@@ -198,52 +289,103 @@ impl SimulatedDevice {
         }
     }
 
+    /// Leave the settings root out of device info
+    pub fn without_settings_root(self) -> Self {
+        self.without_settings_root_for(usize::MAX)
+    }
+
+    /// Leave the settings root out of the first `reads` reads of device info
+    pub fn without_settings_root_for(self, reads: usize) -> Self {
+        self.inner
+            .blank_settings_root_reads
+            .store(reads, Ordering::SeqCst);
+        self
+    }
+
     pub fn serve(&self) {
+        self.serve_with_api_delay(Duration::from_secs(0));
+    }
+
+    /// Start the API server some time after the description server, like a device which is
+    /// still waking up
+    pub fn serve_with_api_delay(&self, delay: Duration) {
         // Device Description Server
         tokio::spawn(warp::serve(self.description()).run(([127, 0, 0, 1], 8008)));
         log::info!(target: "test::simulated_device::serve", "Starting Description server");
 
         // Device API Server
-        tokio::spawn(
-            warp::serve(self.api())
-                .tls()
-                .key(self.inner.pkey.clone())
-                .cert(self.inner.cert.clone())
-                .run(([127, 0, 0, 1], self.inner.port)),
-        );
-        log::info!(target: "test::simulated_device::serve", "Starting API server");
+        let api = warp::serve(self.api())
+            .tls()
+            .key(self.inner.pkey.clone())
+            .cert(self.inner.cert.clone())
+            .run(([127, 0, 0, 1], self.inner.port));
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            log::info!(target: "test::simulated_device::serve", "Starting API server");
+            api.await
+        });
     }
 
     fn description(&self) -> BoxedFilter<(impl Reply,)> {
-        warp::path("ssdp")
-            .and(warp::path("device-desc.xml"))
-            .and(warp::path::end())
+        // Also served at a path of its own, like a device behind a port forward
+        warp::path!("ssdp" / "device-desc.xml")
+            .or(warp::path!("forwarded" / "desc.xml"))
+            .unify()
             .and(warp::get())
             .map({
                 let desc_xml = device_desc!(self.inner.name, self.inner.model, self.inner.uuid);
                 move || {
                     let desc_xml = desc_xml.clone();
                     Response::builder()
-                        .header("Application-URL", "http//127.0.0.1:8008/apps/")
+                        .header("Application-URL", "http://127.0.0.1:8008/apps/")
                         .header("Content-Length", desc_xml.len())
                         .header("Content-Type", "application/xml")
                         .body(desc_xml)
                         .unwrap()
                 }
             })
+            .or(self.dial())
             .with(warp::log("test::simulated_device::description"))
             .boxed()
     }
 
+    /// DIAL app launch, only some apps are known
+    fn dial(&self) -> BoxedFilter<(impl Reply,)> {
+        warp::path("apps")
+            .and(warp::path::param())
+            .and(warp::path::end())
+            .and(warp::post())
+            .map(|app: String| {
+                let status = match app.as_str() {
+                    "YouTube" | "Netflix" => 201,
+                    _ => 404,
+                };
+                Response::builder()
+                    .status(status)
+                    .body(String::new())
+                    .unwrap()
+            })
+            .boxed()
+    }
+
     fn api(&self) -> BoxedFilter<(impl Reply,)> {
-        self.pairing()
+        let handlers = self
+            .pairing()
             .or(self.power_state())
             .or(self.inputs())
             .or(self.device_info())
             .or(self.settings())
             .or(self.virtual_remote())
-            .or(self.uri_not_found())
+            .or(self.current_app())
+            .or(self.uri_not_found());
+        warp::any()
+            .and_then(track_in_flight)
+            .untuple_one()
+            .and(handlers)
             .with(warp::log("test::simulated_device::api"))
+            .with(warp::log::custom(|_| {
+                REQUEST_COUNT.fetch_add(1, Ordering::SeqCst);
+            }))
             .boxed()
     }
 
@@ -323,9 +465,11 @@ impl SimulatedDevice {
                     )
                     .or(warp::path("current_input")
                         .and(warp::path::end())
-                        .and(warp::get().map({
+                        .and(warp::get().and(warp::header::optional("auth")).map({
                             let device = self.clone();
-                            move || commands::current_input(device.clone())
+                            move |auth: Option<String>| {
+                                commands::current_input(auth, device.clone())
+                            }
                         }))
                         .or(warp::put().and(warp::body::json()).map({
                             let device = self.clone();
@@ -363,6 +507,25 @@ impl SimulatedDevice {
             .boxed()
     }
 
+    /// Current App Command. Soundbars do not run apps so they do not have it.
+    fn current_app(&self) -> BoxedFilter<(impl Reply,)> {
+        let runs_apps = self.inner.settings_root == "tv_settings";
+        warp::path!("app" / "current")
+            .and(
+                warp::get()
+                    .and(warp::any().map(move || runs_apps))
+                    .and_then(|runs_apps: bool| async move {
+                        match runs_apps {
+                            true => Ok(commands::current_app()),
+                            false => Err(warp::reject::not_found()),
+                        }
+                    })
+                    .or(self.expected_get())
+                    .or(self.uri_not_found()),
+            )
+            .boxed()
+    }
+
     /// Read/Write Settings Commands
     fn settings(&self) -> BoxedFilter<(impl Reply,)> {
         warp::path("menu_native")
@@ -390,10 +553,14 @@ struct SimulatedDeviceRef {
     name: String,
     model: String,
     settings_root: String,
+    /// Number of device info reads left which leave out the settings root, like some firmware
+    /// while booting
+    blank_settings_root_reads: AtomicUsize,
     port: u16,
     uuid: String,
     code_set: HashMap<u32, Vec<u32>>,
     state: RwLock<State>,
+    auth_tokens: RwLock<Vec<String>>,
     powered_on: RwLock<bool>,
     input_list: HashMap<String, Input>,
     current_input: RwLock<String>,
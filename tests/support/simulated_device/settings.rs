@@ -4,9 +4,17 @@ use smartcast::SliderInfo;
 
 use rand::Rng;
 use serde_json::{json, Value};
-use warp::{filters::BoxedFilter, Filter, Reply};
+use warp::{filters::BoxedFilter, path::Tail, Filter, Reply};
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, RwLock};
 
 pub const LIST_LEN: usize = 5;
+pub const EXPECTED_ESN: &str = "VZR2QHWKX7PN";
+pub const EXPECTED_USAGE_HOURS: &str = "2,418 Hours";
+pub const EXPECTED_TIME: &str = "2021-06-14 19:32";
+pub const EXPECTED_SIGNAL: &str = "3840x2160@60Hz HDR10";
 
 #[derive(Debug, Clone)]
 pub enum SettingType {
@@ -30,16 +38,15 @@ impl SettingType {
     }
 }
 
-impl ToString for SettingType {
-    fn to_string(&self) -> String {
+impl std::fmt::Display for SettingType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Slider => "T_VALUE_ABS_V1",
-            Self::Value => "T_VALUE_V1",
-            Self::Menu(_) => "T_MENU_V1",
-            Self::List => "T_LIST_V1",
-            Self::XList => "T_LIST_X_V1",
+            Self::Slider => write!(f, "T_VALUE_ABS_V1"),
+            Self::Value => write!(f, "T_VALUE_V1"),
+            Self::Menu(_) => write!(f, "T_MENU_V1"),
+            Self::List => write!(f, "T_LIST_V1"),
+            Self::XList => write!(f, "T_LIST_X_V1"),
         }
-        .into()
     }
 }
 
@@ -48,10 +55,33 @@ pub struct Setting {
     pub name: String,
     pub cname: String,
     pub setting_type: SettingType,
-    pub value: Value,
+    pub value: Arc<RwLock<Value>>,
     pub hidden: bool,
-    pub hashval: u32,
+    /// Whether the device reports the setting as `READONLY`
+    pub readonly: bool,
+    hashval: u32,
+    /// Number of times the setting was written, which its hashval changes with like on real
+    /// firmware
+    writes: Arc<AtomicU32>,
+    /// Number of writes to the menu the setting is in, which its hashval changes with
+    menu_writes: Arc<AtomicU32>,
+    /// Hash of the menu the setting is in, first in the `HASHLIST` of its menu and of itself
+    menu_hashval: u32,
+    /// Whether the setting has no hashval of its own and is written with its menu's hash
+    menu_hashed: bool,
+    /// Whether writes with the setting's own hashval succeed but are ignored, so only writes with
+    /// its menu's hash change it
+    menu_hash_required: bool,
     pub elements: Vec<String>,
+    /// Underlying values of the elements, which only differ from the elements for an `XList`
+    pub values: Vec<String>,
+    pub slider: SliderInfo,
+    /// Whether a `Value` has slider info at its static endpoint. The static endpoint of a `Slider`
+    /// without it is not found.
+    pub static_slider: bool,
+    /// Slider info at the dynamic endpoint, which some firmware has instead of or as well as the
+    /// static slider info
+    pub dynamic_slider: Option<SliderInfo>,
 }
 
 impl Setting {
@@ -75,11 +105,134 @@ impl Setting {
             name: rand_data::string(6),
             cname: setting_type.cname(),
             setting_type,
-            value,
+            value: Arc::new(RwLock::new(value)),
             hidden: false,
+            readonly: false,
             hashval: rng.gen(),
+            writes: Arc::new(AtomicU32::new(0)),
+            menu_writes: Arc::new(AtomicU32::new(0)),
+            menu_hashval: rng.gen(),
+            menu_hashed: false,
+            menu_hash_required: false,
+            values: elements.clone(),
             elements,
+            slider: expected_slider_info(),
+            static_slider: true,
+            dynamic_slider: None,
+        }
+    }
+
+    /// Setting with a known name and cname
+    pub fn named(setting_type: SettingType, name: &str, cname: &str) -> Self {
+        let mut setting = Self::new(setting_type);
+        setting.name = name.into();
+        setting.cname = cname.into();
+        setting
+    }
+
+    /// Set list elements. The current value becomes the first element.
+    pub fn with_elements(mut self, elements: &[&str]) -> Self {
+        self.elements = elements.iter().map(|e| e.to_string()).collect();
+        self.values = self.elements.clone();
+        self.with_value(json!(elements[0]))
+    }
+
+    /// Set `XList` elements as `(label, value)` pairs. The current value becomes the first value.
+    pub fn with_element_pairs(mut self, pairs: &[(&str, &str)]) -> Self {
+        self.elements = pairs.iter().map(|(label, _)| label.to_string()).collect();
+        self.values = pairs.iter().map(|(_, value)| value.to_string()).collect();
+        self.with_value(json!(pairs[0].1))
+    }
+
+    pub fn with_value(self, value: Value) -> Self {
+        *self.value.write().unwrap() = value;
+        self
+    }
+
+    /// `Value` which is not a slider, or `Slider` without a static endpoint
+    pub fn without_slider(mut self) -> Self {
+        self.static_slider = false;
+        self
+    }
+
+    pub fn with_slider(mut self, slider: SliderInfo) -> Self {
+        self.slider = slider;
+        self
+    }
+
+    pub fn with_dynamic_slider(mut self, slider: SliderInfo) -> Self {
+        self.dynamic_slider = Some(slider);
+        self
+    }
+
+    /// Setting the device reports as `READONLY`, like system information
+    pub fn read_only(mut self) -> Self {
+        self.readonly = true;
+        self
+    }
+
+    /// Setting without a `HASHVAL`, like some nested settings, which is written with the hash
+    /// from its menu's `HASHLIST` instead
+    pub fn menu_hashed(mut self) -> Self {
+        self.menu_hashed = true;
+        self
+    }
+
+    /// Setting with a `HASHVAL` of its own, which the device answers writes with successfully but
+    /// only changes when written with the hash from its menu's `HASHLIST`
+    pub fn menu_hash_required(mut self) -> Self {
+        self.menu_hash_required = true;
+        self
+    }
+
+    pub fn value(&self) -> Value {
+        self.value.read().unwrap().clone()
+    }
+
+    pub fn hashval(&self) -> u32 {
+        self.hashval
+            .wrapping_add(self.writes.load(Ordering::SeqCst))
+            .wrapping_add(self.menu_writes.load(Ordering::SeqCst))
+    }
+
+    /// Whether `hashval` is the one the setting is written with. The hashval from before one of
+    /// the setting's own writes is still accepted, so it can be written again without reading it.
+    pub fn accepts_hashval(&self, hashval: u32) -> bool {
+        if self.menu_hashed {
+            return hashval == self.menu_hashval;
         }
+        if self.menu_hash_required && hashval == self.menu_hashval {
+            return true;
+        }
+        let writes = self.writes.load(Ordering::SeqCst);
+        let menu_hashval = self
+            .hashval
+            .wrapping_add(self.menu_writes.load(Ordering::SeqCst));
+        (0..=writes).any(|write| hashval == menu_hashval.wrapping_add(write))
+    }
+
+    /// Whether a write with `hashval` is answered with success without changing the setting
+    pub fn ignores_hashval(&self, hashval: u32) -> bool {
+        self.menu_hash_required && hashval != self.menu_hashval
+    }
+
+    /// `HASHVAL` as reported in dynamic responses
+    fn hashval_json(&self) -> String {
+        if self.menu_hashed {
+            "null".into()
+        } else {
+            self.hashval().to_string()
+        }
+    }
+
+    /// Change the hashval of every setting in the menu
+    pub fn menu_written(&self) {
+        self.menu_writes.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn set_value(&self, value: Value) {
+        *self.value.write().unwrap() = value;
+        self.writes.fetch_add(1, Ordering::SeqCst);
     }
 
     fn dynamic_in_menu(&self) -> String {
@@ -90,13 +243,13 @@ impl Setting {
                     "CNAME": "{}",
                     "HASHVAL": {},
                     "NAME": "{}",
-                    "TYPE": "{}",
+                    "TYPE": "{}"
                 }}
                 "#,
                 self.cname,
-                self.hashval,
+                self.hashval_json(),
                 self.name,
-                self.setting_type.to_string(),
+                self.setting_type,
             ),
             SettingType::XList => format!(
                 r#"
@@ -106,15 +259,17 @@ impl Setting {
                     "HASHVAL": {},
                     "NAME": "{}",
                     "TYPE": "{}",
-                    "VALUE": {}
+                    "VALUE": {},
+                    "VALUES": ["{}"]
                 }}
                 "#,
                 self.cname,
                 self.elements.join("\", \""),
-                self.hashval,
+                self.hashval_json(),
                 self.name,
-                self.setting_type.to_string(),
-                self.value,
+                self.setting_type,
+                self.value(),
+                self.values.join("\", \""),
             ),
             _ => format!(
                 r#"
@@ -122,24 +277,74 @@ impl Setting {
                     "CNAME": "{}",
                     "HASHVAL": {},
                     "NAME": "{}",
+                    "READONLY": "{}",
                     "TYPE": "{}",
                     "VALUE": {}
                 }}
                 "#,
                 self.cname,
-                self.hashval,
+                self.hashval_json(),
                 self.name,
-                self.setting_type.to_string(),
-                self.value,
+                if self.readonly { "TRUE" } else { "FALSE" },
+                self.setting_type,
+                self.value(),
             ),
         }
     }
 
     fn dynamic_as_string(&self) -> String {
         let mut rng = rand::thread_rng();
-        let hashlist: Vec<u32> = vec![rng.gen(), rng.gen()];
+        // A menu's own hash comes first, which is the hash its settings have as menu_hashval
+        let hashlist: Vec<u32> = match self.setting_type {
+            SettingType::Menu(_) => vec![self.hashval, rng.gen()],
+            _ => vec![self.menu_hashval, rng.gen()],
+        };
 
         match self.setting_type.clone() {
+            SettingType::Slider if self.dynamic_slider.is_some() => {
+                let slider = self.dynamic_slider.clone().unwrap();
+                format!(
+                    r#"
+                    {{
+                        "HASHLIST": {:?},
+                        "ITEMS": [
+                        {{
+                            "CENTER": {},
+                            "CNAME": "{}",
+                            "DECMARKER": "{}",
+                            "HASHVAL": {},
+                            "INCMARKER": "{}",
+                            "INCREMENT": {},
+                            "MAXIMUM": {},
+                            "MINIMUM": {},
+                            "NAME": "{}",
+                            "TYPE": "{}",
+                            "VALUE": {}
+                        }}
+                        ],
+                        "PARAMETERS": {{
+                            "FLAT": "TRUE",
+                            "HASHONLY": "FALSE",
+                            "HELPTEXT": "FALSE"
+                        }},
+                        {}
+                    }}
+                    "#,
+                    hashlist,
+                    json!(slider.center),
+                    self.cname,
+                    slider.dec_marker,
+                    self.hashval_json(),
+                    slider.inc_marker,
+                    slider.increment,
+                    slider.max,
+                    slider.min,
+                    self.name,
+                    self.setting_type,
+                    self.value(),
+                    status!(Result::Success),
+                )
+            }
             SettingType::Menu(submenus) => {
                 let items: String = submenus
                     .iter()
@@ -168,10 +373,10 @@ impl Setting {
                     items,
                     self.name,
                     status!(Result::Success),
-                    self.setting_type.to_string(),
+                    self.setting_type,
                 )
             }
-            SettingType::XList => {
+            SettingType::List if super::DYNAMIC_ELEMENTS.load(Ordering::SeqCst) => {
                 format!(
                     r#"
                     {{
@@ -197,10 +402,45 @@ impl Setting {
                     hashlist,
                     self.cname,
                     self.elements,
-                    self.hashval,
+                    self.hashval_json(),
                     self.name,
-                    self.setting_type.to_string(),
-                    self.value,
+                    self.setting_type,
+                    self.value(),
+                    status!(Result::Success),
+                )
+            }
+            SettingType::XList => {
+                format!(
+                    r#"
+                    {{
+                        "HASHLIST": {:?},
+                        "ITEMS": [
+                        {{
+                            "CNAME": "{}",
+                            "ELEMENTS": {:?},
+                            "HASHVAL": {},
+                            "NAME": "{}",
+                            "TYPE": "{}",
+                            "VALUE": {},
+                            "VALUES": {:?}
+                        }}
+                        ],
+                        "PARAMETERS": {{
+                            "FLAT": "TRUE",
+                            "HASHONLY": "FALSE",
+                            "HELPTEXT": "FALSE"
+                        }},
+                        {}
+                    }}
+                    "#,
+                    hashlist,
+                    self.cname,
+                    self.elements,
+                    self.hashval_json(),
+                    self.name,
+                    self.setting_type,
+                    self.value(),
+                    self.values,
                     status!(Result::Success),
                 )
             }
@@ -228,10 +468,10 @@ impl Setting {
                     "#,
                     hashlist,
                     self.cname,
-                    self.hashval,
+                    self.hashval_json(),
                     self.name,
-                    self.setting_type.to_string(),
-                    self.value,
+                    self.setting_type,
+                    self.value(),
                     status!(Result::Success),
                 )
             }
@@ -240,6 +480,35 @@ impl Setting {
 
     fn static_as_string(&self) -> String {
         match self.setting_type {
+            SettingType::List | SettingType::XList
+                if super::DYNAMIC_ELEMENTS.load(Ordering::SeqCst) =>
+            {
+                format!(
+                    r#"
+                    {{
+                        "HASHVAL": {},
+                        "ITEMS": [
+                        {{
+                            "CNAME": "{}",
+                            "NAME": "{}",
+                            "TYPE": "{}"
+                        }}
+                        ],
+                        "PARAMETERS": {{
+                            "FLAT": "TRUE",
+                            "HASHONLY": "FALSE",
+                            "HELPTEXT": "FALSE"
+                        }},
+                        {}
+                    }}
+                    "#,
+                    self.hashval(),
+                    self.cname,
+                    self.name,
+                    self.setting_type,
+                    status!(Result::Success),
+                )
+            }
             SettingType::List | SettingType::XList => {
                 format!(
                     r#"
@@ -261,16 +530,19 @@ impl Setting {
                         {}
                     }}
                     "#,
-                    self.hashval,
+                    self.hashval(),
                     self.cname,
                     self.elements,
                     self.name,
-                    self.setting_type.to_string(),
+                    self.setting_type,
                     status!(Result::Success),
                 )
             }
+            SettingType::Slider if !self.static_slider => {
+                format!("{{{}}}", status!(Result::UriNotFound))
+            }
             SettingType::Slider => {
-                let exp_slider = expected_slider_info();
+                let exp_slider = self.slider.clone();
                 format!(
                     r#"
                     {{
@@ -296,8 +568,8 @@ impl Setting {
                         {}
                     }}
                     "#,
-                    self.hashval,
-                    exp_slider.center.unwrap(),
+                    self.hashval(),
+                    json!(exp_slider.center),
                     self.cname,
                     exp_slider.dec_marker,
                     exp_slider.inc_marker,
@@ -308,8 +580,33 @@ impl Setting {
                     status!(Result::Success),
                 )
             }
+            SettingType::Value if !self.static_slider => format!(
+                r#"
+                {{
+                    "HASHVAL": {},
+                    "ITEMS": [
+                    {{
+                        "CNAME": "{}",
+                        "NAME": "{}",
+                        "TYPE": "{}"
+                    }}
+                    ],
+                    "PARAMETERS": {{
+                        "FLAT": "TRUE",
+                        "HASHONLY": "FALSE",
+                        "HELPTEXT": "FALSE"
+                    }},
+                    {}
+                }}
+                "#,
+                self.hashval(),
+                self.cname,
+                self.name,
+                self.setting_type,
+                status!(Result::Success),
+            ),
             SettingType::Value => {
-                let exp_slider = expected_slider_info();
+                let exp_slider = self.slider.clone();
                 format!(
                     r#"
                     {{
@@ -332,7 +629,7 @@ impl Setting {
                         {}
                     }}
                     "#,
-                    self.hashval,
+                    self.hashval(),
                     self.cname,
                     exp_slider.increment,
                     exp_slider.max,
@@ -354,56 +651,23 @@ impl Setting {
         serde_json::from_str(&strvalue).unwrap()
     }
 
+    /// Dynamic response to a `HASHONLY` read, whose items have no values
+    pub fn hash_only_value(&self) -> Value {
+        let mut value = self.dynamic_value();
+        for item in value["ITEMS"].as_array_mut().unwrap() {
+            item.as_object_mut()
+                .unwrap()
+                .retain(|key, _| ["CNAME", "HASHVAL", "NAME", "TYPE"].contains(&key.as_str()));
+        }
+        value["PARAMETERS"]["HASHONLY"] = json!("TRUE");
+        value
+    }
+
     pub fn static_value(&self) -> Value {
         let strvalue = self.static_as_string();
         // println!("{}", strvalue);
         serde_json::from_str(&strvalue).unwrap()
     }
-
-    fn dynamic_filter_write(&self) -> BoxedFilter<(impl Reply,)> {
-        let cname = warp::path(self.cname.clone());
-        let end = warp::path::end()
-            .and(warp::put())
-            .and(warp::body::json())
-            .map({
-                let setting = self.clone();
-                move |val| commands::write_setting(val, setting.clone())
-            });
-
-        if matches!(self.setting_type, SettingType::Menu(_)) {
-            end.boxed()
-        } else {
-            cname.and(end).boxed()
-        }
-    }
-
-    fn dynamic_filter_read(&self) -> BoxedFilter<(impl Reply,)> {
-        let cname = warp::path(self.cname.clone());
-        let end = warp::path::end().and(warp::get()).map({
-            let setting = self.clone();
-            move || commands::read_setting_dynamic(setting.clone())
-        });
-
-        if matches!(self.setting_type, SettingType::Menu(_)) {
-            end.boxed()
-        } else {
-            cname.and(end).boxed()
-        }
-    }
-
-    fn static_filter(&self) -> BoxedFilter<(impl Reply,)> {
-        let cname = warp::path(self.cname.clone());
-        let end = warp::path::end().and(warp::get()).map({
-            let setting = self.clone();
-            move || commands::read_setting_static(setting.clone())
-        });
-
-        if matches!(self.setting_type, SettingType::Menu(_)) {
-            end.boxed()
-        } else {
-            cname.and(end).boxed()
-        }
-    }
 }
 
 pub fn expected_slider_info() -> SliderInfo {
@@ -417,41 +681,348 @@ pub fn expected_slider_info() -> SliderInfo {
     }
 }
 
+/// Settings tree of the simulated device
+///
+/// Generic settings are placed at the top level alongside menus for the settings which the
+/// library looks up by path. Those menus depend on the type of device.
+pub fn tree(settings_root: &str) -> Setting {
+    let mut top = vec![
+        Setting::new(SettingType::Value),
+        Setting::new(SettingType::Slider),
+        Setting::new(SettingType::List),
+        Setting::new(SettingType::XList),
+    ];
+
+    if settings_root == "tv_settings" {
+        top.push(Setting::named(
+            SettingType::Menu(vec![Setting::named(
+                SettingType::List,
+                "Sleep Timer",
+                "sleep_timer",
+            )
+            .with_elements(&[
+                "Off",
+                "30 Minutes",
+                "60 Minutes",
+                "90 Minutes",
+                "120 Minutes",
+                "180 Minutes",
+            ])]),
+            "Timers",
+            "timers",
+        ));
+        top.push(Setting::named(
+            SettingType::Menu(vec![
+                Setting::named(SettingType::Slider, "Backlight", "backlight")
+                    .with_value(json!(50))
+                    .with_slider(SliderInfo {
+                        dec_marker: "".into(),
+                        inc_marker: "".into(),
+                        increment: 1,
+                        max: 100,
+                        min: 0,
+                        center: Some(50),
+                    }),
+                Setting::named(SettingType::Slider, "Brightness", "brightness")
+                    .with_value(json!(50))
+                    .with_slider(SliderInfo {
+                        dec_marker: "".into(),
+                        inc_marker: "".into(),
+                        increment: 5,
+                        max: 100,
+                        min: 0,
+                        center: Some(50),
+                    }),
+                // Slider info only at the dynamic endpoint
+                Setting::named(SettingType::Slider, "Sharpness", "sharpness")
+                    .with_value(json!(10))
+                    .without_slider()
+                    .with_dynamic_slider(SliderInfo {
+                        dec_marker: "".into(),
+                        inc_marker: "".into(),
+                        increment: 2,
+                        max: 20,
+                        min: 0,
+                        center: Some(10),
+                    }),
+                // Bounds at the static endpoint, the rest at the dynamic endpoint
+                Setting::named(SettingType::Slider, "Tint", "tint")
+                    .with_value(json!(0))
+                    .with_slider(SliderInfo {
+                        dec_marker: "".into(),
+                        inc_marker: "".into(),
+                        increment: 1,
+                        max: 50,
+                        min: -50,
+                        center: None,
+                    })
+                    .with_dynamic_slider(SliderInfo {
+                        dec_marker: "Red".into(),
+                        inc_marker: "Green".into(),
+                        increment: 1,
+                        max: 50,
+                        min: -50,
+                        center: Some(0),
+                    }),
+                Setting::named(SettingType::List, "HDR Mode", "hdr_mode").with_elements(&[
+                    "Auto",
+                    "Off",
+                    "HDR10",
+                    "HDR10+",
+                    "Dolby Vision",
+                    "HLG",
+                ]),
+                Setting::named(SettingType::XList, "Color Temperature", "color_temperature")
+                    .with_element_pairs(&[
+                        ("Cool", "COOL"),
+                        ("Normal", "NORMAL"),
+                        ("Warm", "WARM"),
+                    ]),
+                Setting::named(SettingType::List, "Picture Size", "picture_size").with_elements(&[
+                    "Normal",
+                    "Stretch",
+                    "Panoramic",
+                    "Wide",
+                    "Zoom",
+                ]),
+                Setting::named(
+                    SettingType::Menu(vec![
+                        Setting::named(SettingType::List, "Local Dimming", "local_dimming")
+                            .with_elements(&["Off", "Low", "Medium", "High"]),
+                        Setting::named(SettingType::List, "Motion Control", "motion_control")
+                            .with_elements(&["Off", "Low", "Medium", "High"]),
+                        Setting::named(SettingType::List, "Forced Resolution", "forced_resolution")
+                            .with_elements(&["Auto", "720p", "1080p", "2160p"]),
+                    ]),
+                    "Advanced Picture",
+                    "advanced_picture",
+                ),
+            ]),
+            "Picture",
+            "picture",
+        ));
+        top.push(Setting::named(
+            SettingType::Menu(vec![
+                Setting::named(SettingType::Value, "Current Channel", "current_channel")
+                    .with_value(json!("4.1"))
+                    .without_slider(),
+                Setting::named(SettingType::List, "Channel List", "channel_list")
+                    .with_elements(&["2.1", "4.1", "4.2", "7.1"]),
+            ]),
+            "Channels",
+            "channels",
+        ));
+        top.push(Setting::named(
+            SettingType::Menu(vec![
+                Setting::named(SettingType::List, "Country", "country").with_elements(&[
+                    "United States",
+                    "Canada",
+                    "Mexico",
+                ]),
+                Setting::named(
+                    SettingType::Menu(vec![Setting::named(
+                        SettingType::List,
+                        "CEC Function",
+                        "cec_function",
+                    )
+                    .with_elements(&["Disabled", "Enabled"])]),
+                    "CEC",
+                    "cec",
+                ),
+                Setting::named(
+                    SettingType::Menu(vec![Setting::named(
+                        SettingType::Menu(vec![
+                            Setting::named(SettingType::Value, "ESN", "esn")
+                                .with_value(json!(EXPECTED_ESN))
+                                .without_slider()
+                                .read_only(),
+                            Setting::named(SettingType::Value, "Panel Hours", "panel_hours")
+                                .with_value(json!(EXPECTED_USAGE_HOURS))
+                                .without_slider()
+                                .read_only(),
+                            Setting::named(SettingType::Value, "Resolution", "resolution")
+                                .with_value(json!(EXPECTED_SIGNAL))
+                                .without_slider()
+                                .read_only(),
+                        ]),
+                        "ULI Information",
+                        "uli_information",
+                    )]),
+                    "System Information",
+                    "system_information",
+                ),
+                Setting::named(
+                    SettingType::Menu(vec![
+                        Setting::named(SettingType::Value, "Current Time", "current_time")
+                            .with_value(json!(EXPECTED_TIME))
+                            .without_slider()
+                            .read_only(),
+                        Setting::named(SettingType::List, "Time Zone", "time_zone")
+                            .with_elements(&["Pacific", "Mountain", "Central", "Eastern"]),
+                    ]),
+                    "Time",
+                    "time",
+                ),
+                Setting::named(
+                    SettingType::Menu(vec![Setting::named(
+                        SettingType::List,
+                        "Eco Mode",
+                        "eco_mode",
+                    )
+                    .with_elements(&["Standard", "Off", "Maximum"])]),
+                    "Energy Saving",
+                    "energy_saving",
+                ),
+                Setting::named(SettingType::List, "Screensaver", "screensaver")
+                    .with_elements(&["Off", "Ambient", "Art", "Photos"]),
+                Setting::named(
+                    SettingType::Menu(vec![Setting::named(
+                        SettingType::Menu(vec![
+                            Setting::named(SettingType::List, "Caption Style", "caption_style")
+                                .with_elements(&["Default", "Large", "High Contrast"])
+                                .menu_hashed(),
+                            Setting::named(SettingType::List, "Caption Color", "caption_color")
+                                .with_elements(&["White", "Yellow", "Cyan"])
+                                .menu_hash_required(),
+                        ]),
+                        "Closed Captions",
+                        "closed_captions",
+                    )]),
+                    "Accessibility",
+                    "accessibility",
+                ),
+                Setting::named(
+                    SettingType::Menu(vec![Setting::named(
+                        SettingType::List,
+                        "Store Demo",
+                        "store_demo",
+                    )
+                    .with_elements(&["Off", "On"])]),
+                    "Reset & Admin",
+                    "reset_and_admin",
+                ),
+                Setting::named(
+                    SettingType::Menu(vec![Setting::named(
+                        SettingType::Value,
+                        "Wi-Fi Password",
+                        "wifi_password",
+                    )
+                    .with_value(json!("hunter22"))
+                    .without_slider()]),
+                    "Network",
+                    "network",
+                ),
+            ]),
+            "System",
+            "system",
+        ));
+    }
+
+    // Volume scales differ by model, the simulated soundbar uses a coarser one
+    let volume_max = if settings_root == "tv_settings" {
+        100
+    } else {
+        31
+    };
+    let mut audio = vec![
+        Setting::named(SettingType::Slider, "Volume", "volume")
+            .with_value(json!(0))
+            .with_slider(SliderInfo {
+                dec_marker: "".into(),
+                inc_marker: "".into(),
+                increment: 1,
+                max: volume_max,
+                min: 0,
+                center: Some(0),
+            }),
+        Setting::named(SettingType::List, "Mute", "mute").with_elements(&["Off", "On"]),
+    ];
+
+    if settings_root == "tv_settings" {
+        audio.push(Setting::named(SettingType::List, "ARC", "arc").with_elements(&["Off", "On"]));
+        audio.push(
+            Setting::named(SettingType::List, "Audio Output", "audio_output").with_elements(&[
+                "TV Speakers",
+                "ARC",
+                "Optical",
+            ]),
+        );
+        audio.push(
+            Setting::named(SettingType::List, "Audio Language", "audio_language")
+                .with_elements(&["English", "Spanish", "French"]),
+        );
+    } else {
+        top.push(Setting::named(
+            SettingType::Menu(vec![
+                Setting::named(SettingType::List, "CEC", "cec").with_elements(&["Off", "On"])
+            ]),
+            "System",
+            "system",
+        ));
+    }
+
+    top.push(Setting::named(SettingType::Menu(audio), "Audio", "audio"));
+
+    Setting::new(SettingType::Menu(top))
+}
+
+/// Map of every setting in the tree by its path relative to the settings root
+fn flatten(path: String, mut setting: Setting, map: &mut HashMap<String, Setting>) {
+    if let SettingType::Menu(children) = &mut setting.setting_type {
+        let menu_writes = Arc::new(AtomicU32::new(0));
+        for child in children.iter_mut() {
+            child.menu_writes = menu_writes.clone();
+            child.menu_hashval = setting.hashval;
+        }
+        for child in children.iter() {
+            let child_path = if path.is_empty() {
+                child.cname.clone()
+            } else {
+                format!("{}/{}", path, child.cname)
+            };
+            flatten(child_path, child.clone(), map);
+        }
+    }
+    map.insert(path, setting);
+}
+
 pub fn generate(settings_root: String) -> BoxedFilter<(impl Reply,)> {
-    let value_setting = Setting::new(SettingType::Value);
-    let slider_setting = Setting::new(SettingType::Slider);
-    let list_setting = Setting::new(SettingType::List);
-    let x_list_setting = Setting::new(SettingType::XList);
-    let menu_setting = Setting::new(SettingType::Menu(vec![
-        value_setting.clone(),
-        slider_setting.clone(),
-        list_setting.clone(),
-        x_list_setting.clone(),
-    ]));
+    let mut settings = HashMap::new();
+    flatten(String::new(), tree(&settings_root), &mut settings);
+    let settings = Arc::new(settings);
 
-    warp::path("dynamic")
-        .and(warp::path(settings_root.clone()))
+    let lookup = {
+        let settings = settings.clone();
+        warp::path::tail().and_then(move |tail: Tail| {
+            let setting = settings.get(tail.as_str()).cloned();
+            async move { setting.ok_or_else(warp::reject::not_found) }
+        })
+    };
+
+    let dynamic_read = warp::get()
+        .and(lookup.clone())
         .and(
-            menu_setting
-                .dynamic_filter_read()
-                .or(value_setting.dynamic_filter_read())
-                .or(slider_setting.dynamic_filter_read())
-                .or(list_setting.dynamic_filter_read())
-                .or(x_list_setting.dynamic_filter_read())
-                .or(menu_setting
-                    .dynamic_filter_write()
-                    .or(value_setting.dynamic_filter_write())
-                    .or(slider_setting.dynamic_filter_write())
-                    .or(list_setting.dynamic_filter_write())
-                    .or(x_list_setting.dynamic_filter_write())),
+            warp::query::<HashMap<String, String>>()
+                .map(|query: HashMap<String, String>| {
+                    query
+                        .get("hashonly")
+                        .is_some_and(|hash_only| hash_only.eq_ignore_ascii_case("true"))
+                })
+                .or(warp::any().map(|| false))
+                .unify(),
         )
-        .or(warp::path("static").and(warp::path(settings_root)).and(
-            menu_setting
-                .static_filter()
-                .or(value_setting.static_filter())
-                .or(slider_setting.static_filter())
-                .or(list_setting.static_filter())
-                .or(x_list_setting.static_filter()),
-        ))
+        .map(commands::read_setting_dynamic);
+    let dynamic_write = warp::put()
+        .and(lookup.clone())
+        .and(warp::body::json())
+        .map(|setting, val| commands::write_setting(val, setting));
+    let static_read = warp::get().and(lookup).map(commands::read_setting_static);
+
+    warp::path("dynamic")
+        .and(warp::path(settings_root.clone()))
+        .and(dynamic_read.or(dynamic_write))
+        .or(warp::path("static")
+            .and(warp::path(settings_root))
+            .and(static_read))
         .boxed()
 }